@@ -0,0 +1,267 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use am03127_client::{ClientError, Panel as Backend};
+use am03127_commands::{page::Page, schedule::Schedule};
+use anyhow::{Context, Result, bail};
+use console::style;
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+use crate::{
+    config::Panel,
+    console::{print_line, print_title},
+};
+
+/// Version of the backup file format, raised on incompatible changes
+const BACKUP_VERSION: u32 = 1;
+
+/// Pages and schedules of panels, stored as TOML if the file name ends in
+/// `.toml` and as JSON otherwise.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Backup {
+    version: u32,
+    /// When the backup was taken (RFC 3339)
+    created: String,
+    panels: BTreeMap<String, PanelContent>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PanelContent {
+    #[serde(default)]
+    pages: Vec<Page>,
+    #[serde(default)]
+    schedules: Vec<Schedule>,
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "toml")
+}
+
+impl Backup {
+    /// Reads a backup file, rejecting files of a newer format version.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read backup file '{}'", path.display()))?;
+        let backup: Backup = if is_toml(path) {
+            toml::from_str(&text).map_err(anyhow::Error::from)
+        } else {
+            serde_json::from_str(&text).map_err(anyhow::Error::from)
+        }
+        .with_context(|| format!("invalid backup file '{}'", path.display()))?;
+
+        if backup.version > BACKUP_VERSION {
+            bail!(
+                "backup file '{}' has format version {}, this CLI supports up to {BACKUP_VERSION}",
+                path.display(),
+                backup.version
+            );
+        }
+        Ok(backup)
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let text = if is_toml(path) {
+            toml::to_string_pretty(self)?
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+        fs::write(path, text)
+            .with_context(|| format!("failed to write backup file '{}'", path.display()))
+    }
+
+    /// Returns the names of the panels in the backup.
+    pub fn panel_names(&self) -> Vec<String> {
+        self.panels.keys().cloned().collect()
+    }
+}
+
+/// Pages and schedules sent to or deleted from a panel
+#[derive(Debug, Default, Serialize)]
+struct Restored {
+    pages: String,
+    schedules: String,
+    deleted_pages: String,
+    deleted_schedules: String,
+}
+
+impl Restored {
+    fn describe(&self, dry_run: bool) -> String {
+        let (sent, deleted) = if dry_run {
+            ("would send", "would delete")
+        } else {
+            ("sent", "deleted")
+        };
+        let list = |ids: &str| {
+            if ids.is_empty() {
+                String::from("-")
+            } else {
+                ids.to_string()
+            }
+        };
+        let mut description = format!(
+            "{sent} pages {} and schedules {}",
+            list(&self.pages),
+            list(&self.schedules)
+        );
+        if !self.deleted_pages.is_empty() || !self.deleted_schedules.is_empty() {
+            description.push_str(&format!(
+                ", {}",
+                style(format!(
+                    "{deleted} pages {} and schedules {}",
+                    list(&self.deleted_pages),
+                    list(&self.deleted_schedules)
+                ))
+                .yellow()
+            ));
+        }
+        description
+    }
+}
+
+/// Sends the content to a panel. With `wipe`, pages and schedules which are
+/// not part of the content are deleted first.
+async fn restore_panel(
+    client: &dyn Backend,
+    content: &PanelContent,
+    wipe: bool,
+    dry_run: bool,
+) -> Result<Restored, ClientError> {
+    let mut restored = Restored {
+        pages: content.pages.iter().map(|page| page.id).collect(),
+        schedules: content
+            .schedules
+            .iter()
+            .map(|schedule| schedule.id)
+            .collect(),
+        ..Default::default()
+    };
+
+    if wipe {
+        restored.deleted_schedules = client
+            .get_schedules()
+            .await?
+            .iter()
+            .map(|schedule| schedule.id)
+            .filter(|id| !restored.schedules.contains(*id))
+            .collect();
+        restored.deleted_pages = client
+            .get_pages()
+            .await?
+            .iter()
+            .map(|page| page.id)
+            .filter(|id| !restored.pages.contains(*id))
+            .collect();
+        if !dry_run {
+            // Schedules go first, as they refer to pages.
+            for id in restored.deleted_schedules.chars() {
+                client.delete_schedule(id).await?;
+            }
+            for id in restored.deleted_pages.chars() {
+                client.delete_page(id).await?;
+            }
+        }
+    }
+
+    if !dry_run {
+        if !content.pages.is_empty() {
+            client.set_pages(&content.pages).await?;
+        }
+        if !content.schedules.is_empty() {
+            client.set_schedules(&content.schedules).await?;
+        }
+    }
+    Ok(restored)
+}
+
+/// Saves the pages and schedules of the panels to `path`. Panels failing to
+/// answer are left out of the file.
+pub async fn backup(panels: &[&Panel], path: &Path) -> Result<()> {
+    print_title(&format!("Backing up panels to '{}'", path.display()));
+
+    let report = super::run_with_spinners(
+        panels,
+        |_, client| async move {
+            Ok(PanelContent {
+                pages: client.get_pages().await?,
+                schedules: client.get_schedules().await?,
+            })
+        },
+        |result| match result {
+            Ok(content) => format!(
+                "{} pages, {} schedules",
+                content.pages.len(),
+                content.schedules.len()
+            ),
+            Err(e) => e.to_string(),
+        },
+    )
+    .await;
+
+    let panels: BTreeMap<String, PanelContent> = report
+        .outcomes
+        .iter()
+        .filter_map(|outcome| Some((outcome.name.clone(), outcome.result.clone().ok()?)))
+        .collect();
+    if !panels.is_empty() {
+        let created = OffsetDateTime::now_local()
+            .unwrap_or_else(|_| OffsetDateTime::now_utc())
+            .replace_nanosecond(0)?
+            .format(&Rfc3339)?;
+        Backup {
+            version: BACKUP_VERSION,
+            created,
+            panels,
+        }
+        .save(path)?;
+    }
+
+    super::finish(&report, "one or more panels could not be backed up")
+}
+
+/// Sends the content of `backup` to the panels, each getting the content
+/// stored under its name, or under `from` if given.
+pub async fn restore(
+    panels: &[&Panel],
+    backup: &Backup,
+    from: Option<&str>,
+    wipe: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let mut contents: HashMap<String, PanelContent> = HashMap::new();
+    for panel in panels {
+        let source = from.unwrap_or(&panel.name);
+        let Some(content) = backup.panels.get(source) else {
+            bail!("the backup has no content for panel '{source}'");
+        };
+        contents.insert(panel.name.clone(), content.clone());
+    }
+
+    let mut title = format!("Restoring backup from {} to panels", backup.created);
+    if dry_run {
+        title.push_str(" (dry run)");
+    }
+    print_title(&title);
+
+    let report = super::run_with_spinners(
+        panels,
+        |name, client| {
+            let content = contents[name].clone();
+            async move { restore_panel(client.as_ref(), &content, wipe, dry_run).await }
+        },
+        |result| match result {
+            Ok(restored) => restored.describe(dry_run),
+            Err(e) => e.to_string(),
+        },
+    )
+    .await;
+
+    if dry_run && report.is_success() {
+        print_line(style("dry run, nothing was changed").dim());
+    }
+    super::finish(&report, "one or more panels could not be restored")
+}
@@ -0,0 +1,126 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use console::style;
+use serde_json::json;
+
+use crate::{
+    config::{self, Config, NewPanel},
+    console::{is_json, print_json, print_line, print_title},
+};
+
+/// Config file written by `config init`, documenting every setting
+const TEMPLATE: &str = r#"# AM03127 panel configuration
+
+# Connection settings of all panels, each panel can override them in its own
+# `client` table. AM03127_CONNECT_TIMEOUT, AM03127_TIMEOUT,
+# AM03127_OTA_TIMEOUT, AM03127_RETRIES and AM03127_BACKOFF override both.
+#   connect_timeout = 5.0
+#   timeout = 10.0
+#   ota_timeout = 120.0
+#   retries = 2
+#   backoff = 0.5
+[client]
+
+# Page effects used by `page` if none are given, per panel in `page`
+#   leading = "scroll_left"
+#   lagging = "hold"
+#   waiting_time = 4
+[page]
+
+# Groups of panels, given by names or globs
+#   floor2 = ["lobby", "meeting-*"]
+[groups]
+
+# Add panels with `am03127-cli config add <name> <address>` or
+# `am03127-cli discover --write`, or by hand:
+#
+# [[panels]]
+# name = "lobby"
+# address = "192.168.1.50"
+# port = 80
+# transport = "http"       # http, serial (address is the serial port) or mock
+# panel_id = 1             # ID the panel answers to on a serial line
+# model = "AM03127 7x80"
+# tags = ["indoor"]
+"#;
+
+pub fn init(path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        bail!(
+            "'{}' already exists, pass --force to replace it",
+            path.display()
+        );
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create '{}'", dir.display()))?;
+    }
+    fs::write(path, TEMPLATE).with_context(|| format!("failed to write '{}'", path.display()))?;
+    print_line(format!("{} created '{}'", style("✓").green(), path.display()));
+    if is_json() {
+        print_json(&json!({ "ok": true, "path": path, "created": true }));
+    }
+    Ok(())
+}
+
+pub fn add(path: &Path, panel: NewPanel) -> Result<()> {
+    let name = panel.name.clone();
+    config::add_panel(path, panel)?;
+    print_line(format!(
+        "{} added panel '{name}' to '{}'",
+        style("+").green(),
+        path.display()
+    ));
+    if is_json() {
+        print_json(&json!({ "ok": true, "path": path, "added": name }));
+    }
+    Ok(())
+}
+
+pub fn remove(path: &Path, name: &str) -> Result<()> {
+    config::remove_panel(path, name)?;
+    print_line(format!(
+        "{} removed panel '{name}' from '{}'",
+        style("-").red(),
+        path.display()
+    ));
+    if is_json() {
+        print_json(&json!({ "ok": true, "path": path, "removed": name }));
+    }
+    Ok(())
+}
+
+pub fn validate(path: &Path) -> Result<()> {
+    print_title(&format!("Validating '{}'", path.display()));
+    let config = Config::from_file(path)?;
+
+    let transports = config
+        .panels
+        .iter()
+        .filter(|panel| panel.transport != config::Transport::Http)
+        .count();
+    if is_json() {
+        print_json(&json!({
+            "ok": true,
+            "path": path,
+            "panels": config.panels.len(),
+            "groups": config.groups.len(),
+        }));
+        return Ok(());
+    }
+    println!(
+        "{} {} panels ({} not over HTTP), {} groups",
+        style("✓").green(),
+        config.panels.len(),
+        transports,
+        config.groups.len()
+    );
+    if config.panels.is_empty() {
+        println!(
+            "{}",
+            style("no panels configured yet, add them with `config add` or `discover --write`")
+                .yellow()
+        );
+    }
+    Ok(())
+}
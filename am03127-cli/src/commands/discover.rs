@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use am03127_client::{DiscoveredPanel, Discovery};
+use anyhow::{Context, Result};
+use console::style;
+use serde_json::json;
+
+use crate::{
+    config::{self, ConfigChange},
+    console::{SpinnerGroup, is_json, print_json, print_title},
+};
+
+pub async fn run(discovery: Discovery, config_path: &Path, write: bool) -> Result<()> {
+    print_title("Discovering panels");
+
+    let spinner = SpinnerGroup::new().add("waiting for answers");
+    let panels = discovery.run().await;
+    spinner.finish_and_clear();
+    let panels = panels.context("failed to send the discovery probe")?;
+
+    if is_json() {
+        return print_discovered(&panels, config_path, write);
+    }
+    if panels.is_empty() {
+        println!("{}", style("no panels found").yellow());
+        return Ok(());
+    }
+
+    let name_width = panels.iter().map(|p| p.name.len()).max().unwrap_or(0);
+    let url_width = panels.iter().map(|p| p.url().len()).max().unwrap_or(0);
+    for panel in &panels {
+        println!(
+            "{:<name_width$}  {:<url_width$}  {}",
+            panel.name,
+            style(panel.url()).dim(),
+            style(&panel.version).cyan(),
+        );
+    }
+
+    if !write {
+        return Ok(());
+    }
+
+    let changes = config::add_discovered(config_path, &panels)?;
+    println!();
+    print_title(&format!("Updating '{}'", config_path.display()));
+    for (name, change) in changes {
+        match change {
+            ConfigChange::Added => {
+                println!("{} {name:<name_width$}  added", style("+").green())
+            }
+            ConfigChange::Moved { from } => println!(
+                "{} {name:<name_width$}  moved from {}",
+                style("~").yellow(),
+                style(from).dim()
+            ),
+            ConfigChange::Unchanged => {
+                println!("{} {name:<name_width$}  unchanged", style("=").dim())
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the discovered panels and the changes to the config file as JSON.
+fn print_discovered(panels: &[DiscoveredPanel], config_path: &Path, write: bool) -> Result<()> {
+    let changes = if write && !panels.is_empty() {
+        config::add_discovered(config_path, panels)?
+    } else {
+        Vec::new()
+    };
+    let panels: Vec<_> = panels
+        .iter()
+        .enumerate()
+        .map(|(index, panel)| {
+            let change = changes.get(index).map(|(name, change)| match change {
+                ConfigChange::Added => json!({ "change": "added", "name": name }),
+                ConfigChange::Moved { from } => {
+                    json!({ "change": "moved", "name": name, "from": from })
+                }
+                ConfigChange::Unchanged => json!({ "change": "unchanged", "name": name }),
+            });
+            json!({
+                "name": panel.name,
+                "version": panel.version,
+                "address": panel.address,
+                "port": panel.port,
+                "url": panel.url(),
+                "config": change,
+            })
+        })
+        .collect();
+    print_json(&json!({ "ok": true, "panels": panels }));
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use am03127_commands::page::{Lagging, Leading, Page, WaitingModeAndSpeed, WaitingTime};
+use am03127_image::{Dithering, Options};
+use anyhow::Result;
+
+use crate::{config::Panel, console::print_title};
+
+pub fn parse_dithering(s: &str) -> Result<Dithering, String> {
+    match s {
+        "threshold" => Ok(Dithering::Threshold),
+        "floyd_steinberg" => Ok(Dithering::FloydSteinberg),
+        "ordered" => Ok(Dithering::Ordered),
+        _ => Err(format!("unknown dithering '{s}'")),
+    }
+}
+
+/// Sends the image as graphic blocks and a page showing them, unless they
+/// would overflow the panel memory.
+pub async fn run(
+    panels: &[&Panel],
+    image_path: &Path,
+    id: char,
+    options: Options,
+    force: bool,
+) -> Result<()> {
+    super::page::check_id(id)?;
+
+    let converted = am03127_image::open(image_path, &options)?;
+    let page = Page::new(
+        id,
+        converted.lines.concat(),
+        Leading::Immediate,
+        Lagging::Hold,
+        WaitingModeAndSpeed::default(),
+        WaitingTime::new(0),
+    );
+
+    print_title(&format!(
+        "Sending '{}' as {} graphic block(s) on page '{id}' to panels",
+        image_path.display(),
+        converted.blocks.len(),
+    ));
+
+    let pages = panels
+        .iter()
+        .map(|panel| (panel.name.clone(), page.clone()))
+        .collect();
+    super::page::send(panels, &pages, &converted.blocks, force).await
+}
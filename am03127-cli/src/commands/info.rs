@@ -1,26 +1,52 @@
 use std::{fs, path::Path};
 
+use am03127_client::AppDescriptor;
 use anyhow::{Context, Result};
 use console::style;
+use serde_json::json;
 
-use super::FirmwareBuildInfo;
-use crate::console::print_title;
+use crate::console::{is_json, print_json, print_title};
+
+/// Formats an eFuse block revision stored as major * 100 + minor.
+fn efuse_revision(revision: u16) -> String {
+    format!("v{}.{}", revision / 100, revision % 100)
+}
 
 pub fn run(firmware_path: &Path) -> Result<()> {
     let firmware = fs::read(firmware_path)
         .with_context(|| format!("failed to read '{}'", firmware_path.display()))?;
 
-    match FirmwareBuildInfo::parse(&firmware) {
+    match AppDescriptor::parse(&firmware) {
+        Some(info) if is_json() => print_json(&json!({ "ok": true, "firmware": info })),
         Some(info) => {
             print_title(&format!(
                 "Firmware: {} {}",
-                style(&info.git_hash).cyan(),
+                style(&info.version).cyan(),
                 style(format!("{} {}", info.build_date, info.build_time)).dim(),
             ));
+            let max_revision = match info.max_efuse_revision {
+                u16::MAX => String::from("any"),
+                revision => efuse_revision(revision),
+            };
+            let fields = [
+                ("Project", info.project_name.clone()),
+                ("ESP-IDF", info.idf_version.clone()),
+                ("Secure version", info.secure_version.to_string()),
+                (
+                    "eFuse revision",
+                    format!(
+                        "{} to {max_revision}",
+                        efuse_revision(info.min_efuse_revision)
+                    ),
+                ),
+            ];
+            for (name, value) in fields {
+                println!("  {}  {value}", style(format!("{name:<14}")).dim());
+            }
         }
         None => {
             anyhow::bail!(
-                "file '{}' is too short to contain firmware header",
+                "file '{}' does not contain a firmware header",
                 firmware_path.display()
             );
         }
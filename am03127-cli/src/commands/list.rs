@@ -1,13 +1,54 @@
-use crate::{config::Panel, console::print_title};
+use console::style;
+use serde_json::json;
+
+use crate::{
+    config::Config,
+    console::{is_json, print_json, print_title},
+};
+
+pub fn run(config: &Config) {
+    let panels = &config.panels;
+    if is_json() {
+        let panels: Vec<_> = panels
+            .iter()
+            .map(|panel| {
+                json!({
+                    "name": panel.name,
+                    "address": panel.address,
+                    "transport": panel.transport,
+                    "url": panel.url(),
+                    "model": panel.model,
+                    "groups": config.groups_of(panel),
+                    "tags": panel.tags,
+                })
+            })
+            .collect();
+        print_json(&json!({ "ok": true, "panels": panels }));
+        return;
+    }
 
-pub fn run(panels: &[Panel]) {
     print_title("Available Panels");
     let label_width = panels.iter().map(|p| p.name.len()).max().unwrap_or(0);
+    let address_width = panels.iter().map(|p| p.address.len()).max().unwrap_or(0);
+    let model_width = panels
+        .iter()
+        .map(|p| p.model.as_deref().map_or(0, str::len))
+        .max()
+        .unwrap_or(0);
     for panel in panels {
-        println!(
-            "{:<label_width$}  {}",
+        let labels: Vec<String> = config
+            .groups_of(panel)
+            .into_iter()
+            .map(|group| format!("@{group}"))
+            .chain(panel.tags.iter().map(|tag| format!("#{tag}")))
+            .collect();
+        let line = format!(
+            "{:<label_width$}  {}  {:<model_width$}  {}",
             panel.name,
-            console::style(&panel.address).dim()
+            style(format!("{:<address_width$}", panel.address)).dim(),
+            panel.model.as_deref().unwrap_or_default(),
+            style(labels.join(" ")).cyan()
         );
+        println!("{}", line.trim_end());
     }
 }
@@ -1,60 +1,206 @@
+pub mod backup;
 pub mod clock;
+pub mod config;
+pub mod discover;
+pub mod image;
 pub mod info;
 pub mod list;
 pub mod open;
 pub mod page;
+pub mod plan;
+pub mod schedule;
 pub mod status;
 pub mod update;
+pub mod versions;
 
-use std::path::PathBuf;
+use std::{collections::HashMap, future::Future, net::Ipv4Addr, path::PathBuf, sync::Arc};
 
-use clap::{Parser, Subcommand};
+use am03127_client::{ClientError, Fleet, FleetReport, Panel as Backend};
+use anyhow::{Context, Result};
+use clap::{Args as ClapArgs, Parser, Subcommand};
+use console::style;
+use serde::Serialize;
 
-use crate::config::Panel;
+use crate::{
+    config::{Config, Panel, Transport},
+    console::{OutputFormat, SpinnerGroup, is_json, print_json},
+};
 
-const FW_GIT_HASH_OFFSET: usize = 0x30;
-const FW_GIT_HASH_LEN: usize = 16;
-const FW_BUILD_TIME_OFFSET: usize = 0x70;
-const FW_BUILD_TIME_LEN: usize = 16;
-const FW_BUILD_DATE_OFFSET: usize = 0x80;
-const FW_BUILD_DATE_LEN: usize = 16;
-const FW_HEADER_MIN_LEN: usize = FW_BUILD_DATE_OFFSET + FW_BUILD_DATE_LEN;
+/// Panels a command targets, all panels if none are selected
+#[derive(ClapArgs)]
+pub struct Selection {
+    /// Panels to target, comma-separated names or globs like "floor2-*"
+    #[arg(short, long, value_delimiter = ',')]
+    pub panels: Vec<String>,
+    /// Groups of panels from the config to target, comma-separated
+    #[arg(short, long, value_delimiter = ',')]
+    pub group: Vec<String>,
+    /// Panels with any of these tags to target, comma-separated
+    #[arg(short, long, value_delimiter = ',')]
+    pub tag: Vec<String>,
+}
+
+impl Selection {
+    pub fn is_empty(&self) -> bool {
+        self.panels.is_empty() && self.group.is_empty() && self.tag.is_empty()
+    }
 
-pub struct FirmwareBuildInfo {
-    pub git_hash: String,
-    pub build_date: String,
-    pub build_time: String,
+    /// Returns the selected panels of the config.
+    pub fn resolve<'a>(&self, config: &'a Config) -> Result<Vec<&'a Panel>> {
+        config.select(&self.panels, &self.group, &self.tag)
+    }
 }
 
-impl FirmwareBuildInfo {
-    pub fn parse(data: &[u8]) -> Option<Self> {
-        if data.len() < FW_HEADER_MIN_LEN {
-            return None;
+pub fn label_width(panels: &[&Panel]) -> usize {
+    panels.iter().map(|p| p.name.len()).max().unwrap_or(0)
+}
+
+/// Builds a fleet of the panels. Panels failing to connect stay in it and
+/// fail every operation.
+pub fn fleet(panels: &[&Panel]) -> Fleet {
+    let mut fleet = Fleet::new();
+    for panel in panels {
+        match panel.connect() {
+            Ok(client) => fleet.add_boxed(&panel.name, client),
+            Err(e) => fleet.add_unavailable(&panel.name, e),
         }
-        Some(Self {
-            git_hash: Self::read_cstr(data, FW_GIT_HASH_OFFSET, FW_GIT_HASH_LEN),
-            build_date: Self::read_cstr(data, FW_BUILD_DATE_OFFSET, FW_BUILD_DATE_LEN),
-            build_time: Self::read_cstr(data, FW_BUILD_TIME_OFFSET, FW_BUILD_TIME_LEN),
+    }
+    fleet
+}
+
+/// Runs `op` on all panels with a spinner per panel, which is replaced by
+/// the line `describe` returns for the panel's result once it is done.
+pub async fn run_with_spinners<T, F, Fut>(
+    panels: &[&Panel],
+    op: F,
+    describe: impl Fn(&Result<T, ClientError>) -> String,
+) -> FleetReport<T>
+where
+    F: Fn(&str, Arc<dyn Backend>) -> Fut,
+    Fut: Future<Output = Result<T, ClientError>> + Send + 'static,
+    T: Send + 'static,
+{
+    let label_width = label_width(panels);
+    let spinners = SpinnerGroup::new();
+    let bars: HashMap<&str, _> = panels
+        .iter()
+        .map(|panel| (panel.name.as_str(), spinners.add(&panel.name)))
+        .collect();
+
+    fleet(panels)
+        .run_with(op, |outcome| {
+            let mark = match outcome.result {
+                Ok(_) => style("✓").green(),
+                Err(_) => style("✗").red(),
+            };
+            SpinnerGroup::finish(
+                &bars[outcome.name.as_str()],
+                format!(
+                    "{mark} {:<label_width$}  {}",
+                    outcome.name,
+                    describe(&outcome.result)
+                ),
+            );
         })
+        .await
+}
+
+/// Fails with the error of the first failed panel of `report`.
+pub fn check<T>(report: &FleetReport<T>, context: &'static str) -> Result<()> {
+    match report.first_error() {
+        Some(e) => Err(e.clone()).context(context),
+        None => Ok(()),
+    }
+}
+
+/// Prints `report` in JSON mode, then fails like [`check`].
+pub fn finish<T: Serialize>(report: &FleetReport<T>, context: &'static str) -> Result<()> {
+    print_report(report);
+    check(report, context)
+}
+
+/// Returns why a panel request failed, telling apart the same cases as the
+/// exit codes.
+pub fn error_kind(error: &ClientError) -> &'static str {
+    match error {
+        e if e.is_offline() => "offline",
+        e if e.is_not_found() => "not_found",
+        e if e.is_bad_request() || matches!(e, ClientError::Nack) => "rejected",
+        e if e.is_server_error() => "panel_failure",
+        _ => "error",
     }
+}
+
+#[derive(Serialize)]
+pub struct ErrorJson {
+    pub kind: &'static str,
+    pub message: String,
+}
 
-    fn read_cstr(data: &[u8], offset: usize, max_len: usize) -> String {
-        let slice = &data[offset..(offset + max_len).min(data.len())];
-        let end = slice.iter().position(|&b| b == 0).unwrap_or(slice.len());
-        String::from_utf8_lossy(&slice[..end]).into_owned()
+impl From<&ClientError> for ErrorJson {
+    fn from(error: &ClientError) -> Self {
+        ErrorJson {
+            kind: error_kind(error),
+            message: error.to_string(),
+        }
     }
 }
 
-pub fn label_width(panels: &[&Panel]) -> usize {
-    panels.iter().map(|p| p.name.len()).max().unwrap_or(0)
+#[derive(Serialize)]
+struct OutcomeJson<'a> {
+    panel: &'a str,
+    ok: bool,
+    elapsed_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorJson>,
+}
+
+/// Prints the result of every panel as a JSON document in JSON mode. Results
+/// without content, as of deleting a page, are left out.
+pub fn print_report<T: Serialize>(report: &FleetReport<T>) {
+    if !is_json() {
+        return;
+    }
+    let panels: Vec<OutcomeJson> = report
+        .outcomes
+        .iter()
+        .map(|outcome| OutcomeJson {
+            panel: &outcome.name,
+            ok: outcome.result.is_ok(),
+            elapsed_ms: outcome.elapsed.as_millis(),
+            result: outcome
+                .result
+                .as_ref()
+                .ok()
+                .and_then(|result| serde_json::to_value(result).ok())
+                .filter(|result| !result.is_null()),
+            error: outcome.result.as_ref().err().map(ErrorJson::from),
+        })
+        .collect();
+    print_json(&serde_json::json!({
+        "ok": report.is_success(),
+        "panels": panels,
+    }));
 }
 
 #[derive(Parser)]
-#[command(about = "AM03127 panel controller CLI")]
+#[command(
+    about = "AM03127 panel controller CLI",
+    after_help = "Exit codes: 1 error, 3 panel offline, 4 not found, 5 rejected by panel, 6 panel failure, 7 plan found changes, 8 panels not up to date"
+)]
 pub struct Args {
-    /// Path to the TOML config file
-    #[arg(short, long, default_value = "panel-config.toml")]
-    pub config: PathBuf,
+    /// Path to the TOML config file [default: ./panel-config.toml, else
+    /// am03127/config.toml in the XDG config directories]
+    #[arg(short, long, env = "AM03127_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Use the config file am03127/<PROFILE>.toml in the XDG config directories
+    #[arg(long, env = "AM03127_PROFILE", conflicts_with = "config")]
+    pub profile: Option<String>,
+    /// How results are printed, JSON for scripts
+    #[arg(short, long, value_enum, default_value_t, env = "AM03127_OUTPUT")]
+    pub output: OutputFormat,
 
     #[command(subcommand)]
     pub command: Commands,
@@ -64,20 +210,52 @@ pub struct Args {
 pub enum Commands {
     /// Check connectivity status of all panels
     Status {
-        /// Panels to target, comma-separated (default: all)
-        #[arg(short, long, value_delimiter = ',')]
-        panels: Vec<String>,
+        #[command(flatten)]
+        select: Selection,
     },
     /// Upload a firmware binary to panels via OTA
     Update {
         /// Path to the firmware .bin file
         firmware: PathBuf,
-        /// Panels to target, comma-separated (default: all)
-        #[arg(short, long, value_delimiter = ',')]
-        panels: Vec<String>,
+        /// Update a canary first, then the others in batches, each panel
+        /// having to come back with the new firmware. Skips panels already
+        /// running it and stops at the first failure
+        #[arg(long)]
+        rollout: bool,
+        /// Panel updated first in a rollout [default: the first outdated panel]
+        #[arg(long, requires = "rollout")]
+        canary: Option<String>,
+        /// Panels updated at the same time after the canary
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+        batch_size: u16,
+        /// Seconds a panel may take to report the new firmware after the upload
+        #[arg(long, default_value_t = 120.0)]
+        health_timeout: f64,
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Create, edit and check the config file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
     },
     /// List all known panels
     List {},
+    /// Find panels on the local network
+    Discover {
+        /// Seconds to wait for panels to answer
+        #[arg(long, default_value_t = 2.0)]
+        timeout: f64,
+        /// Add the panels found to the config file, updating changed addresses
+        #[arg(long)]
+        write: bool,
+        /// UDP port the panels listen on for discovery probes
+        #[arg(long, default_value_t = am03127_commands::discovery::DISCOVERY_PORT)]
+        port: u16,
+        /// Address of the interface to send the multicast probe from
+        #[arg(long)]
+        interface: Option<Ipv4Addr>,
+    },
 
     /// Open a panel's address in the browser
     Open {
@@ -89,29 +267,239 @@ pub enum Commands {
         /// Path to the firmware .bin file
         firmware: PathBuf,
     },
+    /// Compare the firmware of panels with a firmware image or a minimum build
+    Versions {
+        /// Firmware .bin file the panels should run, or a newer build
+        #[arg(required_unless_present = "minimum", conflicts_with = "minimum")]
+        firmware: Option<PathBuf>,
+        /// Oldest accepted build as YYYY-MM-DD [HH:MM]
+        #[arg(long)]
+        minimum: Option<String>,
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Watch panels live and edit and send pages in a terminal dashboard
+    Tui {
+        /// Seconds between two refreshes of all panels
+        #[arg(long, default_value_t = 5.0)]
+        interval: f64,
+        #[command(flatten)]
+        select: Selection,
+    },
     /// Sync the clock on panels to the current local time
     Clock {
-        /// Panels to target, comma-separated (default: all)
-        #[arg(short, long, value_delimiter = ',')]
-        panels: Vec<String>,
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Send a page to panels, or list, show, delete and copy pages
+    Page(PageArgs),
+    /// Manage the schedules deciding when pages are shown
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommands,
     },
-    /// Send a page to panels
-    Page {
+    /// Save the pages and schedules of panels to a JSON or TOML file
+    Backup {
+        /// File to write, TOML if it ends in .toml, JSON otherwise
+        file: PathBuf,
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Send the pages and schedules of a backup file back to panels, by
+    /// default to all panels in the backup
+    Restore {
+        /// Backup file written by `backup`
+        file: PathBuf,
+        /// Restore this panel's content to all targeted panels, e.g. onto a
+        /// replaced controller
+        #[arg(long)]
+        from: Option<String>,
+        /// Delete pages and schedules missing from the backup
+        #[arg(long)]
+        wipe: bool,
+        /// Show what would change without sending anything
+        #[arg(long)]
+        dry_run: bool,
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Show how panels differ from a content file
+    Plan {
+        /// TOML file describing the pages, schedules and clock policy of panels
+        file: PathBuf,
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Bring panels to the content of a content file, deleting what it does not list
+    Apply {
+        /// TOML file describing the pages, schedules and clock policy of panels
+        file: PathBuf,
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Convert an image into graphic blocks and show it on panels
+    Image {
+        /// Path to the PNG, BMP or GIF file
+        image: PathBuf,
+        /// Page slot (A-Z) showing the image
+        #[arg(long, default_value_t = 'A')]
+        page: char,
+        /// First graphic page (A-P) storing the image
+        #[arg(long, default_value_t = 'A')]
+        graphic_page: char,
+        /// Width in pixels [default: derived from the aspect ratio]
+        #[arg(long)]
+        width: Option<u32>,
+        /// Color reduction (threshold, floyd_steinberg, ordered)
+        #[arg(long, default_value = "threshold")]
+        dithering: String,
+        /// Channel value (0-255) from which a LED is turned on
+        #[arg(long, default_value_t = 128)]
+        threshold: u8,
+        /// Send the image even if it would overflow the panel memory
+        #[arg(long)]
+        force: bool,
+        #[command(flatten)]
+        select: Selection,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Write a config file documenting all settings
+    Init {
+        /// Replace an existing config file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Add a panel to the config file
+    Add {
+        /// Name to select the panel by
+        name: String,
+        /// Host name or IP of the controller, or the serial port
+        address: String,
+        /// Port of the controller's HTTP server [default: 80]
+        #[arg(long)]
+        port: Option<u16>,
+        /// How the panel is reached [default: http]
+        #[arg(long, value_enum)]
+        transport: Option<Transport>,
+        /// ID the panel answers to on a serial line [default: 1]
+        #[arg(long)]
+        panel_id: Option<u8>,
+        /// Model of the sign, for reference only
+        #[arg(long)]
+        model: Option<String>,
+        /// Tags to select the panel by, comma-separated
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+    },
+    /// Remove a panel from the config file and its groups
+    Remove {
+        /// Name of the panel
+        name: String,
+    },
+    /// Check the config file and the AM03127_* environment variables
+    Validate {},
+}
+
+#[derive(ClapArgs)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct PageArgs {
+    #[command(subcommand)]
+    pub command: Option<PageCommands>,
+    /// Page slot (A-Z)
+    #[arg(required = true)]
+    pub id: Option<char>,
+    /// Text content to display
+    #[arg(required = true)]
+    pub message: Option<String>,
+    /// Entry effect [default: from the config, else immediate] (e.g. scroll_left, scroll_right, snow, random, …)
+    #[arg(long)]
+    pub leading: Option<String>,
+    /// Exit effect [default: from the config, else hold] (e.g. scroll_left, hold, immediate, …)
+    #[arg(long)]
+    pub lagging: Option<String>,
+    /// Waiting time step (0 = 0.5 s, otherwise seconds up to 25) [default: from the config, else 0]
+    #[arg(long)]
+    pub waiting_time: Option<u8>,
+    /// Send the page even if it would overflow the panel memory
+    #[arg(long)]
+    pub force: bool,
+    #[command(flatten)]
+    pub select: Selection,
+}
+
+#[derive(Subcommand)]
+pub enum PageCommands {
+    /// Show all pages of panels, marking pages which differ between them
+    List {
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Show a page of panels
+    Get {
         /// Page slot (A-Z)
         id: char,
-        /// Text content to display
-        message: String,
-        /// Entry effect [default: immediate] (e.g. scroll_left, scroll_right, snow, random, …)
-        #[arg(long, default_value = "immediate")]
-        leading: String,
-        /// Exit effect [default: hold] (e.g. scroll_left, hold, immediate, …)
-        #[arg(long, default_value = "hold")]
-        lagging: String,
-        /// Waiting time in half-second steps (0 = 0.5 s, 25 = 13 s)
-        #[arg(long, default_value_t = 0)]
-        waiting_time: u8,
-        /// Panels to target, comma-separated (default: all)
-        #[arg(short, long, value_delimiter = ',')]
-        panels: Vec<String>,
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Delete a page from panels
+    Delete {
+        /// Page slot (A-Z)
+        id: char,
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Copy a page from one panel to others, by default to all but the source
+    Copy {
+        /// Page slot (A-Z)
+        id: char,
+        /// Panel to read the page from
+        #[arg(long)]
+        from: String,
+        /// Page slot to store the copy in [default: the same slot]
+        #[arg(long)]
+        to_id: Option<char>,
+        /// Send the page even if it would overflow the panel memory
+        #[arg(long)]
+        force: bool,
+        #[command(flatten)]
+        select: Selection,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ScheduleCommands {
+    /// Send a schedule to panels
+    Set {
+        /// Schedule slot (A-E)
+        id: char,
+        /// When the pages are shown: "always", "daily 08:00-18:00" (repeated every
+        /// day) or "2026-12-24 18:00..2026-12-26"
+        period: String,
+        /// Pages to show in turn, e.g. "ABC" or "A,B,C" (at most 31)
+        pages: String,
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Show all schedules of panels
+    List {
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Show a schedule of panels
+    Get {
+        /// Schedule slot (A-E)
+        id: char,
+        #[command(flatten)]
+        select: Selection,
+    },
+    /// Delete a schedule from panels
+    Delete {
+        /// Schedule slot (A-E)
+        id: char,
+        #[command(flatten)]
+        select: Selection,
     },
 }
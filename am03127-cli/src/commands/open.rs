@@ -1,16 +1,27 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use serde_json::json;
 
-use crate::config::Panel;
+use crate::{
+    config::{Panel, Transport},
+    console::{is_json, print_json, print_line},
+};
 
 pub fn run(panel: &Panel) -> Result<()> {
-    let url = format!("http://{}", panel.address);
+    if panel.transport != Transport::Http {
+        bail!("panel '{}' has no web interface", panel.name);
+    }
 
-    println!("Opening {} ...", url);
+    let url = panel.url();
+
+    print_line(format!("Opening {} ...", url));
 
     std::process::Command::new("xdg-open")
         .arg(&url)
         .status()
         .with_context(|| format!("failed to open '{url}'"))?;
+    if is_json() {
+        print_json(&json!({ "ok": true, "url": url }));
+    }
 
     Ok(())
 }
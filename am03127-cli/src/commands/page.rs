@@ -1,13 +1,35 @@
-use am03127_client::PanelClient;
-use am03127_commands::page::{Lagging, Leading, Page, WaitingModeAndSpeed, WaitingTime};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
+
+use am03127_client::{ClientError, FleetReport, Panel as Backend};
+use am03127_commands::{
+    graphic::GraphicBlock,
+    is_page_id_valid,
+    markup::plain_text,
+    memory::{MemoryBudget, MemoryEstimate, MemoryStatus},
+    page::{Lagging, Leading, Page, WaitingModeAndSpeed, WaitingTime},
+};
 use anyhow::{Context, Result, bail};
 use console::style;
+use serde::Serialize;
 
 use crate::{
-    config::Panel,
-    console::{SpinnerGroup, print_title},
+    config::{Panel, Transport},
+    console::{is_json, print_title},
 };
 
+/// Longest message shown in page tables before it is cut off
+const MESSAGE_WIDTH: usize = 40;
+
+pub fn check_id(id: char) -> Result<()> {
+    if !is_page_id_valid(id) {
+        bail!("page id must be an uppercase ASCII letter (A-Z), got '{id}'");
+    }
+    Ok(())
+}
+
 pub fn parse_leading(s: &str) -> Result<Leading, String> {
     serde_json::from_value(serde_json::Value::String(s.to_string()))
         .map_err(|_| format!("unknown leading effect '{s}'"))
@@ -18,65 +40,355 @@ pub fn parse_lagging(s: &str) -> Result<Lagging, String> {
         .map_err(|_| format!("unknown lagging effect '{s}'"))
 }
 
+/// Estimates the panel memory used once `page` replaces the page with the same
+/// ID and `graphic_blocks` graphic blocks are stored along with it.
+async fn estimate_memory(
+    client: &dyn Backend,
+    page: &Page,
+    graphic_blocks: usize,
+) -> Result<MemoryEstimate, ClientError> {
+    let mut pages = client.get_pages().await?;
+    pages.retain(|stored| stored.id != page.id);
+    pages.push(page.clone());
+    let schedules = client.get_schedules().await?;
+    Ok(MemoryBudget::default().estimate(&pages, &schedules, graphic_blocks))
+}
+
+/// Page sent to a panel
+#[derive(Serialize)]
+struct Sent {
+    page: char,
+    /// Memory estimate if the panel memory is almost full
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memory_warning: Option<MemoryEstimate>,
+}
+
+/// Sends the graphic blocks `page` shows and then `page`, unless they would
+/// overflow the panel memory. Returns the memory estimate if the panel memory
+/// is almost full.
+pub async fn send_page(
+    client: &dyn Backend,
+    page: &Page,
+    graphics: &[GraphicBlock],
+    force: bool,
+) -> Result<Option<MemoryEstimate>, ClientError> {
+    let mut warning = None;
+    if !force {
+        let estimate = estimate_memory(client, page, graphics.len()).await?;
+        match estimate.status() {
+            MemoryStatus::Ok => {}
+            MemoryStatus::Warning => warning = Some(estimate),
+            MemoryStatus::Overflow => {
+                return Err(ClientError::BadRequest(format!(
+                    "panel memory would overflow ({estimate})"
+                )));
+            }
+        }
+    }
+    for block in graphics {
+        client.set_graphic(block).await?;
+    }
+    client.set_page(page).await?;
+    Ok(warning)
+}
+
+/// Sends the page of every panel in `pages` and the graphic blocks it shows
+/// with a spinner per panel.
+pub async fn send(
+    panels: &[&Panel],
+    pages: &HashMap<String, Page>,
+    graphics: &[GraphicBlock],
+    force: bool,
+) -> Result<()> {
+    // Panels on a serial port can not be read back to estimate their memory.
+    let serial: HashSet<&str> = panels
+        .iter()
+        .filter(|panel| panel.transport == Transport::Serial)
+        .map(|panel| panel.name.as_str())
+        .collect();
+
+    let graphics = Arc::new(graphics.to_vec());
+    let report = super::run_with_spinners(
+        panels,
+        |name, client| {
+            let page = pages[name].clone();
+            let graphics = graphics.clone();
+            let force = force || serial.contains(name);
+            async move {
+                let memory_warning = send_page(client.as_ref(), &page, &graphics, force).await?;
+                Ok(Sent {
+                    page: page.id,
+                    memory_warning,
+                })
+            }
+        },
+        |result| match result {
+            Ok(Sent {
+                page,
+                memory_warning: None,
+            }) => format!("page '{page}' sent"),
+            Ok(Sent {
+                page,
+                memory_warning: Some(estimate),
+            }) => format!(
+                "page '{page}' sent, {}",
+                style(format!("panel memory almost full ({estimate})")).yellow(),
+            ),
+            Err(e) => e.to_string(),
+        },
+    )
+    .await;
+
+    super::finish(&report, "one or more panels failed to receive the page")
+}
+
+/// Sends a page to the panels. Effects and waiting time not given are taken
+/// from each panel's page defaults.
 pub async fn run(
     panels: &[&Panel],
     id: char,
     message: String,
-    leading: Leading,
-    lagging: Lagging,
-    waiting_time: u8,
+    leading: Option<Leading>,
+    lagging: Option<Lagging>,
+    waiting_time: Option<u8>,
+    force: bool,
 ) -> Result<()> {
-    if !id.is_ascii_uppercase() {
-        bail!("page id must be an uppercase ASCII letter (A-Z), got '{id}'");
-    }
+    check_id(id)?;
 
-    let page = Page::new(
-        id,
-        message,
-        leading,
-        lagging,
-        WaitingModeAndSpeed::default(),
-        WaitingTime::new(waiting_time),
-    );
+    let pages = panels
+        .iter()
+        .map(|panel| {
+            let defaults = &panel.page;
+            let page = Page::new(
+                id,
+                message.clone(),
+                leading.clone().or_else(|| defaults.leading.clone()).unwrap_or_default(),
+                lagging.clone().or_else(|| defaults.lagging.clone()).unwrap_or_default(),
+                WaitingModeAndSpeed::default(),
+                WaitingTime::new(waiting_time.or(defaults.waiting_time).unwrap_or(0)),
+            );
+            (panel.name.clone(), page)
+        })
+        .collect();
 
     print_title(&format!("Sending page '{id}' to panels"));
+    send(panels, &pages, &[], force).await
+}
+
+/// Returns the name an effect or waiting mode is given on the command line.
+pub fn effect_name(effect: &impl Serialize) -> String {
+    serde_json::to_value(effect)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+/// Returns how long a page is shown, e.g. `0.5s` or `25s`.
+pub fn waiting_time_name(waiting_time: &WaitingTime) -> String {
+    format!("{}s", waiting_time.duration().as_secs_f32())
+}
+
+/// Returns the plain text of a message, cut off after [`MESSAGE_WIDTH`] characters.
+pub fn short_message(message: &str) -> String {
+    let text = plain_text(message);
+    if text.chars().count() <= MESSAGE_WIDTH {
+        return text;
+    }
+    let mut short: String = text.chars().take(MESSAGE_WIDTH - 1).collect();
+    short.push('…');
+    short
+}
 
+/// Returns the IDs of pages which are not the same on all panels that
+/// answered, including pages only some of them have.
+fn differing_pages(report: &FleetReport<Vec<Page>>) -> BTreeSet<char> {
+    let lists: Vec<&Vec<Page>> = report
+        .outcomes
+        .iter()
+        .filter_map(|outcome| outcome.result.as_ref().ok())
+        .collect();
+    if lists.len() < 2 {
+        return BTreeSet::new();
+    }
+
+    let find = |pages: &[Page], id: char| pages.iter().find(|page| page.id == id).cloned();
+    lists
+        .iter()
+        .flat_map(|pages| pages.iter().map(|page| page.id))
+        .collect::<BTreeSet<char>>()
+        .into_iter()
+        .filter(|&id| {
+            let first = find(lists[0], id);
+            lists.iter().any(|pages| find(pages, id) != first)
+        })
+        .collect()
+}
+
+/// Prints the pages of every panel as a table below the panel's name, marking
+/// pages which differ between panels. Prints nothing in JSON mode.
+fn print_pages(panels: &[&Panel], report: &FleetReport<Vec<Page>>) {
+    if is_json() {
+        return;
+    }
     let label_width = super::label_width(panels);
-    let spinners = SpinnerGroup::new();
-    let mut set = tokio::task::JoinSet::new();
-
-    for panel in panels {
-        let client = PanelClient::new(&panel.address);
-        let name = panel.name.clone();
-        let pb = spinners.add(&name);
-        let page = page.clone();
-        set.spawn(async move {
-            let result = client.set_page(&page).await;
-            (name, result, pb)
-        });
+    let differing = differing_pages(report);
+
+    let header = ["ID", "Leading", "Lagging", "Waiting", "Time", "Message"];
+    let rows = |pages: &[Page]| -> Vec<[String; 6]> {
+        pages
+            .iter()
+            .map(|page| {
+                [
+                    page.id.to_string(),
+                    effect_name(&page.leading),
+                    effect_name(&page.lagging),
+                    effect_name(&page.waiting_mode_and_speed),
+                    waiting_time_name(&page.waiting_time),
+                    short_message(&page.message),
+                ]
+            })
+            .collect()
+    };
+
+    // Columns line up across panels to make differences easy to spot.
+    let mut widths = header.map(str::len);
+    for outcome in &report.outcomes {
+        if let Ok(pages) = &outcome.result {
+            for row in rows(pages) {
+                for (width, cell) in widths.iter_mut().zip(&row) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+        }
     }
+    let format_row = |cells: [&str; 6]| {
+        let [id, leading, lagging, waiting, time, message] = cells;
+        let [w_id, w_leading, w_lagging, w_waiting, w_time, _] = widths;
+        format!(
+            "{id:<w_id$}  {leading:<w_leading$}  {lagging:<w_lagging$}  {waiting:<w_waiting$}  {time:>w_time$}  {message}"
+        )
+    };
 
-    let mut success = true;
-    while let Some(res) = set.join_next().await {
-        let (name, result, pb) = res.context("panel task panicked")?;
-        match result {
-            Ok(_) => pb.finish_with_message(format!(
-                "{} {name:<label_width$}  page '{id}' sent",
-                style("✓").green(),
-            )),
+    for outcome in &report.outcomes {
+        let name = &outcome.name;
+        let pages = match &outcome.result {
+            Ok(pages) => pages,
             Err(e) => {
-                pb.finish_with_message(format!(
-                    "{} {name:<label_width$}  {e}",
-                    style("✗").red()
-                ));
-                success = false;
+                println!("{} {name:<label_width$}  {e}", style("✗").red());
+                continue;
+            }
+        };
+        if pages.is_empty() {
+            println!(
+                "{} {name:<label_width$}  {}",
+                style("✓").green(),
+                style("no pages").dim()
+            );
+            continue;
+        }
+
+        println!("{} {name}", style("✓").green());
+        println!("    {}", style(format_row(header)).dim());
+        for (page, row) in pages.iter().zip(rows(pages)) {
+            let line = format_row([&row[0], &row[1], &row[2], &row[3], &row[4], &row[5]]);
+            if differing.contains(&page.id) {
+                println!("  {} {}", style("≠").yellow(), style(line).yellow());
+            } else {
+                println!("    {line}");
             }
         }
     }
 
-    if !success {
-        anyhow::bail!("one or more panels failed to receive the page");
+    if !differing.is_empty() {
+        let ids: Vec<String> = differing.iter().map(char::to_string).collect();
+        println!();
+        println!(
+            "{}",
+            style(format!(
+                "≠ pages differing between panels: {}",
+                ids.join(", ")
+            ))
+            .yellow()
+        );
     }
+}
 
-    Ok(())
+pub async fn list(panels: &[&Panel]) -> Result<()> {
+    print_title("Pages on panels");
+
+    let report = super::fleet(panels)
+        .run(|_, client| async move { client.get_pages().await })
+        .await;
+    print_pages(panels, &report);
+
+    super::finish(&report, "one or more panels failed to list pages")
+}
+
+pub async fn get(panels: &[&Panel], id: char) -> Result<()> {
+    check_id(id)?;
+    print_title(&format!("Page '{id}' on panels"));
+
+    let report = super::fleet(panels)
+        .run(|_, client| async move {
+            match client.get_page(id).await? {
+                Some(page) => Ok(vec![page]),
+                None => Err(ClientError::NotFound(String::from("Page not found"))),
+            }
+        })
+        .await;
+    print_pages(panels, &report);
+
+    super::finish(&report, "one or more panels failed to return the page")
+}
+
+pub async fn delete(panels: &[&Panel], id: char) -> Result<()> {
+    check_id(id)?;
+    print_title(&format!("Deleting page '{id}' from panels"));
+
+    let report = super::run_with_spinners(
+        panels,
+        |_, client| async move { client.delete_page(id).await },
+        |result| match result {
+            Ok(()) => format!("page '{id}' deleted"),
+            Err(e) => e.to_string(),
+        },
+    )
+    .await;
+
+    super::finish(&report, "one or more panels failed to delete the page")
+}
+
+/// Copies page `id` of `source` to the panels, stored as page `to_id` if given.
+pub async fn copy(
+    source: &Panel,
+    panels: &[&Panel],
+    id: char,
+    to_id: Option<char>,
+    force: bool,
+) -> Result<()> {
+    check_id(id)?;
+    let to_id = to_id.unwrap_or(id);
+    check_id(to_id)?;
+    if panels.is_empty() {
+        bail!("no panels to copy the page to");
+    }
+
+    let context = || format!("failed to read page '{id}' from '{}'", source.name);
+    let client = source.connect().with_context(context)?;
+    let mut page = client
+        .get_page(id)
+        .await
+        .and_then(|page| page.ok_or_else(|| ClientError::NotFound(String::from("Page not found"))))
+        .with_context(context)?;
+    page.id = to_id;
+
+    print_title(&format!(
+        "Copying page '{id}' of '{}' to page '{to_id}' on panels",
+        source.name
+    ));
+    let pages = panels
+        .iter()
+        .map(|panel| (panel.name.clone(), page.clone()))
+        .collect();
+    send(panels, &pages, &[], force).await
 }
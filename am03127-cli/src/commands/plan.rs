@@ -0,0 +1,527 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    sync::Arc,
+};
+
+use am03127_client::{ClientError, FleetReport, Panel as Backend};
+use am03127_commands::{
+    page::{Lagging, Leading, Page, WaitingModeAndSpeed, WaitingTime},
+    realtime_clock::DateTime,
+    schedule::Schedule,
+};
+use anyhow::{Context, Result, bail};
+use console::style;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use super::{
+    clock::datetime_from_offset,
+    page::{effect_name, short_message},
+    schedule::format_date_time,
+};
+use crate::{
+    config::{Config, Panel},
+    console::{is_json, print_line, print_title},
+};
+
+/// Exit code of `plan` if panels differ from the content file
+pub const EXIT_CHANGES: i32 = 7;
+
+/// Whether `apply` sets the clock of panels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ClockPolicy {
+    /// Leave the clock as it is
+    #[default]
+    Keep,
+    /// Set the clock to the local time on every apply
+    Sync,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PageSpec {
+    id: char,
+    message: String,
+    /// Effects and waiting time default to the panel's page defaults
+    leading: Option<Leading>,
+    lagging: Option<Lagging>,
+    #[serde(default)]
+    waiting_mode: WaitingModeAndSpeed,
+    /// Waiting time step, 0 for 0.5 seconds, otherwise seconds (at most 25)
+    waiting_time: Option<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScheduleSpec {
+    id: char,
+    /// Same format as for `schedule set`
+    period: String,
+    pages: String,
+}
+
+/// Content for the panels a target selects by name or glob, group or tag
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Target {
+    #[serde(default)]
+    panels: Vec<String>,
+    #[serde(default)]
+    groups: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    clock: Option<ClockPolicy>,
+    #[serde(default)]
+    pages: Vec<PageSpec>,
+    #[serde(default)]
+    schedules: Vec<ScheduleSpec>,
+    /// Names of the selected panels, resolved against the config on load
+    #[serde(skip)]
+    members: Vec<String>,
+}
+
+impl Target {
+    fn matches(&self, panel: &str) -> bool {
+        self.members.iter().any(|member| member == panel)
+    }
+}
+
+/// Desired pages, schedules and clock policy of panels.
+///
+/// A panel gets the content of every target naming it, later targets
+/// replacing pages and schedules with the same ID. Panels named by a target
+/// are managed completely: pages and schedules missing from the file are
+/// deleted from them. Other panels are left alone.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContentFile {
+    #[serde(rename = "target", default)]
+    targets: Vec<Target>,
+}
+
+/// Content a single panel should have
+#[derive(Debug, Default)]
+struct Desired {
+    pages: BTreeMap<char, Page>,
+    schedules: BTreeMap<char, Schedule>,
+    clock: ClockPolicy,
+}
+
+impl ContentFile {
+    /// Reads a content file and checks it against the configured panels.
+    pub fn load(path: &Path, config: &Config) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read '{}'", path.display()))?;
+        let mut file: ContentFile = toml::from_str(&text)
+            .with_context(|| format!("invalid content file '{}'", path.display()))?;
+
+        for target in &mut file.targets {
+            if target.panels.is_empty() && target.groups.is_empty() && target.tags.is_empty() {
+                bail!(
+                    "every target in '{}' needs panels, groups or tags",
+                    path.display()
+                );
+            }
+            target.members = config
+                .select(&target.panels, &target.groups, &target.tags)
+                .with_context(|| format!("invalid target in '{}'", path.display()))?
+                .iter()
+                .map(|panel| panel.name.clone())
+                .collect();
+            for page in &target.pages {
+                super::page::check_id(page.id)?;
+            }
+            for schedule in &target.schedules {
+                super::schedule::check_id(schedule.id)?;
+                super::schedule::parse_pages(&schedule.pages)
+                    .with_context(|| format!("invalid pages of schedule '{}'", schedule.id))?;
+            }
+        }
+        Ok(file)
+    }
+
+    /// Returns the content of `panel`, or `None` if no target names it.
+    fn desired(&self, panel: &Panel) -> Result<Option<Desired>> {
+        let mut desired: Option<Desired> = None;
+        let defaults = &panel.page;
+        for target in self
+            .targets
+            .iter()
+            .filter(|target| target.matches(&panel.name))
+        {
+            let desired = desired.get_or_insert_default();
+            if let Some(clock) = target.clock {
+                desired.clock = clock;
+            }
+            for spec in &target.pages {
+                let page = Page::new(
+                    spec.id,
+                    spec.message.clone(),
+                    spec.leading
+                        .clone()
+                        .or_else(|| defaults.leading.clone())
+                        .unwrap_or_default(),
+                    spec.lagging
+                        .clone()
+                        .or_else(|| defaults.lagging.clone())
+                        .unwrap_or_default(),
+                    spec.waiting_mode.clone(),
+                    WaitingTime::new(spec.waiting_time.or(defaults.waiting_time).unwrap_or(0)),
+                );
+                desired.pages.insert(spec.id, page);
+            }
+            for spec in &target.schedules {
+                let (from, to) = super::schedule::parse_period(&spec.period)
+                    .with_context(|| format!("invalid period of schedule '{}'", spec.id))?;
+                let pages = super::schedule::parse_pages(&spec.pages)?;
+                desired
+                    .schedules
+                    .insert(spec.id, Schedule::new(spec.id, from, to, pages));
+            }
+        }
+        Ok(desired)
+    }
+}
+
+/// Page whose content differs from the content file
+#[derive(Debug, Clone, Serialize)]
+struct ChangedPage {
+    #[serde(flatten)]
+    page: Page,
+    /// Names of the fields which differ
+    fields: Vec<&'static str>,
+}
+
+/// Changes needed to bring a panel to its desired content
+#[derive(Debug, Clone, Default, Serialize)]
+struct Changes {
+    added_pages: Vec<Page>,
+    changed_pages: Vec<ChangedPage>,
+    deleted_pages: Vec<char>,
+    added_schedules: Vec<Schedule>,
+    changed_schedules: Vec<Schedule>,
+    deleted_schedules: Vec<char>,
+    sync_clock: bool,
+}
+
+fn changed_fields(live: &Page, desired: &Page) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if live.message != desired.message {
+        fields.push("message");
+    }
+    if live.leading != desired.leading {
+        fields.push("leading");
+    }
+    if live.lagging != desired.lagging {
+        fields.push("lagging");
+    }
+    if live.waiting_mode_and_speed != desired.waiting_mode_and_speed {
+        fields.push("waiting mode");
+    }
+    if live.waiting_time != desired.waiting_time {
+        fields.push("waiting time");
+    }
+    if fields.is_empty() {
+        // Only the line differs
+        fields.push("line");
+    }
+    fields
+}
+
+impl Changes {
+    fn new(desired: &Desired, pages: &[Page], schedules: &[Schedule]) -> Self {
+        let mut changes = Changes {
+            sync_clock: desired.clock == ClockPolicy::Sync,
+            ..Default::default()
+        };
+
+        for page in desired.pages.values() {
+            match pages.iter().find(|live| live.id == page.id) {
+                None => changes.added_pages.push(page.clone()),
+                Some(live) if live != page => changes.changed_pages.push(ChangedPage {
+                    page: page.clone(),
+                    fields: changed_fields(live, page),
+                }),
+                Some(_) => {}
+            }
+        }
+        changes.deleted_pages = pages
+            .iter()
+            .map(|page| page.id)
+            .filter(|id| !desired.pages.contains_key(id))
+            .collect();
+
+        for schedule in desired.schedules.values() {
+            match schedules.iter().find(|live| live.id == schedule.id) {
+                None => changes.added_schedules.push(schedule.clone()),
+                Some(live) if live != schedule => changes.changed_schedules.push(schedule.clone()),
+                Some(_) => {}
+            }
+        }
+        changes.deleted_schedules = schedules
+            .iter()
+            .map(|schedule| schedule.id)
+            .filter(|id| !desired.schedules.contains_key(id))
+            .collect();
+
+        changes
+    }
+
+    /// Returns whether pages or schedules differ. Syncing the clock does not
+    /// count as a change.
+    fn is_empty(&self) -> bool {
+        self.added_pages.is_empty()
+            && self.changed_pages.is_empty()
+            && self.deleted_pages.is_empty()
+            && self.added_schedules.is_empty()
+            && self.changed_schedules.is_empty()
+            && self.deleted_schedules.is_empty()
+    }
+
+    fn summary(&self, applied: bool) -> String {
+        let mut summary = if self.is_empty() {
+            String::from("up to date")
+        } else {
+            let (added, changed, deleted) = if applied {
+                ("added", "changed", "deleted")
+            } else {
+                ("to add", "to change", "to delete")
+            };
+            format!(
+                "{} {added}, {} {changed}, {} {deleted}",
+                self.added_pages.len() + self.added_schedules.len(),
+                self.changed_pages.len() + self.changed_schedules.len(),
+                self.deleted_pages.len() + self.deleted_schedules.len(),
+            )
+        };
+        if self.sync_clock {
+            summary.push_str(if applied {
+                ", clock synced"
+            } else {
+                ", clock synced on apply"
+            });
+        }
+        summary
+    }
+
+    fn print_details(&self) {
+        let page = |page: &Page| {
+            format!(
+                "{} / {}  \"{}\"",
+                effect_name(&page.leading),
+                effect_name(&page.lagging),
+                short_message(&page.message)
+            )
+        };
+        let schedule = |schedule: &Schedule| {
+            format!(
+                "{} to {}  pages {}",
+                format_date_time(&schedule.from),
+                format_date_time(&schedule.to),
+                schedule.pages
+            )
+        };
+
+        for added in &self.added_pages {
+            let line = format!("+ page {}      {}", added.id, page(added));
+            println!("    {}", style(line).green());
+        }
+        for ChangedPage { page: changed, fields } in &self.changed_pages {
+            let line = format!(
+                "~ page {}      {}  ({} changed)",
+                changed.id,
+                page(changed),
+                fields.join(", ")
+            );
+            println!("    {}", style(line).yellow());
+        }
+        for id in &self.deleted_pages {
+            println!("    {}", style(format!("- page {id}")).red());
+        }
+        for added in &self.added_schedules {
+            let line = format!("+ schedule {}  {}", added.id, schedule(added));
+            println!("    {}", style(line).green());
+        }
+        for changed in &self.changed_schedules {
+            let line = format!("~ schedule {}  {}", changed.id, schedule(changed));
+            println!("    {}", style(line).yellow());
+        }
+        for id in &self.deleted_schedules {
+            println!("    {}", style(format!("- schedule {id}")).red());
+        }
+    }
+}
+
+/// Reads the live content of the managed panels and compares it with the
+/// content file. Returns the panels named by the file with their changes.
+async fn changes<'a>(
+    panels: &[&'a Panel],
+    file: &ContentFile,
+) -> Result<(Vec<&'a Panel>, FleetReport<Changes>)> {
+    let mut desired: HashMap<String, Desired> = HashMap::new();
+    let mut managed = Vec::new();
+    for panel in panels {
+        if let Some(content) = file.desired(panel)? {
+            desired.insert(panel.name.clone(), content);
+            managed.push(*panel);
+        }
+    }
+    if managed.is_empty() {
+        bail!("the content file does not name any of the selected panels");
+    }
+
+    let desired = Arc::new(desired);
+    let report = super::fleet(&managed)
+        .run(move |name, client| {
+            let desired = desired.clone();
+            let name = name.to_string();
+            async move {
+                let pages = client.get_pages().await?;
+                let schedules = client.get_schedules().await?;
+                Ok(Changes::new(&desired[&name], &pages, &schedules))
+            }
+        })
+        .await;
+    Ok((managed, report))
+}
+
+/// Prints the changes of every panel, nothing in JSON mode.
+fn print_changes(panels: &[&Panel], report: &FleetReport<Changes>) {
+    if is_json() {
+        return;
+    }
+    let label_width = super::label_width(panels);
+    for outcome in &report.outcomes {
+        let name = &outcome.name;
+        match &outcome.result {
+            Ok(changes) if changes.is_empty() => println!(
+                "{} {name:<label_width$}  {}",
+                style("✓").green(),
+                style(changes.summary(false)).dim()
+            ),
+            Ok(changes) => {
+                println!(
+                    "{} {name:<label_width$}  {}",
+                    style("~").yellow(),
+                    changes.summary(false)
+                );
+                changes.print_details();
+            }
+            Err(e) => println!("{} {name:<label_width$}  {e}", style("✗").red()),
+        }
+    }
+}
+
+/// Shows how the panels differ from the content file. Returns whether any
+/// panel needs changes.
+pub async fn plan(panels: &[&Panel], file: &ContentFile) -> Result<bool> {
+    print_title("Comparing panels with the content file");
+
+    let (managed, report) = changes(panels, file).await?;
+    print_changes(&managed, &report);
+    super::finish(&report, "one or more panels could not be read")?;
+
+    Ok(report.outcomes.iter().any(|outcome| {
+        outcome
+            .result
+            .as_ref()
+            .is_ok_and(|changes| !changes.is_empty())
+    }))
+}
+
+/// Sends the changes needed to bring a panel to its desired content, setting
+/// the clock to `now` if its policy asks for it. Schedules are deleted before
+/// pages and sent after them, as they refer to pages.
+async fn apply_changes(
+    client: &dyn Backend,
+    changes: &Changes,
+    now: &DateTime,
+) -> Result<(), ClientError> {
+    for id in &changes.deleted_schedules {
+        client.delete_schedule(*id).await?;
+    }
+    for id in &changes.deleted_pages {
+        client.delete_page(*id).await?;
+    }
+
+    let pages: Vec<Page> = changes
+        .added_pages
+        .iter()
+        .chain(changes.changed_pages.iter().map(|changed| &changed.page))
+        .cloned()
+        .collect();
+    if !pages.is_empty() {
+        client.set_pages(&pages).await?;
+    }
+    let schedules: Vec<Schedule> = changes
+        .added_schedules
+        .iter()
+        .chain(&changes.changed_schedules)
+        .cloned()
+        .collect();
+    if !schedules.is_empty() {
+        client.set_schedules(&schedules).await?;
+    }
+
+    if changes.sync_clock {
+        client.set_clock(now).await?;
+    }
+    Ok(())
+}
+
+/// Brings the panels to the content of the file, deleting pages and
+/// schedules it does not list. In JSON mode the changes applied to each
+/// panel are printed, or the changes found if there is nothing to apply.
+pub async fn apply(panels: &[&Panel], file: &ContentFile) -> Result<()> {
+    print_title("Comparing panels with the content file");
+
+    let (managed, report) = changes(panels, file).await?;
+    print_changes(&managed, &report);
+    if !report.is_success() {
+        return super::finish(&report, "one or more panels could not be read");
+    }
+
+    let changes: HashMap<String, Changes> = report
+        .outcomes
+        .iter()
+        .filter_map(|outcome| Some((outcome.name.clone(), outcome.result.as_ref().ok()?.clone())))
+        .filter(|(_, changes)| !changes.is_empty() || changes.sync_clock)
+        .collect();
+    let targets: Vec<&Panel> = managed
+        .into_iter()
+        .filter(|panel| changes.contains_key(&panel.name))
+        .collect();
+    if targets.is_empty() {
+        super::print_report(&report);
+        return Ok(());
+    }
+
+    let now =
+        datetime_from_offset(OffsetDateTime::now_local().context("failed to get local time")?);
+
+    print_line("");
+    print_title("Applying changes");
+    let changes = Arc::new(changes);
+    let report = super::run_with_spinners(
+        &targets,
+        |name, client| {
+            let changes = changes.clone();
+            let name = name.to_string();
+            async move {
+                let changes = &changes[&name];
+                apply_changes(client.as_ref(), changes, &now).await?;
+                Ok(changes.clone())
+            }
+        },
+        |result| match result {
+            Ok(changes) => changes.summary(true),
+            Err(e) => e.to_string(),
+        },
+    )
+    .await;
+
+    super::finish(&report, "one or more panels failed to apply the changes")
+}
@@ -0,0 +1,280 @@
+use am03127_client::{ClientError, FleetReport};
+use am03127_commands::{
+    MAX_SCHEDULE_PAGES, is_page_id_valid, is_schedule_id_valid,
+    schedule::{Schedule, ScheduleDateTime},
+};
+use anyhow::{Context, Result, anyhow, bail};
+use console::style;
+use time::{Date, Month, PrimitiveDateTime, Time};
+
+use crate::{
+    config::Panel,
+    console::{is_json, print_title},
+};
+
+pub fn check_id(id: char) -> Result<()> {
+    if !is_schedule_id_valid(id) {
+        bail!("schedule id must be a letter from A to E, got '{id}'");
+    }
+    Ok(())
+}
+
+/// Parses the pages of a schedule, given as `ABC` or `A,B,C`.
+pub fn parse_pages(input: &str) -> Result<String> {
+    let pages: String = input
+        .chars()
+        .filter(|c| *c != ',' && !c.is_whitespace())
+        .collect();
+    if pages.is_empty() {
+        bail!("a schedule needs at least one page");
+    }
+    if let Some(invalid) = pages.chars().find(|&id| !is_page_id_valid(id)) {
+        bail!("page id must be an uppercase ASCII letter (A-Z), got '{invalid}'");
+    }
+    if pages.len() > MAX_SCHEDULE_PAGES {
+        bail!(
+            "a schedule holds at most {MAX_SCHEDULE_PAGES} pages, got {}",
+            pages.len()
+        );
+    }
+    Ok(pages)
+}
+
+/// Splits a period into its keyword and the rest, e.g. `daily` and `08:00-18:00`.
+fn keyword(input: &str) -> (&str, &str) {
+    let input = input.trim();
+    match input.split_once(char::is_whitespace) {
+        Some((keyword, rest)) => (keyword, rest.trim()),
+        None => (input, ""),
+    }
+}
+
+/// Parses when a schedule is active.
+///
+/// Accepts `always`, `daily HH:MM-HH:MM` and explicit ranges like
+/// `2026-12-24..2026-12-26` or `2026-12-24 18:00..2026-12-26 06:00`.
+/// Keywords are case-insensitive. `daily` is sent with the date 00/00/00,
+/// which the panel repeats every day.
+pub fn parse_period(input: &str) -> Result<(ScheduleDateTime, ScheduleDateTime)> {
+    let input = input.trim();
+    let (from, to) = match keyword(input) {
+        (keyword, "") if keyword.eq_ignore_ascii_case("always") => {
+            return Ok((
+                ScheduleDateTime::new(0, 1, 1, 0, 0),
+                ScheduleDateTime::new(99, 12, 31, 23, 59),
+            ));
+        }
+        (keyword, window) if keyword.eq_ignore_ascii_case("daily") => {
+            let (start, end) = window
+                .split_once('-')
+                .ok_or_else(|| anyhow!("expected 'daily HH:MM-HH:MM', got '{input}'"))?;
+            let (start, end) = (parse_time(start)?, parse_time(end)?);
+            if start >= end {
+                bail!(
+                    "a daily window must end after it starts on the same day, \
+                     split '{input}' at midnight into two schedules"
+                );
+            }
+            return Ok((
+                ScheduleDateTime::daily(start.hour(), start.minute()),
+                ScheduleDateTime::daily(end.hour(), end.minute()),
+            ));
+        }
+        _ => match input.split_once("..") {
+            Some((from, to)) => (
+                parse_date_time(from, Time::MIDNIGHT)?,
+                parse_date_time(to, Time::from_hms(23, 59, 0)?)?,
+            ),
+            None => bail!(
+                "unknown schedule period '{input}', expected 'always', 'daily HH:MM-HH:MM' or 'FROM..TO'"
+            ),
+        },
+    };
+
+    if from >= to {
+        bail!("the schedule must end after it starts");
+    }
+    Ok((schedule_date_time(from)?, schedule_date_time(to)?))
+}
+
+fn parse_time(input: &str) -> Result<Time> {
+    let input = input.trim();
+    let (hour, minute) = input
+        .split_once(':')
+        .and_then(|(hour, minute)| Some((hour.parse().ok()?, minute.parse().ok()?)))
+        .ok_or_else(|| anyhow!("expected a time like 08:00, got '{input}'"))?;
+    Time::from_hms(hour, minute, 0).with_context(|| format!("invalid time '{input}'"))
+}
+
+/// Parses `YYYY-MM-DD` with an optional ` HH:MM`, using `time` if the time is
+/// left out.
+pub fn parse_date_time(input: &str, time: Time) -> Result<PrimitiveDateTime> {
+    let input = input.trim();
+    let (date, clock) = match input.split_once([' ', 'T']) {
+        Some((date, clock)) => (date, Some(clock)),
+        None => (input, None),
+    };
+    let fields: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = fields[..] else {
+        bail!("expected a date like 2026-12-24, got '{date}'");
+    };
+    let parse = |field: &str| {
+        field
+            .parse::<u16>()
+            .with_context(|| format!("expected a date like 2026-12-24, got '{date}'"))
+    };
+    let month = Month::try_from(parse(month)? as u8).context("month must be from 1 to 12")?;
+    let date = Date::from_calendar_date(i32::from(parse(year)?), month, parse(day)? as u8)
+        .with_context(|| format!("invalid date '{date}'"))?;
+    let time = match clock {
+        Some(clock) => parse_time(clock)?,
+        None => time,
+    };
+    Ok(PrimitiveDateTime::new(date, time))
+}
+
+fn schedule_date_time(dt: PrimitiveDateTime) -> Result<ScheduleDateTime> {
+    if !(2000..=2099).contains(&dt.year()) {
+        bail!(
+            "the panel only supports the years 2000 to 2099, got {}",
+            dt.year()
+        );
+    }
+    Ok(ScheduleDateTime::new(
+        (dt.year() - 2000) as u8,
+        dt.month() as u8,
+        dt.day(),
+        dt.hour(),
+        dt.minute(),
+    ))
+}
+
+pub fn format_date_time(dt: &ScheduleDateTime) -> String {
+    if dt.is_daily() {
+        return format!("daily {:02}:{:02}", dt.hour, dt.minute);
+    }
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        2000 + u16::from(dt.year),
+        dt.month,
+        dt.day,
+        dt.hour,
+        dt.minute
+    )
+}
+
+/// Prints the schedules of every panel as a table below the panel's name,
+/// nothing in JSON mode.
+fn print_schedules(panels: &[&Panel], report: &FleetReport<Vec<Schedule>>) {
+    if is_json() {
+        return;
+    }
+    let label_width = super::label_width(panels);
+    for outcome in &report.outcomes {
+        let name = &outcome.name;
+        let schedules = match &outcome.result {
+            Ok(schedules) => schedules,
+            Err(e) => {
+                println!("{} {name:<label_width$}  {e}", style("✗").red());
+                continue;
+            }
+        };
+        if schedules.is_empty() {
+            println!(
+                "{} {name:<label_width$}  {}",
+                style("✓").green(),
+                style("no schedules").dim()
+            );
+            continue;
+        }
+
+        println!("{} {name}", style("✓").green());
+        println!(
+            "    {}",
+            style(format!("{:<2}  {:<16}  {:<16}  Pages", "ID", "From", "To")).dim()
+        );
+        for schedule in schedules {
+            println!(
+                "    {:<2}  {:<16}  {:<16}  {}",
+                schedule.id,
+                format_date_time(&schedule.from),
+                format_date_time(&schedule.to),
+                style(&schedule.pages).cyan()
+            );
+        }
+    }
+}
+
+pub async fn set(panels: &[&Panel], id: char, period: &str, pages: &str) -> Result<()> {
+    check_id(id)?;
+    let pages = parse_pages(pages)?;
+    let (from, to) = parse_period(period)?;
+    let schedule = Schedule::new(id, from, to, pages);
+
+    print_title(&format!(
+        "Sending schedule '{id}' ({} to {}, pages {}) to panels",
+        format_date_time(&schedule.from),
+        format_date_time(&schedule.to),
+        schedule.pages
+    ));
+
+    let report = super::run_with_spinners(
+        panels,
+        |_, client| {
+            let schedule = schedule.clone();
+            async move { client.set_schedule(&schedule).await }
+        },
+        |result| match result {
+            Ok(()) => format!("schedule '{id}' sent"),
+            Err(e) => e.to_string(),
+        },
+    )
+    .await;
+
+    super::finish(&report, "one or more panels failed to receive the schedule")
+}
+
+pub async fn list(panels: &[&Panel]) -> Result<()> {
+    print_title("Schedules on panels");
+
+    let report = super::fleet(panels)
+        .run(|_, client| async move { client.get_schedules().await })
+        .await;
+    print_schedules(panels, &report);
+
+    super::finish(&report, "one or more panels failed to list schedules")
+}
+
+pub async fn get(panels: &[&Panel], id: char) -> Result<()> {
+    check_id(id)?;
+    print_title(&format!("Schedule '{id}' on panels"));
+
+    let report = super::fleet(panels)
+        .run(|_, client| async move {
+            match client.get_schedule(id).await? {
+                Some(schedule) => Ok(vec![schedule]),
+                None => Err(ClientError::NotFound(String::from("Schedule not found"))),
+            }
+        })
+        .await;
+    print_schedules(panels, &report);
+
+    super::finish(&report, "one or more panels failed to return the schedule")
+}
+
+pub async fn delete(panels: &[&Panel], id: char) -> Result<()> {
+    check_id(id)?;
+    print_title(&format!("Deleting schedule '{id}' from panels"));
+
+    let report = super::run_with_spinners(
+        panels,
+        |_, client| async move { client.delete_schedule(id).await },
+        |result| match result {
+            Ok(()) => format!("schedule '{id}' deleted"),
+            Err(e) => e.to_string(),
+        },
+    )
+    .await;
+
+    super::finish(&report, "one or more panels failed to delete the schedule")
+}
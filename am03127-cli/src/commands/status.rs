@@ -1,44 +1,27 @@
-use am03127_client::PanelClient;
-use anyhow::{Context, Result};
+use anyhow::Result;
+use console::style;
 
-use crate::{
-    config::Panel,
-    console::{SpinnerGroup, print_title},
-};
+use crate::{config::Panel, console::print_title};
 
 pub async fn run(panels: &[&Panel]) -> Result<()> {
     print_title("Checking panel status");
-    let label_width = super::label_width(panels);
-    let spinners = SpinnerGroup::new();
-    let mut set = tokio::task::JoinSet::new();
 
-    for panel in panels {
-        let client = PanelClient::new(&panel.address);
-        let name = panel.name.clone();
-        let pb = spinners.add(&name);
-        set.spawn(async move {
-            let result = client.get_status().await;
-            (name, result, pb)
-        });
-    }
-
-    while let Some(res) = set.join_next().await {
-        let (name, result, pb) = res.context("panel task panicked")?;
-        match result {
-            Err(_) => pb.finish_with_message(format!(
-                "{} {name:<label_width$}  {}",
-                console::style("✗").red(),
-                console::style("offline").red(),
-            )),
-            Ok(info) => pb.finish_with_message(format!(
-                "{} {name:<label_width$}  {} {} {}",
-                console::style("✓").green(),
-                console::style("online").green(),
-                console::style(&info.version).cyan(),
-                console::style(format!("{} {}", info.build_date, info.build_time)).dim(),
-            )),
-        }
-    }
+    let report = super::run_with_spinners(
+        panels,
+        |_, client| async move { client.get_status().await },
+        |result| match result {
+            Ok(info) => format!(
+                "{} {} {}",
+                style("online").green(),
+                style(&info.version).cyan(),
+                style(format!("{} {}", info.build_date, info.build_time)).dim(),
+            ),
+            Err(e) if e.is_offline() => style("offline").red().to_string(),
+            Err(e) => style(e).red().to_string(),
+        },
+    )
+    .await;
+    super::print_report(&report);
 
     Ok(())
 }
@@ -1,68 +1,302 @@
-use std::{fs, path::Path};
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use am03127_client::PanelClient;
-use anyhow::{Context, Result};
+use am03127_client::{
+    AppDescriptor, BuildInfo, ClientError, Firmware, FleetReport, Panel as Backend, PanelOutcome,
+};
+use anyhow::{Context, Result, bail};
 use console::style;
+use indicatif::ProgressBar;
+use serde::Serialize;
 
-use super::FirmwareBuildInfo;
 use crate::{
     config::Panel,
-    console::{SpinnerGroup, print_title},
+    console::{ProgressGroup, print_line, print_title},
 };
 
-pub async fn run(panels: &[&Panel], firmware_path: &Path) -> Result<()> {
-    let firmware = fs::read(firmware_path)
+/// Time between two status requests while waiting for a panel to come back
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How a rollout updates the panels, one batch after another
+pub struct Rollout {
+    /// Panel updated alone before all others, the first outdated panel if
+    /// not given
+    pub canary: Option<String>,
+    /// Panels updated at the same time after the canary
+    pub batch_size: usize,
+    /// How long a panel may take to report the new firmware after the upload
+    pub health_timeout: Duration,
+}
+
+/// What a rollout did with a panel
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Step {
+    /// The panel already ran the firmware
+    Skipped,
+    /// The panel was updated and came back with the firmware
+    Updated,
+    /// The rollout stopped before the panel was updated
+    Pending,
+}
+
+#[derive(Serialize)]
+struct RolloutResult {
+    step: Step,
+    /// Build the panel reported last
+    #[serde(skip_serializing_if = "Option::is_none")]
+    running: Option<BuildInfo>,
+}
+
+fn load(firmware_path: &Path) -> Result<Firmware> {
+    let data = fs::read(firmware_path)
         .with_context(|| format!("failed to read '{}'", firmware_path.display()))?;
+    Firmware::parse(data)
+        .with_context(|| format!("refusing to upload '{}'", firmware_path.display()))
+}
 
-    if let Some(info) = FirmwareBuildInfo::parse(&firmware) {
-        print_title(&format!(
-            "Updating {} panel(s) to {} built at {}",
-            panels.len(),
-            style(&info.git_hash).cyan(),
-            style(format!("{} {}", info.build_date, info.build_time)).dim(),
-        ));
+/// Polls the status of a panel until it runs `target`. Once `timeout` passed,
+/// fails with the last error, or because the panel runs another build.
+async fn await_firmware(
+    client: &dyn Backend,
+    target: &AppDescriptor,
+    timeout: Duration,
+) -> Result<BuildInfo, ClientError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let status = client.get_status().await;
+        if let Ok(info) = &status
+            && target.is_running(info)
+        {
+            return status;
+        }
+        if Instant::now() >= deadline {
+            return Err(match status {
+                Ok(info) => ClientError::Other(format!(
+                    "panel still runs {} {} {} after {}s",
+                    info.version,
+                    info.build_date,
+                    info.build_time,
+                    timeout.as_secs()
+                )),
+                Err(e) => e,
+            });
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
     }
+}
 
+/// Uploads the firmware to all panels at once with a progress bar per panel.
+/// With `health_timeout`, each panel then has to come back with the new
+/// firmware in time.
+async fn upload(
+    panels: &[&Panel],
+    firmware: &Firmware,
+    health_timeout: Option<Duration>,
+) -> FleetReport<BuildInfo> {
     let label_width = super::label_width(panels);
-    let spinners = SpinnerGroup::new();
-    let mut set = tokio::task::JoinSet::new();
-
-    for panel in panels {
-        let client = PanelClient::new(&panel.address);
-        let name = panel.name.clone();
-        let firmware = firmware.clone();
-        let pb = spinners.add(&name);
-
-        set.spawn(async move {
-            let result = client.update_firmware(&firmware).await;
-            (name, result, pb)
-        });
+    let bars = ProgressGroup::new(firmware.len() as u64, label_width);
+    let progress: HashMap<&str, ProgressBar> = panels
+        .iter()
+        .map(|panel| (panel.name.as_str(), bars.add(&panel.name)))
+        .collect();
+
+    super::fleet(panels)
+        .with_concurrency(panels.len())
+        .run_with(
+            |name, client| {
+                let firmware = firmware.clone();
+                let pb = progress[name].clone();
+                async move {
+                    let progress = Arc::new(move |sent, _total| pb.set_position(sent));
+                    client
+                        .update_firmware_with_progress(&firmware, progress)
+                        .await?;
+                    match health_timeout {
+                        Some(timeout) => {
+                            await_firmware(client.as_ref(), firmware.descriptor(), timeout).await
+                        }
+                        None => Ok(firmware.descriptor().build_info()),
+                    }
+                }
+            },
+            |outcome| {
+                let name = &outcome.name;
+                let message = match (&outcome.result, health_timeout) {
+                    (Ok(_), None) => format!(
+                        "{} {name:<label_width$}  done, rebooting...",
+                        style("✓").green()
+                    ),
+                    (Ok(info), Some(_)) => format!(
+                        "{} {name:<label_width$}  back with {} after {}s",
+                        style("✓").green(),
+                        style(&info.version).cyan(),
+                        outcome.elapsed.as_secs()
+                    ),
+                    (Err(e), _) => format!("{} {name:<label_width$}  {e}", style("✗").red()),
+                };
+                ProgressGroup::finish(&progress[name.as_str()], message);
+            },
+        )
+        .await
+}
+
+/// Prints a title for updating `panels` (e.g. "3 panel(s)") to the firmware.
+fn print_firmware_title(prefix: &str, panels: &str, info: &AppDescriptor) {
+    print_title(&format!(
+        "{prefix}{panels} to {} built at {}",
+        style(&info.version).cyan(),
+        style(format!("{} {}", info.build_date, info.build_time)).dim(),
+    ));
+}
+
+pub async fn run(panels: &[&Panel], firmware_path: &Path) -> Result<()> {
+    let firmware = load(firmware_path)?;
+    let count = format!("{} panel(s)", panels.len());
+    print_firmware_title("Updating ", &count, firmware.descriptor());
+
+    let report = upload(panels, &firmware, None).await;
+    super::finish(&report, "one or more panels failed to update")
+}
+
+/// Updates the panels not running the firmware yet, the canary first and then
+/// the others in batches. Every panel has to come back with the new firmware
+/// before the next batch starts, the rollout stops at the first failure.
+pub async fn rollout(panels: &[&Panel], firmware_path: &Path, rollout: &Rollout) -> Result<()> {
+    let firmware = load(firmware_path)?;
+    let target = firmware.descriptor().clone();
+    if let Some(canary) = &rollout.canary
+        && !panels.iter().any(|panel| &panel.name == canary)
+    {
+        bail!("canary '{canary}' is not one of the selected panels");
     }
 
-    let mut success = true;
-    while let Some(res) = set.join_next().await {
-        let (name, result, pb) = res.context("panel task panicked")?;
-        match result {
-            Ok(_) => {
-                pb.finish_with_message(format!(
-                    "{} {name:<label_width$}  done, rebooting...",
-                    style("✓").green()
-                ));
-            }
-            Err(e) => {
-                pb.finish_with_message(format!(
-                    "{} {name:<label_width$}  {e}",
-                    style("✗").red()
-                ));
-                success = false;
+    print_title(&format!(
+        "Checking the firmware of {} panel(s)",
+        panels.len()
+    ));
+    let status = super::run_with_spinners(
+        panels,
+        |_, client| async move { client.get_status().await },
+        |result| match result {
+            Ok(info) if target.is_running(info) => {
+                style(format!("already runs {}, skipped", info.version))
+                    .dim()
+                    .to_string()
             }
+            Ok(info) => format!(
+                "runs {} {} {}",
+                info.version, info.build_date, info.build_time
+            ),
+            Err(e) => e.to_string(),
+        },
+    )
+    .await;
+    if !status.is_success() {
+        return super::finish(
+            &status,
+            "could not check the firmware of one or more panels, nothing was updated",
+        );
+    }
+
+    let mut results: HashMap<String, PanelOutcome<RolloutResult>> = HashMap::new();
+    let mut outdated = Vec::new();
+    for outcome in status.outcomes {
+        let Ok(info) = outcome.result else {
+            continue;
+        };
+        let up_to_date = target.is_running(&info);
+        if !up_to_date {
+            outdated.push(outcome.name.clone());
         }
+        let result = RolloutResult {
+            step: if up_to_date {
+                Step::Skipped
+            } else {
+                Step::Pending
+            },
+            running: Some(info),
+        };
+        results.insert(
+            outcome.name.clone(),
+            PanelOutcome {
+                name: outcome.name,
+                result: Ok(result),
+                elapsed: outcome.elapsed,
+            },
+        );
     }
 
-    if !success {
-        anyhow::bail!("one or more panels failed to update");
+    let mut order: Vec<&Panel> = panels
+        .iter()
+        .copied()
+        .filter(|panel| outdated.contains(&panel.name))
+        .collect();
+    if let Some(index) = rollout
+        .canary
+        .as_ref()
+        .and_then(|canary| order.iter().position(|panel| &panel.name == canary))
+    {
+        let canary = order.remove(index);
+        order.insert(0, canary);
     }
 
-    Ok(())
-}
+    let mut batches: Vec<&[&Panel]> = Vec::new();
+    if let Some((canary, rest)) = order.split_first() {
+        batches.push(std::slice::from_ref(canary));
+        batches.extend(rest.chunks(rollout.batch_size.max(1)));
+    }
+    if batches.is_empty() {
+        print_line(style(format!("all panels already run {}", target.version)).green());
+    }
+
+    for (index, batch) in batches.iter().enumerate() {
+        print_line("");
+        if index == 0 {
+            let canary = format!("canary '{}'", batch[0].name);
+            print_firmware_title("Updating ", &canary, &target);
+        } else {
+            let prefix = format!("Batch {}/{}: updating ", index, batches.len() - 1);
+            print_firmware_title(&prefix, &format!("{} panel(s)", batch.len()), &target);
+        }
 
+        let report = upload(batch, &firmware, Some(rollout.health_timeout)).await;
+        let failed = !report.is_success();
+        for outcome in report.outcomes {
+            results.insert(
+                outcome.name.clone(),
+                PanelOutcome {
+                    name: outcome.name,
+                    result: outcome.result.map(|info| RolloutResult {
+                        step: Step::Updated,
+                        running: Some(info),
+                    }),
+                    elapsed: outcome.elapsed,
+                },
+            );
+        }
+        if failed {
+            let pending = order.len() - batches[..=index].iter().map(|b| b.len()).sum::<usize>();
+            if pending > 0 {
+                print_line(style(format!("rollout stopped, {pending} panel(s) not updated")).red());
+            }
+            break;
+        }
+    }
+
+    let report = FleetReport {
+        outcomes: panels
+            .iter()
+            .filter_map(|panel| results.remove(&panel.name))
+            .collect(),
+    };
+    super::finish(
+        &report,
+        "the rollout stopped at a panel which failed to update",
+    )
+}
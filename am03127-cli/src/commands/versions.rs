@@ -0,0 +1,233 @@
+use std::{fs, path::Path};
+
+use am03127_client::{AppDescriptor, BuildInfo, FleetReport, PanelOutcome};
+use anyhow::{Context, Result, bail};
+use console::{StyledObject, style};
+use serde::Serialize;
+use time::{Date, Month, PrimitiveDateTime, Time};
+
+use crate::{
+    config::Panel,
+    console::{is_json, print_title},
+};
+
+/// Exit code of `versions` if panels are outdated or run a dirty build
+pub const EXIT_NONCOMPLIANT: i32 = 8;
+
+/// Month names of the `__DATE__` format
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Firmware the panels should run
+pub enum Requirement {
+    /// The build of an image, or a newer one
+    Firmware(AppDescriptor),
+    /// Any build made at or after the time
+    Minimum(PrimitiveDateTime),
+}
+
+impl Requirement {
+    /// Reads the build of a firmware image.
+    pub fn firmware(path: &Path) -> Result<Self> {
+        let data =
+            fs::read(path).with_context(|| format!("failed to read '{}'", path.display()))?;
+        match AppDescriptor::parse(&data) {
+            Some(descriptor) => Ok(Requirement::Firmware(descriptor)),
+            None => bail!(
+                "file '{}' does not contain a firmware header",
+                path.display()
+            ),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Requirement::Firmware(descriptor) => format!(
+                "{} built at {}",
+                style(&descriptor.version).cyan(),
+                style(format!(
+                    "{} {}",
+                    descriptor.build_date, descriptor.build_time
+                ))
+                .dim()
+            ),
+            Requirement::Minimum(minimum) => format!(
+                "builds made at or after {}",
+                style(format!(
+                    "{} {:02}:{:02}",
+                    minimum.date(),
+                    minimum.hour(),
+                    minimum.minute()
+                ))
+                .cyan()
+            ),
+        }
+    }
+
+    fn check(&self, info: &BuildInfo) -> Compliance {
+        if info.is_dirty() {
+            return Compliance::Dirty;
+        }
+        let built_at = built_at(&info.build_date, &info.build_time);
+        let up_to_date = match self {
+            Requirement::Firmware(descriptor) => {
+                descriptor.version == info.version
+                    || built_at.is_some_and(|built_at| {
+                        built_at_of(descriptor).is_some_and(|required| built_at >= required)
+                    })
+            }
+            Requirement::Minimum(minimum) => built_at.is_some_and(|built_at| built_at >= *minimum),
+        };
+        if up_to_date {
+            Compliance::UpToDate
+        } else {
+            Compliance::Outdated
+        }
+    }
+}
+
+/// How the firmware of a panel compares with the requirement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Compliance {
+    UpToDate,
+    Outdated,
+    /// Built from a work tree with uncommitted changes
+    Dirty,
+}
+
+#[derive(Serialize)]
+struct PanelVersion {
+    compliance: Compliance,
+    #[serde(flatten)]
+    running: BuildInfo,
+}
+
+fn built_at_of(descriptor: &AppDescriptor) -> Option<PrimitiveDateTime> {
+    built_at(&descriptor.build_date, &descriptor.build_time)
+}
+
+/// Returns when a firmware was built, from the date `/status` reports
+/// (`2026-10-18`) or the `__DATE__` format (`Oct 18 2026`), and `HH:MM:SS`.
+fn built_at(date: &str, time: &str) -> Option<PrimitiveDateTime> {
+    let (year, month, day) = match date.split_whitespace().collect::<Vec<_>>()[..] {
+        [month, day, year] => {
+            let month = MONTHS.iter().position(|name| *name == month)? + 1;
+            (year, month as u8, day)
+        }
+        [date] => {
+            let [year, month, day] = date.split('-').collect::<Vec<_>>()[..] else {
+                return None;
+            };
+            (year, month.parse().ok()?, day)
+        }
+        _ => return None,
+    };
+    let date = Date::from_calendar_date(
+        year.parse().ok()?,
+        Month::try_from(month).ok()?,
+        day.parse().ok()?,
+    )
+    .ok()?;
+
+    let [hour, minute, second] = time
+        .split(':')
+        .map(str::parse)
+        .collect::<Result<Vec<u8>, _>>()
+        .ok()?[..]
+    else {
+        return None;
+    };
+    Some(PrimitiveDateTime::new(
+        date,
+        Time::from_hms(hour, minute, second).ok()?,
+    ))
+}
+
+/// Prints the panels grouped by how their firmware compares, nothing in JSON
+/// mode.
+fn print_groups(panels: &[&Panel], report: &FleetReport<PanelVersion>) {
+    if is_json() {
+        return;
+    }
+    let label_width = super::label_width(panels);
+    let version_width = report
+        .succeeded()
+        .filter_map(|outcome| outcome.result.as_ref().ok())
+        .map(|version| version.running.version.len())
+        .max()
+        .unwrap_or(0);
+
+    let groups: [(StyledObject<&str>, &str, Option<Compliance>); 4] = [
+        (style("✓").green(), "Up to date", Some(Compliance::UpToDate)),
+        (style("~").yellow(), "Outdated", Some(Compliance::Outdated)),
+        (style("!").magenta(), "Dirty build", Some(Compliance::Dirty)),
+        (style("✗").red(), "Unreachable", None),
+    ];
+    for (mark, title, compliance) in groups {
+        let lines: Vec<String> = report
+            .outcomes
+            .iter()
+            .filter_map(|outcome| {
+                let name = &outcome.name;
+                match (&outcome.result, compliance) {
+                    (Ok(version), Some(compliance)) if version.compliance == compliance => {
+                        let running = &version.running;
+                        Some(format!(
+                            "{name:<label_width$}  {}  {}",
+                            style(format!("{:<version_width$}", running.version)).cyan(),
+                            style(format!("{} {}", running.build_date, running.build_time)).dim()
+                        ))
+                    }
+                    (Err(e), None) => Some(format!("{name:<label_width$}  {e}")),
+                    _ => None,
+                }
+            })
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        println!("{mark} {title} ({})", lines.len());
+        for line in lines {
+            println!("    {line}");
+        }
+    }
+}
+
+/// Compares the firmware of the panels with the requirement. Returns whether
+/// any panel is outdated or runs a dirty build.
+pub async fn run(panels: &[&Panel], requirement: &Requirement) -> Result<bool> {
+    print_title(&format!(
+        "Comparing {} panel(s) with {}",
+        panels.len(),
+        requirement.describe()
+    ));
+
+    let report = super::fleet(panels)
+        .run(|_, client| async move { client.get_status().await })
+        .await;
+    let report = FleetReport {
+        outcomes: report
+            .outcomes
+            .into_iter()
+            .map(|outcome| PanelOutcome {
+                name: outcome.name,
+                result: outcome.result.map(|running| PanelVersion {
+                    compliance: requirement.check(&running),
+                    running,
+                }),
+                elapsed: outcome.elapsed,
+            })
+            .collect(),
+    };
+    print_groups(panels, &report);
+    super::finish(&report, "one or more panels could not be reached")?;
+
+    Ok(report.succeeded().any(|outcome| {
+        outcome
+            .result
+            .as_ref()
+            .is_ok_and(|version| version.compliance != Compliance::UpToDate)
+    }))
+}
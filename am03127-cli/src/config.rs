@@ -1,45 +1,652 @@
-use std::{fs, path::Path};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
 
-use anyhow::{Context, Result, bail};
-use serde::Deserialize;
+use am03127_client::{ClientError, DiscoveredPanel, MockPanel, PanelClient, SerialPanel};
+use am03127_commands::page::{Lagging, Leading};
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use toml_edit::{Array, ArrayOfTables, DocumentMut, Item, Table, value};
+
+/// Port of the controller's HTTP server if none is configured
+const DEFAULT_HTTP_PORT: u16 = 80;
+/// Config file looked up in the current directory before the XDG directories
+const LOCAL_CONFIG: &str = "panel-config.toml";
+/// Directory in the XDG config directories holding the config files
+const CONFIG_DIR: &str = "am03127";
+
+/// Returns the user's config directory, `$XDG_CONFIG_HOME` or `~/.config`.
+fn user_config_dir() -> Option<PathBuf> {
+    let non_empty = |name| env::var_os(name).filter(|value| !value.is_empty());
+    non_empty("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| non_empty("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+/// Returns the XDG config directories, most important first.
+fn config_dirs() -> Vec<PathBuf> {
+    let system = env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| String::from("/etc/xdg"));
+    user_config_dir()
+        .into_iter()
+        .chain(system.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from))
+        .collect()
+}
+
+/// Returns the config file to use.
+///
+/// Unless a path is given, this is `panel-config.toml` in the current
+/// directory, then `am03127/config.toml` in the XDG config directories. A
+/// profile is looked up as `am03127/<profile>.toml` in the XDG config
+/// directories only. If no file exists, the path in the user's config
+/// directory is returned, where `config init` creates it.
+pub fn locate(path: Option<&Path>, profile: Option<&str>) -> Result<PathBuf> {
+    if let Some(path) = path {
+        return Ok(path.to_path_buf());
+    }
+
+    let file = match profile {
+        Some(profile) => {
+            if profile.is_empty() || profile.starts_with('.') || profile.contains(['/', '\\']) {
+                bail!("invalid profile name '{profile}'");
+            }
+            format!("{profile}.toml")
+        }
+        None if Path::new(LOCAL_CONFIG).exists() => return Ok(PathBuf::from(LOCAL_CONFIG)),
+        None => String::from("config.toml"),
+    };
+    let found = config_dirs()
+        .into_iter()
+        .map(|dir| dir.join(CONFIG_DIR).join(&file))
+        .find(|path| path.exists());
+    match found {
+        Some(path) => Ok(path),
+        None => user_config_dir()
+            .map(|dir| dir.join(CONFIG_DIR).join(&file))
+            .context("no config directory found, set XDG_CONFIG_HOME or HOME, or pass --config"),
+    }
+}
+
+/// Reads an environment variable, `None` if it is not set.
+fn env_var<T: FromStr>(name: &str) -> Result<Option<T>> {
+    match env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| anyhow!("invalid value '{value}' of {name}")),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("invalid value of {name}")),
+    }
+}
+
+/// How a panel is reached
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Transport {
+    /// REST API of the ESP32 controller, `address` is its host name or IP
+    #[default]
+    Http,
+    /// RS232 wired to this machine, `address` is the serial port
+    Serial,
+    /// In-memory panel without hardware, `address` is ignored
+    Mock,
+}
+
+/// Connection settings, given globally in `[client]` or per panel
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientSettings {
+    /// Seconds to wait for a TCP connection
+    pub connect_timeout: Option<f64>,
+    /// Seconds to wait for an answer
+    pub timeout: Option<f64>,
+    /// Seconds to wait for a firmware upload
+    pub ota_timeout: Option<f64>,
+    /// Number of times a failed request is sent again
+    pub retries: Option<u32>,
+    /// Seconds before the first retry, doubled for every further retry
+    pub backoff: Option<f64>,
+}
+
+impl ClientSettings {
+    /// Reads the settings overriding those of every panel from the
+    /// `AM03127_*` environment variables, e.g. `AM03127_TIMEOUT`.
+    fn from_env() -> Result<ClientSettings> {
+        Ok(ClientSettings {
+            connect_timeout: env_var("AM03127_CONNECT_TIMEOUT")?,
+            timeout: env_var("AM03127_TIMEOUT")?,
+            ota_timeout: env_var("AM03127_OTA_TIMEOUT")?,
+            retries: env_var("AM03127_RETRIES")?,
+            backoff: env_var("AM03127_BACKOFF")?,
+        })
+    }
+
+    /// Fills settings missing here with those of `fallback`.
+    fn or(&self, fallback: &ClientSettings) -> ClientSettings {
+        ClientSettings {
+            connect_timeout: self.connect_timeout.or(fallback.connect_timeout),
+            timeout: self.timeout.or(fallback.timeout),
+            ota_timeout: self.ota_timeout.or(fallback.ota_timeout),
+            retries: self.retries.or(fallback.retries),
+            backoff: self.backoff.or(fallback.backoff),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let durations = [
+            ("connect_timeout", self.connect_timeout),
+            ("timeout", self.timeout),
+            ("ota_timeout", self.ota_timeout),
+            ("backoff", self.backoff),
+        ];
+        for (name, value) in durations {
+            if let Some(value) = value
+                && Duration::try_from_secs_f64(value).is_err()
+            {
+                bail!("'{name}' must be a non-negative number of seconds, got {value}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Effects of pages sent with `page` if none are given on the command line,
+/// given globally in `[page]` or per panel
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PageDefaults {
+    pub leading: Option<Leading>,
+    pub lagging: Option<Lagging>,
+    /// Waiting time step, 0 for 0.5 seconds, otherwise seconds (at most 25)
+    pub waiting_time: Option<u8>,
+}
+
+impl PageDefaults {
+    /// Fills settings missing here with those of `fallback`.
+    fn or(&self, fallback: &PageDefaults) -> PageDefaults {
+        PageDefaults {
+            leading: self.leading.clone().or_else(|| fallback.leading.clone()),
+            lagging: self.lagging.clone().or_else(|| fallback.lagging.clone()),
+            waiting_time: self.waiting_time.or(fallback.waiting_time),
+        }
+    }
+}
 
 #[derive(Debug, Deserialize)]
 pub struct Panel {
     pub name: String,
     pub address: String,
+    /// Model of the sign, for reference only
+    pub model: Option<String>,
+    /// Free-form labels to select panels by, e.g. `outdoor`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub transport: Transport,
+    /// ID the panel answers to on a serial line
+    #[serde(default = "default_panel_id")]
+    pub panel_id: u8,
+    /// Port of the controller's HTTP server
+    pub port: Option<u16>,
+    /// Complete URL of the controller, takes precedence over address and port
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub client: ClientSettings,
+    #[serde(default)]
+    pub page: PageDefaults,
+}
+
+fn default_panel_id() -> u8 {
+    1
+}
+
+impl Panel {
+    /// Creates the backend talking to this panel.
+    pub fn connect(&self) -> Result<Box<dyn am03127_client::Panel>, ClientError> {
+        let settings = &self.client;
+        Ok(match self.transport {
+            Transport::Http => {
+                let mut builder = PanelClient::builder(&self.address);
+                if let Some(port) = self.port {
+                    builder = builder.port(port);
+                }
+                if let Some(base_url) = &self.base_url {
+                    builder = builder.base_url(base_url);
+                }
+                if let Some(timeout) = settings.connect_timeout {
+                    builder = builder.connect_timeout(Duration::from_secs_f64(timeout));
+                }
+                if let Some(timeout) = settings.timeout {
+                    builder = builder.timeout(Duration::from_secs_f64(timeout));
+                }
+                if let Some(timeout) = settings.ota_timeout {
+                    builder = builder.ota_timeout(Duration::from_secs_f64(timeout));
+                }
+                if let Some(retries) = settings.retries {
+                    builder = builder.retries(retries);
+                }
+                if let Some(backoff) = settings.backoff {
+                    builder = builder.backoff(Duration::from_secs_f64(backoff));
+                }
+                Box::new(builder.build()?)
+            }
+            Transport::Serial => {
+                let mut panel = SerialPanel::open(&self.address, self.panel_id)?;
+                if let Some(timeout) = settings.timeout {
+                    panel = panel.with_timeout(Duration::from_secs_f64(timeout));
+                }
+                if let Some(retries) = settings.retries {
+                    panel = panel.with_retries(retries);
+                }
+                Box::new(panel)
+            }
+            Transport::Mock => Box::new(MockPanel::new()),
+        })
+    }
+
+    /// Returns the URL of the controller's web interface.
+    pub fn url(&self) -> String {
+        match (&self.base_url, self.port) {
+            (Some(base_url), _) => base_url.clone(),
+            (None, Some(port)) => format!("http://{}:{port}", self.address),
+            (None, None) => format!("http://{}", self.address),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
+    /// Connection settings for all panels without their own
+    #[serde(default)]
+    pub client: ClientSettings,
+    /// Page effects for all panels without their own
+    #[serde(default)]
+    pub page: PageDefaults,
+    /// Named groups of panels, given by names or globs
+    #[serde(default)]
+    pub groups: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
     pub panels: Vec<Panel>,
 }
 
+/// Returns whether `name` matches `pattern`, in which `*` stands for any
+/// number of characters and `?` for a single one. Case is ignored.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((star, tried)) => {
+                    backtrack = Some((star, tried + 1));
+                    p = star + 1;
+                    n = tried + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 impl Config {
+    /// Reads the config file, applying the connection settings given as
+    /// `AM03127_*` environment variables to every panel.
     pub fn from_file(path: &Path) -> Result<Self> {
-        let contents = fs::read_to_string(path)
-            .with_context(|| format!("failed to read '{}'", path.display()))?;
-        toml::from_str(&contents).context("failed to parse config")
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => bail!(
+                "config file '{}' not found, create it with `am03127-cli config init`",
+                path.display()
+            ),
+            Err(e) => return Err(e).with_context(|| format!("failed to read '{}'", path.display())),
+        };
+        let mut config = Config::parse(&contents)
+            .with_context(|| format!("invalid config file '{}'", path.display()))?;
+
+        let overrides = ClientSettings::from_env()?;
+        overrides
+            .validate()
+            .context("invalid AM03127_* environment variable")?;
+        for panel in &mut config.panels {
+            panel.client = overrides.or(&panel.client);
+        }
+        Ok(config)
     }
 
+    /// Parses and checks a config, filling panel settings with the global ones.
+    fn parse(contents: &str) -> Result<Self> {
+        let mut config: Config = toml::from_str(contents).context("failed to parse config")?;
+        config.client.validate()?;
+        for (i, panel) in config.panels.iter().enumerate() {
+            if config.panels[..i]
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(&panel.name))
+            {
+                bail!("panel '{}' is configured more than once", panel.name);
+            }
+        }
+        for panel in &mut config.panels {
+            panel
+                .client
+                .validate()
+                .with_context(|| format!("invalid settings of panel '{}'", panel.name))?;
+            panel.client = panel.client.or(&config.client);
+            panel.page = panel.page.or(&config.page);
+        }
+        for (group, members) in &config.groups {
+            for member in members {
+                if !config.panels.iter().any(|p| glob_match(member, &p.name)) {
+                    bail!("group '{group}' names unknown panel '{member}'");
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Returns the panels matching any of the names or globs.
     pub fn select_panels<'a>(&'a self, names: &[String]) -> Result<Vec<&'a Panel>> {
-        if names.is_empty() {
+        self.select(names, &[], &[])
+    }
+
+    /// Returns the panels matching any of the names or globs, belonging to
+    /// any of the groups or having any of the tags. Without any of them, all
+    /// panels are returned.
+    pub fn select<'a>(
+        &'a self,
+        names: &[String],
+        groups: &[String],
+        tags: &[String],
+    ) -> Result<Vec<&'a Panel>> {
+        if names.is_empty() && groups.is_empty() && tags.is_empty() {
             return Ok(self.panels.iter().collect());
         }
 
+        let matches = |pattern: &str| self.panels.iter().any(|p| glob_match(pattern, &p.name));
         let unknown: Vec<&str> = names
             .iter()
-            .filter(|n| !self.panels.iter().any(|p| p.name.eq_ignore_ascii_case(n)))
             .map(String::as_str)
+            .filter(|n| !matches(n))
             .collect();
-
         if !unknown.is_empty() {
             bail!("unknown panel(s): {}", unknown.join(", "));
         }
 
+        let mut patterns: Vec<&str> = names.iter().map(String::as_str).collect();
+        for group in groups {
+            let Some(members) = self.groups.get(group) else {
+                let known: Vec<&str> = self.groups.keys().map(String::as_str).collect();
+                bail!(
+                    "unknown group '{group}', configured groups: {}",
+                    if known.is_empty() { String::from("none") } else { known.join(", ") }
+                );
+            };
+            patterns.extend(members.iter().map(String::as_str));
+        }
+
+        let has_tag = |panel: &Panel, tag: &str| panel.tags.iter().any(|t| t.eq_ignore_ascii_case(tag));
+        let untagged: Vec<&str> = tags
+            .iter()
+            .map(String::as_str)
+            .filter(|tag| !self.panels.iter().any(|p| has_tag(p, tag)))
+            .collect();
+        if !untagged.is_empty() {
+            bail!("no panel has the tag(s): {}", untagged.join(", "));
+        }
+
         Ok(self
             .panels
             .iter()
-            .filter(|p| names.iter().any(|n| n.eq_ignore_ascii_case(&p.name)))
+            .filter(|p| {
+                patterns.iter().any(|pattern| glob_match(pattern, &p.name))
+                    || tags.iter().any(|tag| has_tag(p, tag))
+            })
             .collect())
     }
+
+    /// Returns the names of the groups `panel` belongs to.
+    pub fn groups_of(&self, panel: &Panel) -> Vec<&str> {
+        self.groups
+            .iter()
+            .filter(|(_, members)| members.iter().any(|m| glob_match(m, &panel.name)))
+            .map(|(group, _)| group.as_str())
+            .collect()
+    }
+}
+
+/// Change made to the config file for a discovered panel
+pub enum ConfigChange {
+    Added,
+    /// The panel is configured with another address, e.g. after a new DHCP
+    /// lease
+    Moved {
+        from: String,
+    },
+    Unchanged,
+}
+
+/// Adds discovered panels to the config file, creating it if needed.
+///
+/// Panels already configured with the discovered address are left as they
+/// are. Otherwise panels configured with the same name, ignoring ASCII case,
+/// get the discovered address. Panels announcing a name another panel of the
+/// same discovery already took, e.g. when flashed with the same image, are
+/// added with a numbered name. Everything else in the file is kept as it is.
+///
+/// Returns the configured name and the change for each panel, in order.
+pub fn add_discovered(
+    path: &Path,
+    panels: &[DiscoveredPanel],
+) -> Result<Vec<(String, ConfigChange)>> {
+    let mut document = read_document(path)?;
+    let entries = panel_entries(&mut document)?;
+
+    let mut changes = Vec::new();
+    let mut claimed = Vec::new();
+    for panel in panels {
+        let address = panel.address.to_string();
+        let port = (panel.port != DEFAULT_HTTP_PORT).then_some(i64::from(panel.port));
+        let configured = |entry: &Table| {
+            (
+                entry
+                    .get("address")
+                    .and_then(Item::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                entry.get("port").and_then(Item::as_integer),
+            )
+        };
+        let entry_name =
+            |entry: &Table| entry.get("name").and_then(Item::as_str).map(str::to_string);
+
+        let at_address = entries
+            .iter()
+            .position(|entry| configured(entry) == (address.clone(), port));
+        let by_name = || {
+            entries.iter().enumerate().position(|(index, entry)| {
+                !claimed.contains(&index)
+                    && entry_name(entry).is_some_and(|name| name.eq_ignore_ascii_case(&panel.name))
+            })
+        };
+
+        let (name, change) = if let Some(index) = at_address {
+            claimed.push(index);
+            let name = entry_name(entries.get(index).unwrap()).unwrap_or_default();
+            (name, ConfigChange::Unchanged)
+        } else if let Some(index) = by_name() {
+            claimed.push(index);
+            let entry = entries.get_mut(index).unwrap();
+            let from = match configured(entry) {
+                (address, Some(port)) => format!("{address}:{port}"),
+                (address, None) => address,
+            };
+            set_address(entry, &address, port);
+            let name = entry_name(entry).unwrap_or_default();
+            (name, ConfigChange::Moved { from })
+        } else {
+            let taken = |name: &str| {
+                entries.iter().any(|entry| {
+                    entry_name(entry).is_some_and(|taken| taken.eq_ignore_ascii_case(name))
+                })
+            };
+            let mut name = panel.name.clone();
+            let mut number = 2;
+            while taken(&name) {
+                name = format!("{}-{number}", panel.name);
+                number += 1;
+            }
+            let mut entry = Table::new();
+            entry.insert("name", value(&name));
+            set_address(&mut entry, &address, port);
+            entries.push(entry);
+            claimed.push(entries.len() - 1);
+            (name, ConfigChange::Added)
+        };
+        changes.push((name, change));
+    }
+
+    save_document(path, &document)?;
+    Ok(changes)
+}
+
+fn set_address(entry: &mut Table, address: &str, port: Option<i64>) {
+    entry.insert("address", value(address));
+    match port {
+        Some(port) => {
+            entry.insert("port", value(port));
+        }
+        None => {
+            entry.remove("port");
+        }
+    }
+}
+
+/// Reads the config file for editing, an empty document if it does not exist.
+fn read_document(path: &Path) -> Result<DocumentMut> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("failed to read '{}'", path.display())),
+    };
+    contents
+        .parse()
+        .with_context(|| format!("failed to parse '{}'", path.display()))
+}
+
+fn panel_entries(document: &mut DocumentMut) -> Result<&mut ArrayOfTables> {
+    document
+        .entry("panels")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .context("'panels' must be an array of tables")
+}
+
+/// Writes an edited config file if it is still a valid config. The file is
+/// replaced at once, so it is never left half written.
+fn save_document(path: &Path, document: &DocumentMut) -> Result<()> {
+    let contents = document.to_string();
+    Config::parse(&contents).context("the change would make the config invalid")?;
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create '{}'", dir.display()))?;
+    }
+    let temporary = path.with_extension("toml.tmp");
+    fs::write(&temporary, contents)
+        .and_then(|()| fs::rename(&temporary, path))
+        .with_context(|| format!("failed to write '{}'", path.display()))
+}
+
+/// Panel to add to the config file, optional settings are left out if unset
+pub struct NewPanel {
+    pub name: String,
+    pub address: String,
+    pub port: Option<u16>,
+    pub transport: Option<Transport>,
+    pub panel_id: Option<u8>,
+    pub model: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// Adds a panel to the config file, creating it if needed.
+pub fn add_panel(path: &Path, panel: NewPanel) -> Result<()> {
+    let mut document = read_document(path)?;
+    let entries = panel_entries(&mut document)?;
+    if entries.iter().any(|entry| {
+        entry
+            .get("name")
+            .and_then(Item::as_str)
+            .is_some_and(|name| name.eq_ignore_ascii_case(&panel.name))
+    }) {
+        bail!("panel '{}' is already configured", panel.name);
+    }
+
+    let mut entry = Table::new();
+    entry.insert("name", value(&panel.name));
+    set_address(&mut entry, &panel.address, panel.port.map(i64::from));
+    if let Some(transport) = panel.transport {
+        let transport = clap::ValueEnum::to_possible_value(&transport).expect("no variant is skipped");
+        entry.insert("transport", value(transport.get_name()));
+    }
+    if let Some(panel_id) = panel.panel_id {
+        entry.insert("panel_id", value(i64::from(panel_id)));
+    }
+    if let Some(model) = &panel.model {
+        entry.insert("model", value(model));
+    }
+    if !panel.tags.is_empty() {
+        entry.insert("tags", value(panel.tags.iter().collect::<Array>()));
+    }
+    entries.push(entry);
+
+    save_document(path, &document)
+}
+
+/// Removes a panel from the config file and from the groups naming it.
+pub fn remove_panel(path: &Path, name: &str) -> Result<()> {
+    let mut document = read_document(path)?;
+    let entries = panel_entries(&mut document)?;
+    let position = entries.iter().position(|entry| {
+        entry
+            .get("name")
+            .and_then(Item::as_str)
+            .is_some_and(|configured| configured.eq_ignore_ascii_case(name))
+    });
+    let Some(position) = position else {
+        bail!("unknown panel '{name}'");
+    };
+    entries.remove(position);
+
+    if let Some(groups) = document.get_mut("groups").and_then(Item::as_table_like_mut) {
+        for (_, members) in groups.iter_mut() {
+            if let Some(members) = members.as_array_mut() {
+                members.retain(|member| {
+                    !member
+                        .as_str()
+                        .is_some_and(|member| member.eq_ignore_ascii_case(name))
+                });
+            }
+        }
+    }
+
+    save_document(path, &document)
 }
@@ -1,14 +1,102 @@
-use std::time::Duration;
+use std::{
+    io::{IsTerminal, stdout},
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use console::style;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
 
 const TICK_STRINGS: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏", ""];
 const TICK_INTERVAL: Duration = Duration::from_millis(80);
 
-/// Prints a bold section title to stdout.
+/// How commands print their results
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Colored text, with spinners if stdout is a terminal
+    #[default]
+    Table,
+    /// Text without colors and spinners
+    Plain,
+    /// A single JSON document on stdout
+    Json,
+}
+
+static OUTPUT: OnceLock<OutputFormat> = OnceLock::new();
+/// Whether a JSON document was printed already
+static JSON_PRINTED: AtomicBool = AtomicBool::new(false);
+
+/// Sets the output format for the rest of the program.
+pub fn set_output(format: OutputFormat) {
+    if format != OutputFormat::Table {
+        console::set_colors_enabled(false);
+    }
+    let _ = OUTPUT.set(format);
+}
+
+pub fn output() -> OutputFormat {
+    OUTPUT.get().copied().unwrap_or_default()
+}
+
+pub fn is_json() -> bool {
+    output() == OutputFormat::Json
+}
+
+/// Returns whether progress is animated, which needs a terminal.
+fn is_animated() -> bool {
+    output() == OutputFormat::Table && stdout().is_terminal()
+}
+
+/// Returns a draw target for progress on stdout, hidden unless animated.
+fn draw_target() -> ProgressDrawTarget {
+    if is_animated() {
+        ProgressDrawTarget::stdout()
+    } else {
+        ProgressDrawTarget::hidden()
+    }
+}
+
+/// Prints a bold section title to stdout, nothing in JSON mode.
 pub fn print_title(title: &str) {
-    println!("{}", style(title).bold());
+    if !is_json() {
+        println!("{}", style(title).bold());
+    }
+}
+
+/// Prints a line of text output, nothing in JSON mode.
+pub fn print_line(line: impl std::fmt::Display) {
+    if !is_json() {
+        println!("{line}");
+    }
+}
+
+/// Prints `value` as the JSON document of the command.
+pub fn print_json(value: &impl Serialize) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{json}"),
+        Err(e) => eprintln!("error: failed to encode the output as JSON: {e}"),
+    }
+    JSON_PRINTED.store(true, Ordering::Relaxed);
+}
+
+/// Returns whether the command printed its JSON document already.
+pub fn json_printed() -> bool {
+    JSON_PRINTED.load(Ordering::Relaxed)
+}
+
+/// Replaces a spinner or progress bar with `message`. Without animation the
+/// message is printed as a line of its own.
+fn finish_bar(pb: &ProgressBar, message: String) {
+    if is_animated() {
+        pb.finish_with_message(message);
+    } else {
+        pb.finish_and_clear();
+        print_line(message);
+    }
 }
 
 /// A group of per-task spinners rendered together.
@@ -23,36 +111,43 @@ impl SpinnerGroup {
             .unwrap()
             .tick_strings(TICK_STRINGS);
         Self {
-            mp: MultiProgress::new(),
+            mp: MultiProgress::with_draw_target(draw_target()),
             style,
         }
     }
 
     /// Add a spinning entry with the given label. The returned [`ProgressBar`]
-    /// should be finished (e.g. via [`ProgressBar::finish_with_message`]) once
-    /// the task completes.
+    /// should be finished with [`SpinnerGroup::finish`] once the task
+    /// completes.
     pub fn add(&self, label: impl Into<String>) -> ProgressBar {
         let pb = self.mp.add(ProgressBar::new_spinner());
         pb.set_style(self.style.clone());
         pb.set_message(label.into());
-        pb.enable_steady_tick(TICK_INTERVAL);
+        if is_animated() {
+            pb.enable_steady_tick(TICK_INTERVAL);
+        }
         pb
     }
+
+    /// Replace the spinner with the given message, e.g. the task's outcome.
+    pub fn finish(pb: &ProgressBar, message: impl Into<String>) {
+        finish_bar(pb, message.into());
+    }
 }
 
 /// A group of per-task byte-count progress bars rendered together.
-pub struct _ProgressGroup {
+pub struct ProgressGroup {
     mp: MultiProgress,
     style: ProgressStyle,
     total: u64,
 }
 
-impl _ProgressGroup {
-    pub fn _new(total_bytes: u64, label_width: usize) -> Self {
-        let template = format!("{{msg:{label_width}}} [{{bar:40.cyan/blue}}] {{percent}}%");
+impl ProgressGroup {
+    pub fn new(total_bytes: u64, label_width: usize) -> Self {
+        let template = format!("  {{msg:{label_width}}}  [{{bar:40.cyan/blue}}] {{percent}}%");
         let style = ProgressStyle::with_template(&template).unwrap();
         Self {
-            mp: MultiProgress::new(),
+            mp: MultiProgress::with_draw_target(draw_target()),
             style,
             total: total_bytes,
         }
@@ -60,10 +155,16 @@ impl _ProgressGroup {
 
     /// Add a progress bar entry for the given label. The returned [`ProgressBar`]
     /// tracks bytes and should be finished once the upload completes.
-    pub fn _add(&self, label: impl Into<String>) -> ProgressBar {
+    pub fn add(&self, label: impl Into<String>) -> ProgressBar {
         let pb = self.mp.add(ProgressBar::new(self.total));
         pb.set_style(self.style.clone());
         pb.set_message(label.into());
         pb
     }
+
+    /// Replace the bar with the given message, e.g. the outcome of the upload.
+    pub fn finish(pb: &ProgressBar, message: impl Into<String>) {
+        pb.set_style(ProgressStyle::with_template("{msg}").unwrap());
+        finish_bar(pb, message.into());
+    }
 }
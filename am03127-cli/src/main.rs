@@ -1,60 +1,238 @@
 mod commands;
 mod config;
 mod console;
+mod tui;
 
-use anyhow::Result;
+use std::time::Duration;
+
+use am03127_client::{ClientError, Discovery};
+use anyhow::{Context, Result};
 use clap::Parser;
-use config::Config;
+use config::{Config, NewPanel};
+use time::Time;
 
-use crate::commands::{Args, Commands};
+use crate::commands::{
+    Args, Commands, ConfigCommands, ErrorJson, PageCommands, ScheduleCommands, backup::Backup,
+    plan::ContentFile, update::Rollout, versions::Requirement,
+};
 
 #[tokio::main]
 async fn main() {
     if let Err(e) = run().await {
         eprintln!("error: {e:#}");
-        std::process::exit(1);
+        let kind = error_kind(&e);
+        if console::is_json() && !console::json_printed() {
+            let error = ErrorJson {
+                kind,
+                message: format!("{e:#}"),
+            };
+            console::print_json(&serde_json::json!({ "ok": false, "error": error }));
+        }
+        std::process::exit(exit_code(kind));
+    }
+}
+
+/// Returns why the command failed, telling apart why a panel request failed.
+fn error_kind(error: &anyhow::Error) -> &'static str {
+    match error.chain().find_map(|e| e.downcast_ref::<ClientError>()) {
+        Some(e) => commands::error_kind(e),
+        None => "error",
+    }
+}
+
+fn exit_code(kind: &str) -> i32 {
+    match kind {
+        "offline" => 3,
+        "not_found" => 4,
+        "rejected" => 5,
+        "panel_failure" => 6,
+        _ => 1,
     }
 }
 
 async fn run() -> Result<()> {
     let args = Args::parse();
+    console::set_output(args.output);
+    let config_path = config::locate(args.config.as_deref(), args.profile.as_deref())?;
 
     match args.command {
-        Commands::Status { panels } => {
-            let config = Config::from_file(&args.config)?;
-            let targets = config.select_panels(&panels)?;
+        Commands::Status { select } => {
+            let config = Config::from_file(&config_path)?;
+            let targets = select.resolve(&config)?;
             commands::status::run(&targets).await?;
         }
-        Commands::Update { firmware, panels } => {
-            let config = Config::from_file(&args.config)?;
-            let targets = config.select_panels(&panels)?;
-            commands::update::run(&targets, &firmware).await?;
+        Commands::Update { firmware, rollout, canary, batch_size, health_timeout, select } => {
+            let config = Config::from_file(&config_path)?;
+            let targets = select.resolve(&config)?;
+            if rollout {
+                let health_timeout = Duration::try_from_secs_f64(health_timeout)
+                    .context("health timeout must be a non-negative number of seconds")?;
+                let rollout = Rollout { canary, batch_size: batch_size.into(), health_timeout };
+                commands::update::rollout(&targets, &firmware, &rollout).await?;
+            } else {
+                commands::update::run(&targets, &firmware).await?;
+            }
         }
+        Commands::Config { command } => match command {
+            ConfigCommands::Init { force } => commands::config::init(&config_path, force)?,
+            ConfigCommands::Add { name, address, port, transport, panel_id, model, tags } => {
+                let panel = NewPanel { name, address, port, transport, panel_id, model, tags };
+                commands::config::add(&config_path, panel)?;
+            }
+            ConfigCommands::Remove { name } => commands::config::remove(&config_path, &name)?,
+            ConfigCommands::Validate {} => commands::config::validate(&config_path)?,
+        },
         Commands::List {} => {
-            let config = Config::from_file(&args.config)?;
-            commands::list::run(&config.panels);
+            let config = Config::from_file(&config_path)?;
+            commands::list::run(&config);
+        }
+        Commands::Discover { timeout, write, port, interface } => {
+            let timeout = Duration::try_from_secs_f64(timeout)
+                .context("timeout must be a non-negative number of seconds")?;
+            let mut discovery = Discovery::new().with_timeout(timeout).with_port(port);
+            if let Some(interface) = interface {
+                discovery = discovery.with_interface(interface);
+            }
+            commands::discover::run(discovery, &config_path, write).await?;
         }
         Commands::Open { panel } => {
-            let config = Config::from_file(&args.config)?;
+            let config = Config::from_file(&config_path)?;
             let targets = config.select_panels(&[panel])?;
             commands::open::run(targets[0])?;
         }
         Commands::Info { firmware } => {
             commands::info::run(&firmware)?;
         }
-        Commands::Clock { panels } => {
-            let config = Config::from_file(&args.config)?;
-            let targets = config.select_panels(&panels)?;
+        Commands::Versions { firmware, minimum, select } => {
+            let config = Config::from_file(&config_path)?;
+            let targets = select.resolve(&config)?;
+            let requirement = match (firmware, minimum) {
+                (Some(firmware), _) => Requirement::firmware(&firmware)?,
+                (None, Some(minimum)) => Requirement::Minimum(
+                    commands::schedule::parse_date_time(&minimum, Time::MIDNIGHT)?,
+                ),
+                (None, None) => unreachable!("clap requires a firmware or a minimum"),
+            };
+            if commands::versions::run(&targets, &requirement).await? {
+                std::process::exit(commands::versions::EXIT_NONCOMPLIANT);
+            }
+        }
+        Commands::Tui { interval, select } => {
+            let config = Config::from_file(&config_path)?;
+            let targets = select.resolve(&config)?;
+            let interval = Duration::try_from_secs_f64(interval)
+                .ok()
+                .filter(|interval| !interval.is_zero())
+                .context("interval must be a positive number of seconds")?;
+            tui::run(&targets, interval).await?;
+        }
+        Commands::Clock { select } => {
+            let config = Config::from_file(&config_path)?;
+            let targets = select.resolve(&config)?;
             commands::clock::run(&targets).await?;
         }
-        Commands::Page { id, message, leading, lagging, waiting_time, panels } => {
-            let config = Config::from_file(&args.config)?;
-            let targets = config.select_panels(&panels)?;
-            let leading = commands::page::parse_leading(&leading)
-                .map_err(|e| anyhow::anyhow!(e))?;
-            let lagging = commands::page::parse_lagging(&lagging)
+        Commands::Page(page) => {
+            let config = Config::from_file(&config_path)?;
+            match page.command {
+                Some(PageCommands::List { select }) => {
+                    let targets = select.resolve(&config)?;
+                    commands::page::list(&targets).await?;
+                }
+                Some(PageCommands::Get { id, select }) => {
+                    let targets = select.resolve(&config)?;
+                    commands::page::get(&targets, id).await?;
+                }
+                Some(PageCommands::Delete { id, select }) => {
+                    let targets = select.resolve(&config)?;
+                    commands::page::delete(&targets, id).await?;
+                }
+                Some(PageCommands::Copy { id, from, to_id, force, select }) => {
+                    let source = config.select_panels(&[from])?[0];
+                    let mut targets = select.resolve(&config)?;
+                    if select.is_empty() {
+                        targets.retain(|panel| panel.name != source.name);
+                    }
+                    commands::page::copy(source, &targets, id, to_id, force).await?;
+                }
+                None => {
+                    let targets = page.select.resolve(&config)?;
+                    let leading = page.leading.as_deref().map(commands::page::parse_leading)
+                        .transpose()
+                        .map_err(|e| anyhow::anyhow!(e))?;
+                    let lagging = page.lagging.as_deref().map(commands::page::parse_lagging)
+                        .transpose()
+                        .map_err(|e| anyhow::anyhow!(e))?;
+                    // Both are required unless a subcommand is given.
+                    let (Some(id), Some(message)) = (page.id, page.message) else {
+                        unreachable!("clap requires the page id and message");
+                    };
+                    commands::page::run(&targets, id, message, leading, lagging, page.waiting_time, page.force).await?;
+                }
+            }
+        }
+        Commands::Schedule { command } => {
+            let config = Config::from_file(&config_path)?;
+            match command {
+                ScheduleCommands::Set { id, period, pages, select } => {
+                    let targets = select.resolve(&config)?;
+                    commands::schedule::set(&targets, id, &period, &pages).await?;
+                }
+                ScheduleCommands::List { select } => {
+                    let targets = select.resolve(&config)?;
+                    commands::schedule::list(&targets).await?;
+                }
+                ScheduleCommands::Get { id, select } => {
+                    let targets = select.resolve(&config)?;
+                    commands::schedule::get(&targets, id).await?;
+                }
+                ScheduleCommands::Delete { id, select } => {
+                    let targets = select.resolve(&config)?;
+                    commands::schedule::delete(&targets, id).await?;
+                }
+            }
+        }
+        Commands::Backup { file, select } => {
+            let config = Config::from_file(&config_path)?;
+            let targets = select.resolve(&config)?;
+            commands::backup::backup(&targets, &file).await?;
+        }
+        Commands::Restore { file, from, wipe, dry_run, select } => {
+            let config = Config::from_file(&config_path)?;
+            let backup = Backup::load(&file)?;
+            let targets = if select.is_empty() && from.is_none() {
+                config.select_panels(&backup.panel_names())?
+            } else {
+                select.resolve(&config)?
+            };
+            commands::backup::restore(&targets, &backup, from.as_deref(), wipe, dry_run).await?;
+        }
+        Commands::Plan { file, select } => {
+            let config = Config::from_file(&config_path)?;
+            let content = ContentFile::load(&file, &config)?;
+            let targets = select.resolve(&config)?;
+            if commands::plan::plan(&targets, &content).await? {
+                std::process::exit(commands::plan::EXIT_CHANGES);
+            }
+        }
+        Commands::Apply { file, select } => {
+            let config = Config::from_file(&config_path)?;
+            let content = ContentFile::load(&file, &config)?;
+            let targets = select.resolve(&config)?;
+            commands::plan::apply(&targets, &content).await?;
+        }
+        Commands::Image { image, page, graphic_page, width, dithering, threshold, force, select } => {
+            let config = Config::from_file(&config_path)?;
+            let targets = select.resolve(&config)?;
+            let dithering = commands::image::parse_dithering(&dithering)
                 .map_err(|e| anyhow::anyhow!(e))?;
-            commands::page::run(&targets, id, message, leading, lagging, waiting_time).await?;
+            let options = am03127_image::Options {
+                width,
+                dithering,
+                threshold,
+                first_page: graphic_page,
+                ..Default::default()
+            };
+            commands::image::run(&targets, &image, page, options, force).await?;
         }
     }
 
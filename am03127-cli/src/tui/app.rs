@@ -0,0 +1,493 @@
+use std::time::{Duration, Instant};
+
+use am03127_client::{BuildInfo, ClientError};
+use am03127_commands::{
+    markup,
+    page::{Lagging, Leading, Page, WaitingModeAndSpeed, WaitingTime},
+    schedule::Schedule,
+};
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    widgets::{ListState, TableState},
+};
+
+use crate::config::{PageDefaults, Panel, Transport};
+
+/// Highest waiting time step (Z, 25 seconds)
+const MAX_WAITING_TIME: u8 = 25;
+
+/// Live state of a panel, as of its last refresh
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub status: Result<BuildInfo, ClientError>,
+    pub pages: Result<Vec<Page>, ClientError>,
+    pub schedules: Result<Vec<Schedule>, ClientError>,
+}
+
+/// A panel as shown on the dashboard
+pub struct PanelView {
+    pub name: String,
+    pub address: String,
+    pub defaults: PageDefaults,
+    /// Picked to receive edited pages
+    pub selected: bool,
+    /// Connected to a serial port, so pages can be sent but not read back
+    pub write_only: bool,
+    pub snapshot: Option<Snapshot>,
+    pub refreshed: Option<Instant>,
+}
+
+impl PanelView {
+    pub fn pages(&self) -> &[Page] {
+        match &self.snapshot {
+            Some(Snapshot {
+                pages: Ok(pages), ..
+            }) => pages,
+            _ => &[],
+        }
+    }
+}
+
+/// Request from the dashboard to the tasks talking to the panels
+pub enum Action {
+    Refresh,
+    Push { page: Page, panels: Vec<String> },
+}
+
+/// List of the dashboard which has the keyboard focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Panels,
+    Pages,
+}
+
+/// Field of the page editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Id,
+    Message,
+    Leading,
+    Lagging,
+    WaitingMode,
+    WaitingTime,
+}
+
+impl Field {
+    const ALL: [Field; 6] = [
+        Field::Id,
+        Field::Message,
+        Field::Leading,
+        Field::Lagging,
+        Field::WaitingMode,
+        Field::WaitingTime,
+    ];
+
+    fn step(self, forward: bool) -> Field {
+        let index = Field::ALL
+            .iter()
+            .position(|field| *field == self)
+            .unwrap_or(0);
+        let count = Field::ALL.len();
+        let next = if forward {
+            (index + 1) % count
+        } else {
+            (index + count - 1) % count
+        };
+        Field::ALL[next]
+    }
+
+    /// Returns the number of choices of an effect field.
+    fn choices(self) -> Option<usize> {
+        match self {
+            Field::Leading => Some(Leading::ALL.len()),
+            Field::Lagging => Some(Lagging::ALL.len()),
+            Field::WaitingMode => Some(WaitingModeAndSpeed::ALL.len()),
+            _ => None,
+        }
+    }
+}
+
+/// Page being edited, effects given by their index in the `ALL` lists
+pub struct Editor {
+    pub id: char,
+    pub message: String,
+    /// Position of the cursor in the message, in characters
+    pub cursor: usize,
+    pub leading: usize,
+    pub lagging: usize,
+    pub waiting_mode: usize,
+    pub waiting_time: u8,
+    pub field: Field,
+    /// Open effect picker of the current field
+    pub picker: Option<ListState>,
+}
+
+fn index_of<T: PartialEq>(all: &[T], value: &T) -> usize {
+    all.iter().position(|item| item == value).unwrap_or(0)
+}
+
+impl Editor {
+    fn new(page: &Page) -> Self {
+        Editor {
+            id: page.id,
+            message: page.message.clone(),
+            cursor: page.message.chars().count(),
+            leading: index_of(Leading::ALL, &page.leading),
+            lagging: index_of(Lagging::ALL, &page.lagging),
+            waiting_mode: index_of(WaitingModeAndSpeed::ALL, &page.waiting_mode_and_speed),
+            waiting_time: page.waiting_time.value(),
+            field: Field::Message,
+            picker: None,
+        }
+    }
+
+    pub fn page(&self) -> Page {
+        Page::new(
+            self.id,
+            self.message.clone(),
+            Leading::ALL[self.leading].clone(),
+            Lagging::ALL[self.lagging].clone(),
+            WaitingModeAndSpeed::ALL[self.waiting_mode].clone(),
+            WaitingTime::new(self.waiting_time),
+        )
+    }
+
+    /// Returns the chosen index of an effect field.
+    pub fn choice(&mut self, field: Field) -> Option<&mut usize> {
+        match field {
+            Field::Leading => Some(&mut self.leading),
+            Field::Lagging => Some(&mut self.lagging),
+            Field::WaitingMode => Some(&mut self.waiting_mode),
+            _ => None,
+        }
+    }
+
+    /// Changes the value of the current field to the next or previous one.
+    fn cycle(&mut self, forward: bool) {
+        let field = self.field;
+        match field {
+            Field::Id => {
+                let offset = self.id as u8 - b'A';
+                let offset = if forward {
+                    (offset + 1) % 26
+                } else {
+                    (offset + 25) % 26
+                };
+                self.id = (b'A' + offset) as char;
+            }
+            Field::Message => {
+                self.cursor = if forward {
+                    (self.cursor + 1).min(self.message.chars().count())
+                } else {
+                    self.cursor.saturating_sub(1)
+                };
+            }
+            Field::WaitingTime => {
+                self.waiting_time = if forward {
+                    (self.waiting_time + 1).min(MAX_WAITING_TIME)
+                } else {
+                    self.waiting_time.saturating_sub(1)
+                };
+            }
+            Field::Leading | Field::Lagging | Field::WaitingMode => {
+                let count = field.choices().unwrap_or(1);
+                if let Some(choice) = self.choice(field) {
+                    *choice = if forward {
+                        (*choice + 1) % count
+                    } else {
+                        (*choice + count - 1) % count
+                    };
+                }
+            }
+        }
+    }
+
+    /// Returns the byte offset of the cursor in the message.
+    fn cursor_offset(&self) -> usize {
+        self.message
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(self.message.len(), |(offset, _)| offset)
+    }
+
+    fn edit_message(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(character) => {
+                let offset = self.cursor_offset();
+                self.message.insert(offset, character);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let offset = self.cursor_offset();
+                self.message.remove(offset);
+            }
+            KeyCode::Delete if self.cursor < self.message.chars().count() => {
+                let offset = self.cursor_offset();
+                self.message.remove(offset);
+            }
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.message.chars().count(),
+            _ => {}
+        }
+    }
+
+    /// Handles a key while the effect picker is open.
+    fn pick(&mut self, key: KeyEvent) {
+        let field = self.field;
+        let count = field.choices().unwrap_or(1);
+        let Some(picker) = &mut self.picker else {
+            return;
+        };
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                let selected = picker.selected().unwrap_or(0);
+                picker.select(Some((selected + count - 1) % count));
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let selected = picker.selected().unwrap_or(0);
+                picker.select(Some((selected + 1) % count));
+            }
+            KeyCode::Enter => {
+                let selected = picker.selected();
+                self.picker = None;
+                if let (Some(selected), Some(choice)) = (selected, self.choice(field)) {
+                    *choice = selected;
+                }
+            }
+            KeyCode::Esc => self.picker = None,
+            _ => {}
+        }
+    }
+}
+
+/// State of the dashboard
+pub struct App {
+    pub panels: Vec<PanelView>,
+    pub panel_table: TableState,
+    pub page_table: TableState,
+    pub focus: Focus,
+    pub editor: Option<Editor>,
+    /// Last outcome to show, and whether it is an error
+    pub message: Option<(String, bool)>,
+    pub interval: Duration,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(panels: &[&Panel], interval: Duration) -> Self {
+        App {
+            panels: panels
+                .iter()
+                .map(|panel| {
+                    let write_only = panel.transport == Transport::Serial;
+                    PanelView {
+                        name: panel.name.clone(),
+                        // The serial port rather than a web interface
+                        address: if write_only {
+                            panel.address.clone()
+                        } else {
+                            panel.url()
+                        },
+                        defaults: panel.page.clone(),
+                        selected: false,
+                        write_only,
+                        snapshot: None,
+                        refreshed: None,
+                    }
+                })
+                .collect(),
+            panel_table: TableState::default().with_selected(Some(0)),
+            page_table: TableState::default().with_selected(Some(0)),
+            focus: Focus::Panels,
+            editor: None,
+            message: None,
+            interval,
+            quit: false,
+        }
+    }
+
+    /// Returns the panel under the cursor.
+    pub fn current(&self) -> Option<&PanelView> {
+        self.panel_table
+            .selected()
+            .and_then(|index| self.panels.get(index))
+    }
+
+    pub fn update(&mut self, name: &str, snapshot: Snapshot) {
+        if let Some(panel) = self.panels.iter_mut().find(|panel| panel.name == name) {
+            panel.snapshot = Some(snapshot);
+            panel.refreshed = Some(Instant::now());
+        }
+    }
+
+    pub fn show(&mut self, message: impl Into<String>, error: bool) {
+        self.message = Some((message.into(), error));
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return None;
+        }
+        if self.editor.is_some() {
+            return self.handle_editor_key(key);
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Panels => Focus::Pages,
+                    Focus::Pages => Focus::Panels,
+                };
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(false),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(true),
+            KeyCode::Char(' ') => {
+                if let Some(index) = self.panel_table.selected()
+                    && let Some(panel) = self.panels.get_mut(index)
+                {
+                    panel.selected = !panel.selected;
+                }
+            }
+            KeyCode::Char('a') => {
+                let select = !self.panels.iter().all(|panel| panel.selected);
+                for panel in &mut self.panels {
+                    panel.selected = select;
+                }
+            }
+            KeyCode::Char('r') => {
+                self.show("refreshing...", false);
+                return Some(Action::Refresh);
+            }
+            KeyCode::Char('e') | KeyCode::Enter => self.edit_current(),
+            KeyCode::Char('n') => self.edit_new(),
+            _ => {}
+        }
+        None
+    }
+
+    fn move_cursor(&mut self, forward: bool) {
+        let (table, count) = match self.focus {
+            Focus::Panels => (&mut self.panel_table, self.panels.len()),
+            Focus::Pages => {
+                let count = self.current().map_or(0, |panel| panel.pages().len());
+                (&mut self.page_table, count)
+            }
+        };
+        if count == 0 {
+            return;
+        }
+        let selected = table.selected().unwrap_or(0).min(count - 1);
+        table.select(Some(if forward {
+            (selected + 1) % count
+        } else {
+            (selected + count - 1) % count
+        }));
+    }
+
+    /// Opens the editor on the page under the cursor, or on a new page if the
+    /// panel has none.
+    fn edit_current(&mut self) {
+        let page = self.current().and_then(|panel| {
+            let pages = panel.pages();
+            let index = self.page_table.selected().unwrap_or(0);
+            pages.get(index.min(pages.len().saturating_sub(1))).cloned()
+        });
+        match page {
+            Some(page) => self.editor = Some(Editor::new(&page)),
+            None => self.edit_new(),
+        }
+    }
+
+    /// Opens the editor on a new page with the first free ID of the panel
+    /// under the cursor and its page defaults.
+    fn edit_new(&mut self) {
+        let Some(panel) = self.current() else {
+            return;
+        };
+        let pages = panel.pages();
+        let id = ('A'..='Z')
+            .find(|id| pages.iter().all(|page| page.id != *id))
+            .unwrap_or('A');
+        let defaults = &panel.defaults;
+        let page = Page::new(
+            id,
+            String::new(),
+            defaults.leading.clone().unwrap_or_default(),
+            defaults.lagging.clone().unwrap_or_default(),
+            WaitingModeAndSpeed::default(),
+            WaitingTime::new(defaults.waiting_time.unwrap_or(0)),
+        );
+        self.editor = Some(Editor::new(&page));
+    }
+
+    /// Returns the panels edited pages are sent to: the selected ones, or the
+    /// one under the cursor if none is selected.
+    pub fn targets(&self) -> Vec<String> {
+        let selected: Vec<String> = self
+            .panels
+            .iter()
+            .filter(|panel| panel.selected)
+            .map(|panel| panel.name.clone())
+            .collect();
+        if selected.is_empty() {
+            self.current()
+                .map(|panel| panel.name.clone())
+                .into_iter()
+                .collect()
+        } else {
+            selected
+        }
+    }
+
+    fn handle_editor_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let targets = self.targets();
+        let editor = self.editor.as_mut()?;
+        if editor.picker.is_some() {
+            editor.pick(key);
+            return None;
+        }
+
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('s') {
+            let page = editor.page();
+            if let Err(e) = markup::validate(&page.message) {
+                self.show(format!("page '{}' not sent: {e}", page.id), true);
+                return None;
+            }
+            self.show(
+                format!("sending page '{}' to {}...", page.id, targets.join(", ")),
+                false,
+            );
+            self.editor = None;
+            return Some(Action::Push {
+                page,
+                panels: targets,
+            });
+        }
+
+        match key.code {
+            KeyCode::Esc => self.editor = None,
+            KeyCode::Tab | KeyCode::Down => editor.field = editor.field.step(true),
+            KeyCode::BackTab | KeyCode::Up => editor.field = editor.field.step(false),
+            KeyCode::Left => editor.cycle(false),
+            KeyCode::Right => editor.cycle(true),
+            KeyCode::Enter => {
+                let field = editor.field;
+                if let Some(choice) = editor.choice(field).map(|choice| *choice) {
+                    editor.picker = Some(ListState::default().with_selected(Some(choice)));
+                }
+            }
+            KeyCode::Char(id) if editor.field == Field::Id => {
+                let id = id.to_ascii_uppercase();
+                if id.is_ascii_uppercase() {
+                    editor.id = id;
+                }
+            }
+            _ if editor.field == Field::Message => editor.edit_message(key),
+            _ => {}
+        }
+        None
+    }
+}
@@ -0,0 +1,270 @@
+mod app;
+mod ui;
+
+use std::{collections::HashSet, io::IsTerminal, sync::Arc, time::Duration};
+
+use am03127_client::{ClientError, Fleet, FleetReport, Panel as Backend};
+use am03127_commands::{memory::MemoryEstimate, page::Page};
+use anyhow::{Result, bail};
+use app::{Action, App, Snapshot};
+use ratatui::crossterm::event::{self, Event as TermEvent, KeyEventKind};
+use tokio::sync::{Notify, mpsc};
+
+use crate::{
+    commands::page::send_page,
+    config::{Panel, Transport},
+    console::is_json,
+};
+
+/// How long the dashboard waits for a key before drawing again
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Message from the tasks talking to the panels to the dashboard
+enum Event {
+    Refreshed(String, Snapshot),
+    Pushed {
+        id: char,
+        report: FleetReport<Option<MemoryEstimate>>,
+    },
+}
+
+/// Name of a panel and its client, or why it could not be connected
+type Connection = (String, Result<Arc<dyn Backend>, ClientError>);
+
+/// Clients of the panels, connected once for the whole session
+struct Connections {
+    panels: Vec<Connection>,
+    /// Panels on a serial port, which can not be read back
+    serial: HashSet<String>,
+}
+
+impl Connections {
+    fn new(panels: &[&Panel]) -> Self {
+        Connections {
+            panels: panels
+                .iter()
+                .map(|panel| (panel.name.clone(), panel.connect().map(Arc::from)))
+                .collect(),
+            serial: panels
+                .iter()
+                .filter(|panel| panel.transport == Transport::Serial)
+                .map(|panel| panel.name.clone())
+                .collect(),
+        }
+    }
+
+    /// Returns the names of the panels which can be read back, and of those
+    /// which failed to connect.
+    fn readable(&self) -> Vec<String> {
+        self.panels
+            .iter()
+            .filter(|(name, client)| client.is_err() || !self.serial.contains(name))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Builds a fleet of the panels named in `names`, all panels if `None`.
+    fn fleet(&self, names: Option<&[String]>) -> Fleet {
+        let mut fleet = Fleet::new();
+        for (name, client) in &self.panels {
+            if names.is_some_and(|names| !names.contains(name)) {
+                continue;
+            }
+            match client {
+                Ok(client) => fleet.add_shared(name, client.clone()),
+                Err(e) => fleet.add_unavailable(name, e.clone()),
+            }
+        }
+        fleet
+    }
+}
+
+/// Reads the status, pages and schedules of a panel. Pages and schedules are
+/// not asked for if the status fails.
+async fn snapshot(client: Arc<dyn Backend>) -> Snapshot {
+    let status = client.get_status().await;
+    if let Err(e) = &status {
+        return Snapshot {
+            status: status.clone(),
+            pages: Err(e.clone()),
+            schedules: Err(e.clone()),
+        };
+    }
+    Snapshot {
+        status,
+        pages: client.get_pages().await,
+        schedules: client.get_schedules().await,
+    }
+}
+
+/// Refreshes all panels every `interval`, or right away once `refresh` is
+/// notified, sending every panel's snapshot as soon as it arrives. Panels on
+/// a serial port are not asked, as they can not answer.
+async fn refresh(
+    connections: Arc<Connections>,
+    interval: Duration,
+    refresh: Arc<Notify>,
+    events: mpsc::UnboundedSender<Event>,
+) {
+    let fleet = connections.fleet(Some(&connections.readable()));
+    loop {
+        fleet
+            .run_with(
+                |_, client| async move { Ok::<_, ClientError>(snapshot(client).await) },
+                |outcome| {
+                    let snapshot = match &outcome.result {
+                        Ok(snapshot) => snapshot.clone(),
+                        Err(e) => Snapshot {
+                            status: Err(e.clone()),
+                            pages: Err(e.clone()),
+                            schedules: Err(e.clone()),
+                        },
+                    };
+                    let _ = events.send(Event::Refreshed(outcome.name.clone(), snapshot));
+                },
+            )
+            .await;
+        if events.is_closed() {
+            return;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = refresh.notified() => {}
+        }
+    }
+}
+
+/// Sends `page` to the panels, refreshing all panels once done.
+async fn push(
+    connections: Arc<Connections>,
+    page: Page,
+    panels: Vec<String>,
+    refresh: Arc<Notify>,
+    events: mpsc::UnboundedSender<Event>,
+) {
+    let report = connections
+        .fleet(Some(&panels))
+        .run(|name, client| {
+            let page = page.clone();
+            let force = connections.serial.contains(name);
+            async move { send_page(client.as_ref(), &page, &[], force).await }
+        })
+        .await;
+    let _ = events.send(Event::Pushed {
+        id: page.id,
+        report,
+    });
+    refresh.notify_one();
+}
+
+/// Shows the outcome of sending a page on the status line.
+fn show_pushed(app: &mut App, id: char, report: &FleetReport<Option<MemoryEstimate>>) {
+    let failed: Vec<String> = report
+        .failed()
+        .filter_map(|outcome| {
+            let e = outcome.result.as_ref().err()?;
+            Some(format!("{}: {e}", outcome.name))
+        })
+        .collect();
+    let almost_full: Vec<&str> = report
+        .succeeded()
+        .filter(|outcome| matches!(outcome.result, Ok(Some(_))))
+        .map(|outcome| outcome.name.as_str())
+        .collect();
+
+    if !failed.is_empty() {
+        app.show(
+            format!("page '{id}' not sent to {}", failed.join(", ")),
+            true,
+        );
+    } else if !almost_full.is_empty() {
+        app.show(
+            format!(
+                "page '{id}' sent, panel memory almost full on {}",
+                almost_full.join(", ")
+            ),
+            false,
+        );
+    } else {
+        app.show(
+            format!("page '{id}' sent to {} panel(s)", report.outcomes.len()),
+            false,
+        );
+    }
+}
+
+/// Draws the dashboard and handles keys until the user quits. Runs on the
+/// current thread, blocking it while waiting for keys.
+fn event_loop(
+    app: &mut App,
+    connections: &Arc<Connections>,
+    refresh: &Arc<Notify>,
+    sender: &mpsc::UnboundedSender<Event>,
+    receiver: &mut mpsc::UnboundedReceiver<Event>,
+) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = (|| -> Result<()> {
+        while !app.quit {
+            while let Ok(event) = receiver.try_recv() {
+                match event {
+                    Event::Refreshed(name, snapshot) => app.update(&name, snapshot),
+                    Event::Pushed { id, report } => show_pushed(app, id, &report),
+                }
+            }
+            terminal.draw(|frame| ui::draw(frame, app))?;
+
+            if !event::poll(FRAME_INTERVAL)? {
+                continue;
+            }
+            let TermEvent::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match app.handle_key(key) {
+                Some(Action::Refresh) => refresh.notify_one(),
+                Some(Action::Push { page, panels }) => {
+                    tokio::spawn(push(
+                        connections.clone(),
+                        page,
+                        panels,
+                        refresh.clone(),
+                        sender.clone(),
+                    ));
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    })();
+    ratatui::restore();
+    result
+}
+
+/// Runs the live dashboard of the panels until the user quits.
+pub async fn run(panels: &[&Panel], interval: Duration) -> Result<()> {
+    if is_json() || !std::io::stdout().is_terminal() {
+        bail!("the dashboard needs a terminal, use the other commands for scripts");
+    }
+    if panels.is_empty() {
+        bail!("no panels to show");
+    }
+
+    let connections = Arc::new(Connections::new(panels));
+    let refresh_now = Arc::new(Notify::new());
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let refresher = tokio::spawn(refresh(
+        connections.clone(),
+        interval,
+        refresh_now.clone(),
+        sender.clone(),
+    ));
+
+    let mut app = App::new(panels, interval);
+    let result = tokio::task::block_in_place(|| {
+        event_loop(&mut app, &connections, &refresh_now, &sender, &mut receiver)
+    });
+    refresher.abort();
+    result
+}
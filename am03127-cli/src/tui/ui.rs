@@ -0,0 +1,479 @@
+use am03127_commands::{
+    formatting::Clock,
+    markup::{self, Token},
+    page::{Lagging, Leading, WaitingModeAndSpeed, WaitingTime},
+};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, Paragraph, Row, Table, Wrap},
+};
+use time::OffsetDateTime;
+
+use super::app::{App, Editor, Field, Focus, PanelView};
+use crate::commands::{
+    error_kind,
+    page::{effect_name, short_message, waiting_time_name},
+    schedule::format_date_time,
+};
+
+/// Colors cycled through by the rainbow color codes
+const RAINBOW: [Color; 4] = [Color::Red, Color::Yellow, Color::Green, Color::LightRed];
+
+/// Color of the highlighted row of the list with the focus
+const HIGHLIGHT: Style = Style::new()
+    .bg(Color::DarkGray)
+    .add_modifier(Modifier::BOLD);
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [header, panels, details, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(app.panels.len().min(12) as u16 + 3),
+        Constraint::Min(6),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_header(frame, app, header);
+    draw_panels(frame, app, panels);
+    draw_details(frame, app, details);
+    draw_footer(frame, app, footer);
+    if app.editor.is_some() {
+        draw_editor(frame, app);
+    }
+}
+
+fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let online = app
+        .panels
+        .iter()
+        .filter(|panel| matches!(&panel.snapshot, Some(snapshot) if snapshot.status.is_ok()))
+        .count();
+    let offline = app
+        .panels
+        .iter()
+        .filter(|panel| matches!(&panel.snapshot, Some(snapshot) if snapshot.status.is_err()))
+        .count();
+    let write_only = app
+        .panels
+        .iter()
+        .filter(|panel| panel.write_only && panel.snapshot.is_none())
+        .count();
+    let mut spans = vec![
+        Span::from(" AM03127 panels  ").bold(),
+        Span::from(format!("{online} online")).green(),
+        Span::from("  "),
+        Span::from(format!("{offline} offline")).red(),
+    ];
+    if write_only > 0 {
+        spans.push(Span::from("  "));
+        spans.push(Span::from(format!("{write_only} write-only")).yellow());
+    }
+    let interval = app.interval.as_secs_f64();
+    spans.push(Span::from(format!("  refreshed every {interval}s")).dark_gray());
+    let line = Line::from(spans);
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// Returns the state of a panel and its color.
+fn state(panel: &PanelView) -> Span<'static> {
+    match &panel.snapshot {
+        None if panel.write_only => Span::from("write-only").yellow(),
+        None => Span::from("waiting").dark_gray(),
+        Some(snapshot) => match &snapshot.status {
+            Ok(_) => Span::from("online").green(),
+            Err(e) => Span::from(error_kind(e).replace('_', " ")).red(),
+        },
+    }
+}
+
+/// Returns how many items a panel answered with, or "-".
+fn count<T, E>(list: Option<&Result<Vec<T>, E>>) -> String {
+    match list {
+        Some(Ok(list)) => list.len().to_string(),
+        _ => String::from("-"),
+    }
+}
+
+fn draw_panels(frame: &mut Frame, app: &mut App, area: Rect) {
+    let rows = app.panels.iter().map(|panel| {
+        let snapshot = panel.snapshot.as_ref();
+        let (version, built) = match snapshot.map(|snapshot| &snapshot.status) {
+            Some(Ok(info)) => (
+                info.version.clone(),
+                format!("{} {}", info.build_date, info.build_time),
+            ),
+            _ => (String::new(), String::new()),
+        };
+        let age = panel
+            .refreshed
+            .map(|refreshed| format!("{}s ago", refreshed.elapsed().as_secs()))
+            .unwrap_or_default();
+        Row::new(vec![
+            Span::from(if panel.selected { "●" } else { " " }).cyan(),
+            Span::from(panel.name.clone()),
+            state(panel),
+            Span::from(version).cyan(),
+            Span::from(built).dark_gray(),
+            Span::from(count(snapshot.map(|snapshot| &snapshot.pages))),
+            Span::from(count(snapshot.map(|snapshot| &snapshot.schedules))),
+            Span::from(age).dark_gray(),
+        ])
+    });
+
+    let name_width = app
+        .panels
+        .iter()
+        .map(|panel| panel.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(5) as u16;
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Length(name_width),
+            Constraint::Length(13),
+            Constraint::Min(10),
+            Constraint::Length(20),
+            Constraint::Length(5),
+            Constraint::Length(9),
+            Constraint::Length(8),
+        ],
+    )
+    .header(
+        Row::new([
+            "",
+            "Panel",
+            "State",
+            "Version",
+            "Built",
+            "Pages",
+            "Schedules",
+            "Updated",
+        ])
+        .dark_gray(),
+    )
+    .block(focus_block(" Panels ", app.focus == Focus::Panels))
+    .row_highlight_style(HIGHLIGHT);
+    frame.render_stateful_widget(table, area, &mut app.panel_table);
+}
+
+fn focus_block(title: &str, focused: bool) -> Block<'_> {
+    let block = Block::bordered().title(title);
+    if focused { block.cyan() } else { block }
+}
+
+fn draw_details(frame: &mut Frame, app: &mut App, area: Rect) {
+    let [pages_area, schedules_area] =
+        Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)]).areas(area);
+    let Some(panel) = app.current() else {
+        return;
+    };
+    let title = format!(" Pages of {} ({}) ", panel.name, panel.address);
+    let focused = app.focus == Focus::Pages;
+
+    match panel.snapshot.as_ref().map(|snapshot| &snapshot.pages) {
+        Some(Ok(pages)) => {
+            let rows: Vec<Row> = pages
+                .iter()
+                .map(|page| {
+                    Row::new(vec![
+                        page.id.to_string(),
+                        effect_name(&page.leading),
+                        effect_name(&page.lagging),
+                        effect_name(&page.waiting_mode_and_speed),
+                        waiting_time_name(&page.waiting_time),
+                        short_message(&page.message),
+                    ])
+                })
+                .collect();
+            let table = Table::new(
+                rows,
+                [
+                    Constraint::Length(2),
+                    Constraint::Length(19),
+                    Constraint::Length(19),
+                    Constraint::Length(16),
+                    Constraint::Length(5),
+                    Constraint::Min(10),
+                ],
+            )
+            .header(
+                Row::new(["ID", "Leading", "Lagging", "Waiting", "Time", "Message"]).dark_gray(),
+            )
+            .block(focus_block(&title, focused))
+            .row_highlight_style(if focused { HIGHLIGHT } else { Style::new() });
+            let selected = app.page_table.selected().unwrap_or(0);
+            app.page_table
+                .select(Some(selected.min(pages.len().saturating_sub(1))));
+            frame.render_stateful_widget(table, pages_area, &mut app.page_table);
+        }
+        other => frame.render_widget(
+            unavailable(
+                other.map(|pages| pages.as_ref().err()),
+                panel.write_only,
+                &title,
+                focused,
+            ),
+            pages_area,
+        ),
+    }
+
+    let panel = app.current().expect("checked above");
+    let title = " Schedules ";
+    match panel.snapshot.as_ref().map(|snapshot| &snapshot.schedules) {
+        Some(Ok(schedules)) => {
+            let rows: Vec<Row> = schedules
+                .iter()
+                .map(|schedule| {
+                    Row::new(vec![
+                        schedule.id.to_string(),
+                        format!(
+                            "{} to {}",
+                            format_date_time(&schedule.from),
+                            format_date_time(&schedule.to)
+                        ),
+                        schedule.pages.clone(),
+                    ])
+                })
+                .collect();
+            let table = Table::new(
+                rows,
+                [
+                    Constraint::Length(2),
+                    Constraint::Length(36),
+                    Constraint::Min(5),
+                ],
+            )
+            .header(Row::new(["ID", "Period", "Pages"]).dark_gray())
+            .block(Block::bordered().title(title));
+            frame.render_widget(table, schedules_area);
+        }
+        other => frame.render_widget(
+            unavailable(
+                other.map(|schedules| schedules.as_ref().err()),
+                panel.write_only,
+                title,
+                false,
+            ),
+            schedules_area,
+        ),
+    }
+}
+
+/// Returns a box telling why a list of a panel is not shown.
+fn unavailable<'a, E: std::fmt::Display>(
+    error: Option<Option<&E>>,
+    write_only: bool,
+    title: &'a str,
+    focused: bool,
+) -> Paragraph<'a> {
+    let text = match error {
+        None if write_only => {
+            Span::from("panels on a serial port can not be read back").dark_gray()
+        }
+        None => Span::from("waiting for the panel...").dark_gray(),
+        Some(Some(e)) => Span::from(e.to_string()).red(),
+        Some(None) => Span::from(""),
+    };
+    Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .block(focus_block(title, focused))
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let help = if app.editor.is_some() {
+        "Tab field  ←/→ change  Enter pick effect  Ctrl-S send  Esc cancel"
+    } else {
+        "↑/↓ move  Tab pages/panels  Space select  a all  e edit  n new page  r refresh  q quit"
+    };
+    let line = match &app.message {
+        Some((message, true)) => Line::from(vec![
+            Span::from(format!(" {message}")).red(),
+            Span::from(format!("  {help}")).dark_gray(),
+        ]),
+        Some((message, false)) => Line::from(vec![
+            Span::from(format!(" {message}")),
+            Span::from(format!("  {help}")).dark_gray(),
+        ]),
+        None => Line::from(Span::from(format!(" {help}")).dark_gray()),
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn draw_editor(frame: &mut Frame, app: &App) {
+    let Some(editor) = &app.editor else {
+        return;
+    };
+    let targets = app.targets().join(", ");
+    let area = frame
+        .area()
+        .centered(Constraint::Percentage(80), Constraint::Length(16));
+    frame.render_widget(Clear, area);
+    let block = Block::bordered()
+        .title(format!(" Page '{}' for {targets} ", editor.id))
+        .cyan();
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [form, preview] =
+        Layout::vertical([Constraint::Length(8), Constraint::Min(4)]).areas(inner);
+    let waiting_time = waiting_time_name(&WaitingTime::new(editor.waiting_time));
+    let fields = [
+        (Field::Id, "ID", editor.id.to_string()),
+        (Field::Message, "Message", editor.message.clone()),
+        (
+            Field::Leading,
+            "Leading",
+            effect_name(&Leading::ALL[editor.leading]),
+        ),
+        (
+            Field::Lagging,
+            "Lagging",
+            effect_name(&Lagging::ALL[editor.lagging]),
+        ),
+        (
+            Field::WaitingMode,
+            "Waiting",
+            effect_name(&WaitingModeAndSpeed::ALL[editor.waiting_mode]),
+        ),
+        (Field::WaitingTime, "Time", waiting_time),
+    ];
+    let lines: Vec<Line> = fields
+        .into_iter()
+        .map(|(field, label, value)| {
+            let label = Span::from(format!(" {label:<9}")).dark_gray();
+            if field == editor.field {
+                Line::from(vec![label, Span::from(value).reversed()])
+            } else {
+                Line::from(vec![label, Span::from(value)])
+            }
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), form);
+    if editor.field == Field::Message && editor.picker.is_none() {
+        let column = form.x + 10 + editor.cursor as u16;
+        frame.set_cursor_position((column.min(form.right().saturating_sub(1)), form.y + 1));
+    }
+
+    draw_preview(frame, editor, preview);
+    if editor.picker.is_some() {
+        draw_picker(frame, editor, area);
+    }
+}
+
+/// Draws the message as the panel would show it, with its colors, the current
+/// clock and placeholders for graphics, below the effects it is shown with.
+fn draw_preview(frame: &mut Frame, editor: &Editor, area: Rect) {
+    let block = Block::bordered().title(" Preview ").dark_gray();
+    let effects = Line::from(
+        format!(
+            "{} → {} {} → {}",
+            effect_name(&Leading::ALL[editor.leading]),
+            effect_name(&WaitingModeAndSpeed::ALL[editor.waiting_mode]),
+            waiting_time_name(&WaitingTime::new(editor.waiting_time)),
+            effect_name(&Lagging::ALL[editor.lagging]),
+        )
+        .dark_gray(),
+    );
+
+    let message = match markup::parse(&editor.message) {
+        Ok(tokens) => render_tokens(tokens),
+        Err(e) => Line::from(Span::from(e.to_string()).red()),
+    };
+    let paragraph = Paragraph::new(vec![effects, message])
+        .wrap(Wrap { trim: false })
+        .block(block)
+        .style(Style::new().bg(Color::Black));
+    frame.render_widget(paragraph, area);
+}
+
+/// Returns the foreground and background of a `<CX>` color code.
+fn text_color(code: char) -> (Color, Color) {
+    let orange = Color::Rgb(255, 140, 0);
+    match code {
+        'A' => (Color::Rgb(140, 0, 0), Color::Reset),
+        'C' => (Color::Rgb(255, 90, 90), Color::Reset),
+        'D' => (Color::Rgb(0, 140, 0), Color::Reset),
+        'E' => (Color::Green, Color::Reset),
+        'F' => (Color::Rgb(130, 255, 130), Color::Reset),
+        'G' => (Color::Rgb(150, 80, 0), Color::Reset),
+        'H' => (orange, Color::Reset),
+        'I' => (Color::Rgb(255, 190, 80), Color::Reset),
+        'J' => (Color::Yellow, Color::Reset),
+        'K' => (Color::Rgb(180, 255, 0), Color::Reset),
+        'L' => (Color::Black, Color::Red),
+        'M' => (Color::Black, Color::Green),
+        'N' => (Color::Black, orange),
+        'P' => (Color::Red, Color::Rgb(0, 140, 0)),
+        'Q' => (Color::Green, Color::Rgb(140, 0, 0)),
+        _ => (Color::Red, Color::Reset),
+    }
+}
+
+fn render_tokens(tokens: Vec<Token>) -> Line<'static> {
+    let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+    let mut color = 'B';
+    let mut rainbow = 0;
+    let mut spans = Vec::new();
+    for token in tokens {
+        let text = match token {
+            Token::Text(text) => text,
+            Token::European(code) => markup::plain_text(&format!("<U{code:02X}>")),
+            Token::Clock(Clock::Date) => format!(
+                "{:02}/{:02}/{:02}",
+                now.day(),
+                u8::from(now.month()),
+                now.year() % 100
+            ),
+            Token::Clock(Clock::Time) => format!("{:02}:{:02}", now.hour(), now.minute()),
+            Token::Graphic(graphic) => format!("[G{}{}]", graphic.page, graphic.block),
+            Token::Color(code) => {
+                color = code;
+                continue;
+            }
+            Token::Font(_) | Token::Bell(_) | Token::Column(_) => continue,
+        };
+        for character in text.chars() {
+            let (foreground, background) = match color {
+                'R' | 'S' => {
+                    rainbow += 1;
+                    (RAINBOW[rainbow % RAINBOW.len()], Color::Reset)
+                }
+                code => text_color(code),
+            };
+            spans.push(Span::styled(
+                character.to_string(),
+                Style::new().fg(foreground).bg(background),
+            ));
+        }
+    }
+    Line::from(spans)
+}
+
+/// Draws the list of effects of the current field over the editor.
+fn draw_picker(frame: &mut Frame, editor: &Editor, editor_area: Rect) {
+    let (title, names): (&str, Vec<String>) = match editor.field {
+        Field::Leading => ("Leading", Leading::ALL.iter().map(effect_name).collect()),
+        Field::Lagging => ("Lagging", Lagging::ALL.iter().map(effect_name).collect()),
+        Field::WaitingMode => (
+            "Waiting",
+            WaitingModeAndSpeed::ALL.iter().map(effect_name).collect(),
+        ),
+        _ => return,
+    };
+    let area = editor_area.centered(
+        Constraint::Length(30),
+        Constraint::Length(names.len().min(14) as u16 + 2),
+    );
+    frame.render_widget(Clear, area);
+    let list = List::new(names.into_iter().map(ListItem::new))
+        .block(Block::bordered().title(format!(" {title} ")).cyan())
+        .highlight_style(HIGHLIGHT)
+        .highlight_symbol("▶ ");
+    let mut state = editor.picker.unwrap_or_default();
+    frame.render_stateful_widget(list, area, &mut state);
+}
@@ -0,0 +1,145 @@
+//! Synchronous client for programs without an async runtime.
+
+use std::{future::Future, sync::Arc};
+
+use am03127_commands::{
+    graphic::GraphicBlock, page::Page, realtime_clock::DateTime, schedule::Schedule,
+};
+use tokio::runtime::Runtime;
+
+use crate::{BuildInfo, ClientError, Firmware, PanelClientBuilder, Result};
+
+/// Blocking HTTP client for a single AM03127 panel.
+///
+/// Has the same methods as the async [`crate::PanelClient`], which it runs on
+/// a runtime of its own. Every call blocks until the controller answered.
+/// Calling it from within an async runtime panics.
+#[derive(Clone)]
+pub struct PanelClient {
+    client: crate::PanelClient,
+    runtime: Arc<Runtime>,
+}
+
+impl PanelClient {
+    /// Creates a client with the default timeouts and retries.
+    pub fn new(address: &str) -> Self {
+        Self::builder(address)
+            .build_blocking()
+            .expect("default client settings are valid")
+    }
+
+    pub fn builder(address: &str) -> PanelClientBuilder {
+        PanelClientBuilder::new(address)
+    }
+
+    /// Wraps an async client.
+    pub fn from_async(client: crate::PanelClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| ClientError::Other(e.to_string()))?;
+        Ok(Self {
+            client,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    // ── Status ────────────────────────────────────────────────────────────────
+
+    pub fn get_status(&self) -> Result<BuildInfo> {
+        self.block_on(self.client.get_status())
+    }
+
+    // ── Clock ─────────────────────────────────────────────────────────────────
+
+    pub fn set_clock(&self, dt: &DateTime) -> Result<()> {
+        self.block_on(self.client.set_clock(dt))
+    }
+
+    // ── Pages ─────────────────────────────────────────────────────────────────
+
+    /// Returns the page with the given ID or `None` if the panel has no such page.
+    pub fn get_page(&self, id: char) -> Result<Option<Page>> {
+        self.block_on(self.client.get_page(id))
+    }
+
+    pub fn set_page(&self, page: &Page) -> Result<()> {
+        self.block_on(self.client.set_page(page))
+    }
+
+    pub fn delete_page(&self, id: char) -> Result<()> {
+        self.block_on(self.client.delete_page(id))
+    }
+
+    pub fn get_pages(&self) -> Result<Vec<Page>> {
+        self.block_on(self.client.get_pages())
+    }
+
+    pub fn set_pages(&self, pages: &[Page]) -> Result<()> {
+        self.block_on(self.client.set_pages(pages))
+    }
+
+    // ── Schedules ─────────────────────────────────────────────────────────────
+
+    /// Returns the schedule with the given ID or `None` if the panel has no
+    /// such schedule.
+    pub fn get_schedule(&self, id: char) -> Result<Option<Schedule>> {
+        self.block_on(self.client.get_schedule(id))
+    }
+
+    pub fn set_schedule(&self, schedule: &Schedule) -> Result<()> {
+        self.block_on(self.client.set_schedule(schedule))
+    }
+
+    pub fn delete_schedule(&self, id: char) -> Result<()> {
+        self.block_on(self.client.delete_schedule(id))
+    }
+
+    pub fn get_schedules(&self) -> Result<Vec<Schedule>> {
+        self.block_on(self.client.get_schedules())
+    }
+
+    pub fn set_schedules(&self, schedules: &[Schedule]) -> Result<()> {
+        self.block_on(self.client.set_schedules(schedules))
+    }
+
+    // ── Graphics ──────────────────────────────────────────────────────────────
+
+    /// Sends a graphic block to the panel. Graphic blocks are not persisted by
+    /// the controller.
+    pub fn set_graphic(&self, block: &GraphicBlock) -> Result<()> {
+        self.block_on(self.client.set_graphic(block))
+    }
+
+    // ── Control ───────────────────────────────────────────────────────────────
+
+    /// Deletes all pages, schedules and graphics from the panel.
+    pub fn reset(&self) -> Result<()> {
+        self.block_on(self.client.reset())
+    }
+
+    /// Checks and uploads new firmware via OTA. The panel reboots
+    /// automatically after a successful upload.
+    pub fn update_firmware(&self, firmware: &[u8]) -> Result<()> {
+        self.block_on(self.client.update_firmware(firmware))
+    }
+
+    /// Streams new firmware via OTA, calling `progress` with the number of
+    /// bytes sent so far and the size of the image.
+    pub fn update_firmware_with_progress(
+        &self,
+        firmware: &Firmware,
+        progress: impl Fn(u64, u64) + Send + Sync + 'static,
+    ) -> Result<()> {
+        self.block_on(
+            self.client
+                .update_firmware_with_progress(firmware, progress),
+        )
+    }
+
+    // ── Helpers ───────────────────────────────────────────────────────────────
+
+    fn block_on<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        self.runtime.block_on(future)
+    }
+}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use crate::{ClientError, PanelClient, Result};
+
+/// Time to wait for a TCP connection to the controller
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time to wait for the answer to a regular request
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time to wait for a firmware upload to complete
+pub const DEFAULT_OTA_TIMEOUT: Duration = Duration::from_secs(300);
+/// Number of times a failed request is sent again
+pub const DEFAULT_RETRIES: u32 = 2;
+/// Delay before the first retry, doubled for every further retry
+pub const DEFAULT_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Builder for a [`PanelClient`] with custom timeouts, retries and address.
+///
+/// Start with [`PanelClient::builder`].
+#[derive(Debug, Clone)]
+pub struct PanelClientBuilder {
+    address: String,
+    port: Option<u16>,
+    base_url: Option<String>,
+    connect_timeout: Duration,
+    timeout: Duration,
+    ota_timeout: Duration,
+    retries: u32,
+    backoff: Duration,
+}
+
+impl PanelClientBuilder {
+    /// Starts a builder for the controller at `address` (host name or IP).
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            port: None,
+            base_url: None,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            timeout: DEFAULT_TIMEOUT,
+            ota_timeout: DEFAULT_OTA_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+        }
+    }
+
+    /// Sets the TCP port of the controller's HTTP server.
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Sets the complete base URL, e.g. for a panel behind a reverse proxy.
+    /// Takes precedence over the address and port.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Sets how long to wait for a TCP connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Sets how long to wait for the answer to a regular request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how long to wait for a firmware upload to complete.
+    pub fn ota_timeout(mut self, timeout: Duration) -> Self {
+        self.ota_timeout = timeout;
+        self
+    }
+
+    /// Sets how often a failed request is sent again.
+    ///
+    /// Requests which never reached the controller are always retried.
+    /// Timeouts and controller failures are only retried for requests that
+    /// can safely be repeated, which excludes firmware uploads.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets the delay before the first retry. The delay doubles with every
+    /// further retry.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn build(self) -> Result<PanelClient> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.timeout)
+            .build()
+            .map_err(|e| ClientError::Other(e.to_string()))?;
+
+        let base_url = match (self.base_url, self.port) {
+            (Some(base_url), _) => base_url,
+            (None, Some(port)) => format!("http://{}:{port}", self.address),
+            (None, None) => format!("http://{}", self.address),
+        };
+
+        Ok(PanelClient {
+            client,
+            base_url,
+            ota_timeout: self.ota_timeout,
+            retries: self.retries,
+            backoff: self.backoff,
+        })
+    }
+
+    /// Builds a [`crate::blocking::PanelClient`] for programs without an
+    /// async runtime.
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> Result<crate::blocking::PanelClient> {
+        crate::blocking::PanelClient::from_async(self.build()?)
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::Duration,
+};
+
+use am03127_commands::discovery::{Announcement, DISCOVERY_GROUP, DISCOVERY_PORT, PROBE};
+use socket2::SockRef;
+use tokio::{net::UdpSocket, time::Instant};
+
+use crate::{PanelClient, Result};
+
+/// Time to wait for panels to announce themselves
+pub const DEFAULT_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Panel which answered a discovery probe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredPanel {
+    pub name: String,
+    /// Firmware version of the controller
+    pub version: String,
+    pub address: IpAddr,
+    /// TCP port of the controller's HTTP server
+    pub port: u16,
+}
+
+impl DiscoveredPanel {
+    /// Returns the base URL of the controller's HTTP server.
+    pub fn url(&self) -> String {
+        format!("http://{}:{}", self.address, self.port)
+    }
+
+    /// Creates a client for the panel with the default settings.
+    pub fn client(&self) -> PanelClient {
+        PanelClient::builder(&self.address.to_string())
+            .port(self.port)
+            .build()
+            .expect("default client settings are valid")
+    }
+}
+
+/// Finds panels on the local network.
+///
+/// Sends a probe to the discovery multicast group and as broadcast, then
+/// collects the announcements of all controllers answering in time.
+#[derive(Debug, Clone)]
+pub struct Discovery {
+    port: u16,
+    interface: Ipv4Addr,
+    timeout: Duration,
+    broadcast: bool,
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        Self {
+            port: DISCOVERY_PORT,
+            interface: Ipv4Addr::UNSPECIFIED,
+            timeout: DEFAULT_DISCOVERY_TIMEOUT,
+            broadcast: true,
+        }
+    }
+}
+
+impl Discovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the UDP port the probe is sent to.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Sets the address of the interface the multicast probe is sent from,
+    /// e.g. [`Ipv4Addr::LOCALHOST`] to find panels on this machine.
+    pub fn with_interface(mut self, interface: Ipv4Addr) -> Self {
+        self.interface = interface;
+        self
+    }
+
+    /// Sets how long to wait for announcements.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets whether the probe is also sent as broadcast, which reaches
+    /// controllers on networks not forwarding multicast.
+    pub fn with_broadcast(mut self, broadcast: bool) -> Self {
+        self.broadcast = broadcast;
+        self
+    }
+
+    /// Sends the probe and returns the panels which answered, sorted by name
+    /// and address.
+    pub async fn run(&self) -> Result<Vec<DiscoveredPanel>> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+        socket.set_broadcast(true)?;
+        socket.set_multicast_loop_v4(true)?;
+        if !self.interface.is_unspecified() {
+            SockRef::from(&socket).set_multicast_if_v4(&self.interface)?;
+        }
+
+        socket
+            .send_to(PROBE, (Ipv4Addr::from(DISCOVERY_GROUP), self.port))
+            .await?;
+        if self.broadcast {
+            // Without a broadcast route the multicast probe still finds panels.
+            let _ = socket
+                .send_to(PROBE, (Ipv4Addr::BROADCAST, self.port))
+                .await;
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        let mut panels: Vec<DiscoveredPanel> = Vec::new();
+        let mut buffer = [0; 512];
+        while let Ok(received) =
+            tokio::time::timeout_at(deadline, socket.recv_from(&mut buffer)).await
+        {
+            let (length, from) = received?;
+            let Some(announcement) = Announcement::parse(&buffer[..length]) else {
+                continue;
+            };
+            let panel = DiscoveredPanel {
+                name: announcement.name,
+                version: announcement.version,
+                address: from.ip(),
+                port: announcement.port,
+            };
+            // Panels reached by multicast and broadcast answer twice. Names
+            // are not unique, as panels flashed with the same image share it.
+            if !panels
+                .iter()
+                .any(|known| known.address == panel.address && known.port == panel.port)
+            {
+                panels.push(panel);
+            }
+        }
+
+        panels.sort_by(|a, b| {
+            a.name
+                .cmp(&b.name)
+                .then(a.address.cmp(&b.address))
+                .then(a.port.cmp(&b.port))
+        });
+        Ok(panels)
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt::{self, Display};
+
+/// Errors returned by the panel backends
+#[derive(Debug, Clone)]
+pub enum ClientError {
+    /// The controller rejected the request as invalid (HTTP 400), e.g.
+    /// "Page ID not valid" or a full panel memory
+    BadRequest(String),
+    /// The requested page or schedule does not exist (HTTP 404)
+    NotFound(String),
+    /// The controller failed to process the request (HTTP 5xx), e.g. because
+    /// of a storage or UART failure
+    Server { status: u16, message: String },
+    /// The controller answered with any other unexpected HTTP status
+    Status { status: u16, message: String },
+    /// The panel on a serial line answered `NACK`
+    Nack,
+    /// The device did not answer in time
+    Timeout,
+    /// The device could not be reached
+    Offline(String),
+    /// The device answered with something that could not be understood
+    InvalidResponse(String),
+    /// The firmware image is not meant for the controller
+    InvalidFirmware(String),
+    /// The backend does not offer the operation
+    Unsupported(String),
+    /// Any other failure while talking to the device
+    Other(String),
+}
+
+impl ClientError {
+    /// Creates the error for a failed HTTP response from its status and body.
+    pub fn from_status(status: u16, message: String) -> Self {
+        match status {
+            400 => ClientError::BadRequest(message),
+            404 => ClientError::NotFound(message),
+            500..=599 => ClientError::Server { status, message },
+            _ => ClientError::Status { status, message },
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, ClientError::NotFound(_))
+    }
+
+    pub fn is_bad_request(&self) -> bool {
+        matches!(self, ClientError::BadRequest(_))
+    }
+
+    pub fn is_server_error(&self) -> bool {
+        matches!(self, ClientError::Server { .. })
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, ClientError::Timeout)
+    }
+
+    /// Returns `true` if the device could not be reached or did not answer.
+    pub fn is_offline(&self) -> bool {
+        matches!(self, ClientError::Offline(_) | ClientError::Timeout)
+    }
+
+    /// Returns `true` if sending the request again may succeed.
+    ///
+    /// Requests which never reached the device are always safe to repeat,
+    /// others only if they are `idempotent`.
+    pub fn is_retryable(&self, idempotent: bool) -> bool {
+        match self {
+            ClientError::Offline(_) => true,
+            ClientError::Timeout | ClientError::Server { .. } | ClientError::Nack => idempotent,
+            _ => false,
+        }
+    }
+
+    /// Returns the HTTP status the controller answered with.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::BadRequest(_) => Some(400),
+            ClientError::NotFound(_) => Some(404),
+            ClientError::Server { status, .. } | ClientError::Status { status, .. } => {
+                Some(*status)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the error body the controller answered with.
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            ClientError::BadRequest(message)
+            | ClientError::NotFound(message)
+            | ClientError::Server { message, .. }
+            | ClientError::Status { message, .. } => Some(message),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::BadRequest(message) => write!(f, "rejected by panel: {message}"),
+            ClientError::NotFound(message) => write!(f, "not found: {message}"),
+            ClientError::Server { status, message } => {
+                write!(f, "panel failure ({status}): {message}")
+            }
+            ClientError::Status { status, message } => {
+                write!(f, "unexpected status {status}: {message}")
+            }
+            ClientError::Nack => write!(f, "panel answered NACK"),
+            ClientError::Timeout => write!(f, "no answer in time"),
+            ClientError::Offline(message) => write!(f, "offline: {message}"),
+            ClientError::InvalidResponse(message) => write!(f, "invalid response: {message}"),
+            ClientError::InvalidFirmware(message) => write!(f, "invalid firmware: {message}"),
+            ClientError::Unsupported(message) => write!(f, "not supported: {message}"),
+            ClientError::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(value: reqwest::Error) -> Self {
+        if value.is_timeout() {
+            ClientError::Timeout
+        } else if value.is_connect() {
+            ClientError::Offline(value.to_string())
+        } else if value.is_decode() {
+            ClientError::InvalidResponse(value.to_string())
+        } else if let Some(status) = value.status() {
+            ClientError::from_status(status.as_u16(), value.to_string())
+        } else {
+            ClientError::Other(value.to_string())
+        }
+    }
+}
+
+impl From<std::io::Error> for ClientError {
+    fn from(value: std::io::Error) -> Self {
+        match value.kind() {
+            std::io::ErrorKind::TimedOut => ClientError::Timeout,
+            std::io::ErrorKind::NotFound | std::io::ErrorKind::BrokenPipe => {
+                ClientError::Offline(value.to_string())
+            }
+            _ => ClientError::Other(value.to_string()),
+        }
+    }
+}
+
+impl From<serialport::Error> for ClientError {
+    fn from(value: serialport::Error) -> Self {
+        std::io::Error::from(value).into()
+    }
+}
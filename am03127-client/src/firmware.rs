@@ -0,0 +1,173 @@
+use bytes::Bytes;
+use serde::Serialize;
+
+use crate::{BuildInfo, ClientError, Result};
+
+/// First byte of every ESP application image
+pub const ESP_IMAGE_MAGIC: u8 = 0xE9;
+/// Chip ID of the ESP32-C3 in the image header
+pub const ESP32C3_CHIP_ID: u16 = 0x0005;
+/// Magic word starting the application descriptor
+pub const APP_DESC_MAGIC: u32 = 0xABCD_5432;
+/// Project name the controller firmware is built with
+pub const PROJECT_NAME: &str = "AM03127-Controller";
+/// Size of an OTA partition of the controller
+pub const MAX_FIRMWARE_SIZE: usize = 0x1E_0000;
+
+const CHIP_ID_OFFSET: usize = 0x0C;
+/// The descriptor follows the image header and the first segment header
+const APP_DESC_OFFSET: usize = 0x20;
+const APP_DESC_SIZE: usize = 0x100;
+const SECURE_VERSION_OFFSET: usize = APP_DESC_OFFSET + 0x04;
+const VERSION_OFFSET: usize = APP_DESC_OFFSET + 0x10;
+const PROJECT_NAME_OFFSET: usize = APP_DESC_OFFSET + 0x30;
+const BUILD_TIME_OFFSET: usize = APP_DESC_OFFSET + 0x50;
+const BUILD_DATE_OFFSET: usize = APP_DESC_OFFSET + 0x60;
+const IDF_VERSION_OFFSET: usize = APP_DESC_OFFSET + 0x70;
+const MIN_EFUSE_REVISION_OFFSET: usize = APP_DESC_OFFSET + 0xB0;
+const MAX_EFUSE_REVISION_OFFSET: usize = APP_DESC_OFFSET + 0xB2;
+const APP_DESC_END: usize = APP_DESC_OFFSET + APP_DESC_SIZE;
+
+/// Build information from the `esp_app_desc` of a firmware image
+#[derive(Debug, Clone, Serialize)]
+pub struct AppDescriptor {
+    pub version: String,
+    pub project_name: String,
+    pub build_time: String,
+    pub build_date: String,
+    pub idf_version: String,
+    /// Version checked by the anti-rollback feature of the bootloader
+    pub secure_version: u32,
+    /// Oldest eFuse block revision the image runs on, as major * 100 + minor
+    pub min_efuse_revision: u16,
+    /// Newest eFuse block revision the image runs on, `u16::MAX` for any
+    pub max_efuse_revision: u16,
+}
+
+impl AppDescriptor {
+    /// Reads the descriptor of an image, or `None` if the image is too short
+    /// or has no descriptor.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < APP_DESC_END || read_u32(data, APP_DESC_OFFSET) != APP_DESC_MAGIC {
+            return None;
+        }
+        Some(Self {
+            version: read_cstr(data, VERSION_OFFSET, 32),
+            project_name: read_cstr(data, PROJECT_NAME_OFFSET, 32),
+            build_time: read_cstr(data, BUILD_TIME_OFFSET, 16),
+            build_date: read_cstr(data, BUILD_DATE_OFFSET, 16),
+            idf_version: read_cstr(data, IDF_VERSION_OFFSET, 32),
+            secure_version: read_u32(data, SECURE_VERSION_OFFSET),
+            min_efuse_revision: read_u16(data, MIN_EFUSE_REVISION_OFFSET),
+            max_efuse_revision: read_u16(data, MAX_EFUSE_REVISION_OFFSET),
+        })
+    }
+
+    /// Returns `true` if a controller reporting `info` on `/status` runs the
+    /// image of this descriptor.
+    pub fn is_running(&self, info: &BuildInfo) -> bool {
+        self.version == info.version
+            && self.build_date == info.build_date
+            && self.build_time == info.build_time
+    }
+
+    /// Returns the build information a controller running the image reports.
+    pub fn build_info(&self) -> BuildInfo {
+        BuildInfo {
+            version: self.version.clone(),
+            build_time: self.build_time.clone(),
+            build_date: self.build_date.clone(),
+        }
+    }
+}
+
+/// Firmware image checked to be meant for the controller.
+///
+/// Cloning is cheap, the image data is shared.
+#[derive(Debug, Clone)]
+pub struct Firmware {
+    data: Bytes,
+    descriptor: AppDescriptor,
+}
+
+impl Firmware {
+    /// Checks that `data` is an ESP32-C3 image of the controller firmware
+    /// which fits into an OTA partition.
+    pub fn parse(data: impl Into<Bytes>) -> Result<Self> {
+        let data = data.into();
+        let invalid = |message: String| Err(ClientError::InvalidFirmware(message));
+
+        if data.len() > MAX_FIRMWARE_SIZE {
+            return invalid(format!(
+                "image has {} bytes, the OTA partition only {MAX_FIRMWARE_SIZE}",
+                data.len()
+            ));
+        }
+        if data.first() != Some(&ESP_IMAGE_MAGIC) {
+            return invalid(String::from("not an ESP application image"));
+        }
+        if data.len() < APP_DESC_END {
+            return invalid(String::from("image is too short"));
+        }
+        let chip_id = read_u16(&data, CHIP_ID_OFFSET);
+        if chip_id != ESP32C3_CHIP_ID {
+            return invalid(format!(
+                "image is built for chip ID {chip_id:#06x}, not the ESP32-C3"
+            ));
+        }
+        let Some(descriptor) = AppDescriptor::parse(&data) else {
+            return invalid(String::from("image has no application descriptor"));
+        };
+        if descriptor.project_name != PROJECT_NAME {
+            return invalid(format!(
+                "image is built for project '{}', not '{PROJECT_NAME}'",
+                descriptor.project_name
+            ));
+        }
+
+        Ok(Self { data, descriptor })
+    }
+
+    pub fn descriptor(&self) -> &AppDescriptor {
+        &self.descriptor
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the image split into chunks sharing the image data.
+    pub(crate) fn chunks(&self, size: usize) -> impl Iterator<Item = Bytes> + use<> {
+        let data = self.data.clone();
+        (0..data.len())
+            .step_by(size)
+            .map(move |start| data.slice(start..(start + size).min(data.len())))
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+fn read_cstr(data: &[u8], offset: usize, max_len: usize) -> String {
+    let slice = &data[offset..(offset + max_len).min(data.len())];
+    let end = slice.iter().position(|&b| b == 0).unwrap_or(slice.len());
+    String::from_utf8_lossy(&slice[..end]).into_owned()
+}
@@ -0,0 +1,224 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::{ClientError, Panel, Result};
+
+/// Number of panels a [`Fleet`] talks to at the same time by default
+pub const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Panel of a fleet, or why it could not be set up
+struct Member {
+    name: String,
+    panel: Result<Arc<dyn Panel>>,
+}
+
+/// Named group of panels an operation is run on concurrently.
+///
+/// Runs at most [`DEFAULT_CONCURRENCY`] operations at the same time and
+/// collects the outcome of every panel into a [`FleetReport`], so a failing
+/// panel never stops the others.
+pub struct Fleet {
+    members: Vec<Member>,
+    concurrency: usize,
+    timeout: Option<Duration>,
+}
+
+impl Default for Fleet {
+    fn default() -> Self {
+        Self {
+            members: Vec::new(),
+            concurrency: DEFAULT_CONCURRENCY,
+            timeout: None,
+        }
+    }
+}
+
+impl Fleet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many panels are talked to at the same time.
+    pub fn with_concurrency(mut self, limit: usize) -> Self {
+        self.concurrency = limit.max(1);
+        self
+    }
+
+    /// Sets how long the operation may take per panel, not counting the time
+    /// spent waiting for a free slot. Slower panels fail with
+    /// [`ClientError::Timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, panel: impl Panel + 'static) {
+        self.add_shared(name, Arc::new(panel));
+    }
+
+    pub fn add_boxed(&mut self, name: impl Into<String>, panel: Box<dyn Panel>) {
+        self.add_shared(name, Arc::from(panel));
+    }
+
+    pub fn add_shared(&mut self, name: impl Into<String>, panel: Arc<dyn Panel>) {
+        self.members.push(Member {
+            name: name.into(),
+            panel: Ok(panel),
+        });
+    }
+
+    /// Adds a panel which could not be set up, e.g. because its serial port
+    /// failed to open. Every operation fails for it with `error`.
+    pub fn add_unavailable(&mut self, name: impl Into<String>, error: ClientError) {
+        self.members.push(Member {
+            name: name.into(),
+            panel: Err(error),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.members.iter().map(|member| member.name.as_str())
+    }
+
+    /// Runs `op` with the name and client of every panel.
+    pub async fn run<T, F, Fut>(&self, op: F) -> FleetReport<T>
+    where
+        F: Fn(&str, Arc<dyn Panel>) -> Fut,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.run_with(op, |_| {}).await
+    }
+
+    /// Runs `op` with the name and client of every panel and calls `on_done`
+    /// with the outcome of every panel as soon as it is finished.
+    pub async fn run_with<T, F, Fut>(
+        &self,
+        op: F,
+        mut on_done: impl FnMut(&PanelOutcome<T>),
+    ) -> FleetReport<T>
+    where
+        F: Fn(&str, Arc<dyn Panel>) -> Fut,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let slots = Arc::new(Semaphore::new(self.concurrency));
+        let mut outcomes: Vec<Option<PanelOutcome<T>>> = Vec::new();
+        let mut tasks = JoinSet::new();
+        let mut indices = HashMap::new();
+
+        for (index, member) in self.members.iter().enumerate() {
+            outcomes.push(None);
+            let panel = match &member.panel {
+                Ok(panel) => panel.clone(),
+                Err(e) => {
+                    let outcome = PanelOutcome {
+                        name: member.name.clone(),
+                        result: Err(e.clone()),
+                        elapsed: Duration::ZERO,
+                    };
+                    on_done(&outcome);
+                    outcomes[index] = Some(outcome);
+                    continue;
+                }
+            };
+
+            let operation = op(&member.name, panel);
+            let slots = slots.clone();
+            let timeout = self.timeout;
+            let handle = tasks.spawn(async move {
+                let _slot = slots.acquire_owned().await;
+                let start = Instant::now();
+                let result = match timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, operation)
+                        .await
+                        .unwrap_or(Err(ClientError::Timeout)),
+                    None => operation.await,
+                };
+                (result, start.elapsed())
+            });
+            indices.insert(handle.id(), index);
+        }
+
+        while let Some(joined) = tasks.join_next_with_id().await {
+            let (id, (result, elapsed)) = match joined {
+                Ok(finished) => finished,
+                Err(e) => (
+                    e.id(),
+                    (
+                        Err(ClientError::Other(String::from("panel task panicked"))),
+                        Duration::ZERO,
+                    ),
+                ),
+            };
+            let index = indices[&id];
+            let outcome = PanelOutcome {
+                name: self.members[index].name.clone(),
+                result,
+                elapsed,
+            };
+            on_done(&outcome);
+            outcomes[index] = Some(outcome);
+        }
+
+        FleetReport {
+            outcomes: outcomes.into_iter().flatten().collect(),
+        }
+    }
+}
+
+/// Result of an operation on a single panel of a [`Fleet`]
+#[derive(Debug)]
+pub struct PanelOutcome<T> {
+    pub name: String,
+    pub result: Result<T>,
+    /// Time the operation took on this panel
+    pub elapsed: Duration,
+}
+
+/// Outcomes of an operation on all panels of a [`Fleet`], in the order the
+/// panels were added.
+#[derive(Debug)]
+pub struct FleetReport<T> {
+    pub outcomes: Vec<PanelOutcome<T>>,
+}
+
+impl<T> FleetReport<T> {
+    /// Returns `true` if the operation succeeded on every panel.
+    pub fn is_success(&self) -> bool {
+        self.outcomes.iter().all(|outcome| outcome.result.is_ok())
+    }
+
+    pub fn succeeded(&self) -> impl Iterator<Item = &PanelOutcome<T>> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.result.is_ok())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &PanelOutcome<T>> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.result.is_err())
+    }
+
+    /// Returns the error of the first failed panel.
+    pub fn first_error(&self) -> Option<&ClientError> {
+        self.outcomes
+            .iter()
+            .find_map(|outcome| outcome.result.as_ref().err())
+    }
+}
@@ -1,42 +1,89 @@
-use am03127_commands::{page::Page, realtime_clock::DateTime, schedule::Schedule};
-use anyhow::Result;
-use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
 
-#[derive(Debug, Clone, Deserialize)]
+use am03127_commands::{
+    graphic::GraphicBlock, page::Page, realtime_clock::DateTime, schedule::Schedule,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::stream;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod builder;
+mod discovery;
+mod error;
+mod firmware;
+mod fleet;
+mod mock;
+mod panel;
+mod serial;
+
+pub use builder::PanelClientBuilder;
+pub use discovery::{DiscoveredPanel, Discovery};
+pub use error::ClientError;
+pub use firmware::{AppDescriptor, Firmware};
+pub use fleet::{DEFAULT_CONCURRENCY, Fleet, FleetReport, PanelOutcome};
+pub use mock::MockPanel;
+pub use panel::{Panel, Progress};
+pub use serial::SerialPanel;
+
+/// Suffix of the version of firmware built with uncommitted changes
+pub const DIRTY_SUFFIX: &str = "-dirty";
+
+/// Size of the pieces a firmware image is streamed in
+const OTA_CHUNK_SIZE: usize = 16 * 1024;
+
+pub type Result<T, E = ClientError> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BuildInfo {
     pub version: String,
     pub build_time: String,
     pub build_date: String,
 }
 
+impl BuildInfo {
+    /// Returns `true` if the firmware was built from a work tree with
+    /// uncommitted changes.
+    pub fn is_dirty(&self) -> bool {
+        self.version.ends_with(DIRTY_SUFFIX)
+    }
+}
+
 /// Async HTTP client for a single AM03127 panel.
 ///
-/// Construct with [`PanelClient::new`] and pass the panel's IP address.
+/// Construct with [`PanelClient::new`] and pass the panel's IP address, or
+/// use [`PanelClient::builder`] to change timeouts, retries and the URL.
 /// The underlying [`reqwest::Client`] is cloned cheaply; the actual connection
 /// pool is shared.
 #[derive(Clone)]
 pub struct PanelClient {
     client: reqwest::Client,
     base_url: String,
+    ota_timeout: Duration,
+    retries: u32,
+    backoff: Duration,
 }
 
 impl PanelClient {
+    /// Creates a client with the default timeouts and retries.
     pub fn new(address: &str) -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            base_url: format!("http://{address}"),
-        }
+        Self::builder(address)
+            .build()
+            .expect("default client settings are valid")
+    }
+
+    pub fn builder(address: &str) -> PanelClientBuilder {
+        PanelClientBuilder::new(address)
     }
 
     // ── Status ────────────────────────────────────────────────────────────────
 
     pub async fn get_status(&self) -> Result<BuildInfo> {
         let info = self
-            .client
-            .get(self.url("/status"))
-            .send()
+            .send(self.client.get(self.url("/status")))
             .await?
-            .error_for_status()?
             .json::<BuildInfo>()
             .await?;
         Ok(info)
@@ -45,149 +92,148 @@ impl PanelClient {
     // ── Clock ─────────────────────────────────────────────────────────────────
 
     pub async fn set_clock(&self, dt: &DateTime) -> Result<()> {
-        self.client
-            .post(self.url("/clock"))
-            .json(dt)
-            .send()
-            .await?
-            .error_for_status()?;
+        self.send(self.client.post(self.url("/clock")).json(dt))
+            .await?;
         Ok(())
     }
 
     // ── Pages ─────────────────────────────────────────────────────────────────
 
-    pub async fn get_page(&self, id: char) -> Result<Page> {
-        let page = self
-            .client
-            .get(self.url(format!("/page/{id}")))
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Page>()
-            .await?;
-        Ok(page)
+    /// Returns the page with the given ID or `None` if the panel has no such page.
+    pub async fn get_page(&self, id: char) -> Result<Option<Page>> {
+        let request = self.client.get(self.url(format!("/page/{id}")));
+        match self.send(request).await {
+            Ok(response) => Ok(Some(response.json::<Page>().await?)),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
     pub async fn set_page(&self, page: &Page) -> Result<()> {
-        self.client
-            .post(self.url(format!("/page/{}", page.id)))
-            .json(page)
-            .send()
-            .await?
-            .error_for_status()?;
+        self.send(
+            self.client
+                .post(self.url(format!("/page/{}", page.id)))
+                .json(page),
+        )
+        .await?;
         Ok(())
     }
 
     pub async fn delete_page(&self, id: char) -> Result<()> {
-        self.client
-            .delete(self.url(format!("/page/{id}")))
-            .send()
-            .await?
-            .error_for_status()?;
+        self.send(self.client.delete(self.url(format!("/page/{id}"))))
+            .await?;
         Ok(())
     }
 
     pub async fn get_pages(&self) -> Result<Vec<Page>> {
         let pages = self
-            .client
-            .get(self.url("/pages"))
-            .send()
+            .send(self.client.get(self.url("/pages")))
             .await?
-            .error_for_status()?
             .json::<Vec<Page>>()
             .await?;
         Ok(pages)
     }
 
     pub async fn set_pages(&self, pages: &[Page]) -> Result<()> {
-        self.client
-            .post(self.url("/pages"))
-            .json(pages)
-            .send()
-            .await?
-            .error_for_status()?;
+        self.send(self.client.post(self.url("/pages")).json(pages))
+            .await?;
         Ok(())
     }
 
     // ── Schedules ─────────────────────────────────────────────────────────────
 
-    pub async fn get_schedule(&self, id: char) -> Result<Schedule> {
-        let schedule = self
-            .client
-            .get(self.url(format!("/schedule/{id}")))
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Schedule>()
-            .await?;
-        Ok(schedule)
+    /// Returns the schedule with the given ID or `None` if the panel has no
+    /// such schedule.
+    pub async fn get_schedule(&self, id: char) -> Result<Option<Schedule>> {
+        let request = self.client.get(self.url(format!("/schedule/{id}")));
+        match self.send(request).await {
+            Ok(response) => Ok(Some(response.json::<Schedule>().await?)),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
     pub async fn set_schedule(&self, schedule: &Schedule) -> Result<()> {
-        self.client
-            .post(self.url(format!("/schedule/{}", schedule.id)))
-            .json(schedule)
-            .send()
-            .await?
-            .error_for_status()?;
+        let url = self.url(format!("/schedule/{}", schedule.id));
+        self.send(self.client.post(url).json(schedule)).await?;
         Ok(())
     }
 
     pub async fn delete_schedule(&self, id: char) -> Result<()> {
-        self.client
-            .delete(self.url(format!("/schedule/{id}")))
-            .send()
-            .await?
-            .error_for_status()?;
+        self.send(self.client.delete(self.url(format!("/schedule/{id}"))))
+            .await?;
         Ok(())
     }
 
     pub async fn get_schedules(&self) -> Result<Vec<Schedule>> {
         let schedules = self
-            .client
-            .get(self.url("/schedules"))
-            .send()
+            .send(self.client.get(self.url("/schedules")))
             .await?
-            .error_for_status()?
             .json::<Vec<Schedule>>()
             .await?;
         Ok(schedules)
     }
 
     pub async fn set_schedules(&self, schedules: &[Schedule]) -> Result<()> {
-        self.client
-            .post(self.url("/schedules"))
-            .json(schedules)
-            .send()
-            .await?
-            .error_for_status()?;
+        self.send(self.client.post(self.url("/schedules")).json(schedules))
+            .await?;
+        Ok(())
+    }
+
+    // ── Graphics ──────────────────────────────────────────────────────────────
+
+    /// Sends a graphic block to the panel. Graphic blocks are not persisted by
+    /// the controller.
+    pub async fn set_graphic(&self, block: &GraphicBlock) -> Result<()> {
+        self.send(self.client.post(self.url("/graphic")).json(block))
+            .await?;
         Ok(())
     }
 
     // ── Control ───────────────────────────────────────────────────────────────
 
-    /// Deletes all pages and schedules from the panel.
+    /// Deletes all pages, schedules and graphics from the panel.
     pub async fn reset(&self) -> Result<()> {
-        self.client
-            .post(self.url("/reset"))
-            .send()
-            .await?
-            .error_for_status()?;
+        self.send(self.client.post(self.url("/reset"))).await?;
         Ok(())
     }
 
-    /// Uploads new firmware via OTA. The panel reboots automatically after a
-    /// successful upload.
+    /// Checks and uploads new firmware via OTA. The panel reboots
+    /// automatically after a successful upload.
     pub async fn update_firmware(&self, firmware: &[u8]) -> Result<()> {
-        let len = firmware.len();
-        self.client
-            .put(self.url("/ota"))
-            .header("Content-Type", "application/octet-stream")
-            .header("Content-Length", len)
-            .body(firmware.to_vec())
-            .send()
-            .await?
-            .error_for_status()?;
+        let firmware = Firmware::parse(Bytes::copy_from_slice(firmware))?;
+        self.update_firmware_with_progress(&firmware, |_, _| {})
+            .await
+    }
+
+    /// Streams new firmware via OTA, calling `progress` with the number of
+    /// bytes sent so far and the size of the image.
+    pub async fn update_firmware_with_progress(
+        &self,
+        firmware: &Firmware,
+        progress: impl Fn(u64, u64) + Send + Sync + 'static,
+    ) -> Result<()> {
+        let total = firmware.len() as u64;
+        let progress = Arc::new(progress);
+        // A repeated upload would flash the image again after a reboot.
+        self.retry(false, || {
+            let progress = progress.clone();
+            let mut sent = 0;
+            let chunks = firmware.chunks(OTA_CHUNK_SIZE).map(move |chunk| {
+                sent += chunk.len() as u64;
+                progress(sent, total);
+                Ok::<_, std::io::Error>(chunk)
+            });
+            let request = self
+                .client
+                .put(self.url("/ota"))
+                .timeout(self.ota_timeout)
+                .header("Content-Type", "application/octet-stream")
+                .header("Content-Length", total)
+                .body(reqwest::Body::wrap_stream(stream::iter(chunks)));
+            Self::send_once(request)
+        })
+        .await?;
         Ok(())
     }
 
@@ -196,4 +242,124 @@ impl PanelClient {
     fn url(&self, path: impl AsRef<str>) -> String {
         format!("{}{}", self.base_url, path.as_ref())
     }
+
+    /// Sends a request which can safely be repeated.
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        self.retry(true, || {
+            let request = request.try_clone();
+            async move {
+                let request = request.ok_or_else(|| {
+                    ClientError::Other(String::from("request can not be repeated"))
+                })?;
+                Self::send_once(request).await
+            }
+        })
+        .await
+    }
+
+    /// Runs `attempt` until it succeeds, retrying with exponential backoff.
+    ///
+    /// Requests which never reached the controller are always retried,
+    /// timeouts and controller failures only if the request is `idempotent`.
+    async fn retry<T, F>(&self, idempotent: bool, mut attempt: impl FnMut() -> F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            match attempt().await {
+                Err(e) if retry < self.retries && e.is_retryable(idempotent) => {
+                    tokio::time::sleep(self.backoff * 2u32.saturating_pow(retry)).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends a request and turns error responses into a [`ClientError`]
+    /// carrying the controller's error message.
+    async fn send_once(request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let message = response.text().await.unwrap_or_default();
+        Err(ClientError::from_status(status.as_u16(), message))
+    }
+}
+
+#[async_trait]
+impl Panel for PanelClient {
+    async fn get_status(&self) -> Result<BuildInfo> {
+        PanelClient::get_status(self).await
+    }
+
+    async fn set_clock(&self, dt: &DateTime) -> Result<()> {
+        PanelClient::set_clock(self, dt).await
+    }
+
+    async fn get_page(&self, id: char) -> Result<Option<Page>> {
+        PanelClient::get_page(self, id).await
+    }
+
+    async fn set_page(&self, page: &Page) -> Result<()> {
+        PanelClient::set_page(self, page).await
+    }
+
+    async fn delete_page(&self, id: char) -> Result<()> {
+        PanelClient::delete_page(self, id).await
+    }
+
+    async fn get_pages(&self) -> Result<Vec<Page>> {
+        PanelClient::get_pages(self).await
+    }
+
+    async fn set_pages(&self, pages: &[Page]) -> Result<()> {
+        PanelClient::set_pages(self, pages).await
+    }
+
+    async fn get_schedule(&self, id: char) -> Result<Option<Schedule>> {
+        PanelClient::get_schedule(self, id).await
+    }
+
+    async fn set_schedule(&self, schedule: &Schedule) -> Result<()> {
+        PanelClient::set_schedule(self, schedule).await
+    }
+
+    async fn delete_schedule(&self, id: char) -> Result<()> {
+        PanelClient::delete_schedule(self, id).await
+    }
+
+    async fn get_schedules(&self) -> Result<Vec<Schedule>> {
+        PanelClient::get_schedules(self).await
+    }
+
+    async fn set_schedules(&self, schedules: &[Schedule]) -> Result<()> {
+        PanelClient::set_schedules(self, schedules).await
+    }
+
+    async fn set_graphic(&self, block: &GraphicBlock) -> Result<()> {
+        PanelClient::set_graphic(self, block).await
+    }
+
+    async fn reset(&self) -> Result<()> {
+        PanelClient::reset(self).await
+    }
+
+    async fn update_firmware(&self, firmware: &[u8]) -> Result<()> {
+        PanelClient::update_firmware(self, firmware).await
+    }
+
+    async fn update_firmware_with_progress(
+        &self,
+        firmware: &Firmware,
+        progress: Progress,
+    ) -> Result<()> {
+        PanelClient::update_firmware_with_progress(self, firmware, move |sent, total| {
+            progress(sent, total)
+        })
+        .await
+    }
 }
@@ -0,0 +1,208 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use am03127_commands::{
+    graphic::GraphicBlock,
+    is_page_id_valid, is_schedule_id_valid,
+    memory::{MemoryBudget, MemoryStatus},
+    page::Page,
+    realtime_clock::DateTime,
+    schedule::Schedule,
+};
+use async_trait::async_trait;
+
+use crate::{AppDescriptor, BuildInfo, ClientError, Panel, Result};
+
+/// Contents of a [`MockPanel`]
+#[derive(Debug, Default)]
+struct State {
+    clock: Option<DateTime>,
+    pages: BTreeMap<char, Page>,
+    schedules: BTreeMap<char, Schedule>,
+    graphics: Vec<GraphicBlock>,
+    firmware: Option<Vec<u8>>,
+}
+
+/// In-memory panel for exercising panel automation without a network.
+///
+/// Validates requests like the firmware does and keeps everything it receives.
+/// Clones share the same contents, so a test can keep one to inspect the
+/// panel after handing another to the code under test.
+#[derive(Debug, Clone, Default)]
+pub struct MockPanel {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the last clock setting received.
+    pub fn clock(&self) -> Option<DateTime> {
+        self.state().clock
+    }
+
+    /// Returns all graphic blocks received since the last reset, oldest first.
+    pub fn graphics(&self) -> Vec<GraphicBlock> {
+        self.state().graphics.clone()
+    }
+
+    /// Returns the last firmware image uploaded.
+    pub fn firmware(&self) -> Option<Vec<u8>> {
+        self.state().firmware.clone()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl Panel for MockPanel {
+    async fn get_status(&self) -> Result<BuildInfo> {
+        // Like the controller after rebooting into the last uploaded image
+        let descriptor = self.state().firmware.as_deref().and_then(AppDescriptor::parse);
+        if let Some(descriptor) = descriptor {
+            return Ok(descriptor.build_info());
+        }
+        Ok(BuildInfo {
+            version: String::from("mock"),
+            build_time: String::from("00:00:00"),
+            build_date: String::from("1970-01-01"),
+        })
+    }
+
+    async fn set_clock(&self, dt: &DateTime) -> Result<()> {
+        self.state().clock = Some(*dt);
+        Ok(())
+    }
+
+    async fn get_page(&self, id: char) -> Result<Option<Page>> {
+        check_page_id(id)?;
+        Ok(self.state().pages.get(&id).cloned())
+    }
+
+    async fn set_page(&self, page: &Page) -> Result<()> {
+        check_page_id(page.id)?;
+        let mut state = self.state();
+        let mut pages = state.pages.clone();
+        pages.insert(page.id, page.clone());
+        check_memory(&pages, &state.schedules, &state.graphics)?;
+        state.pages = pages;
+        Ok(())
+    }
+
+    async fn delete_page(&self, id: char) -> Result<()> {
+        check_page_id(id)?;
+        self.state().pages.remove(&id);
+        Ok(())
+    }
+
+    async fn get_pages(&self) -> Result<Vec<Page>> {
+        Ok(self.state().pages.values().cloned().collect())
+    }
+
+    async fn set_pages(&self, pages: &[Page]) -> Result<()> {
+        for page in pages {
+            self.set_page(page).await?;
+        }
+        Ok(())
+    }
+
+    async fn get_schedule(&self, id: char) -> Result<Option<Schedule>> {
+        check_schedule_id(id)?;
+        Ok(self.state().schedules.get(&id).cloned())
+    }
+
+    async fn set_schedule(&self, schedule: &Schedule) -> Result<()> {
+        check_schedule_id(schedule.id)?;
+        let mut state = self.state();
+        let mut schedules = state.schedules.clone();
+        schedules.insert(schedule.id, schedule.clone());
+        check_memory(&state.pages, &schedules, &state.graphics)?;
+        state.schedules = schedules;
+        Ok(())
+    }
+
+    async fn delete_schedule(&self, id: char) -> Result<()> {
+        check_schedule_id(id)?;
+        self.state().schedules.remove(&id);
+        Ok(())
+    }
+
+    async fn get_schedules(&self) -> Result<Vec<Schedule>> {
+        Ok(self.state().schedules.values().cloned().collect())
+    }
+
+    async fn set_schedules(&self, schedules: &[Schedule]) -> Result<()> {
+        for schedule in schedules {
+            self.set_schedule(schedule).await?;
+        }
+        Ok(())
+    }
+
+    async fn set_graphic(&self, block: &GraphicBlock) -> Result<()> {
+        if !block.is_valid() {
+            return Err(ClientError::BadRequest("Graphic block not valid".into()));
+        }
+        let mut state = self.state();
+        let mut graphics = state.graphics.clone();
+        graphics.push(block.clone());
+        check_memory(&state.pages, &state.schedules, &graphics)?;
+        state.graphics = graphics;
+        Ok(())
+    }
+
+    async fn reset(&self) -> Result<()> {
+        let mut state = self.state();
+        state.pages.clear();
+        state.schedules.clear();
+        state.graphics.clear();
+        Ok(())
+    }
+
+    async fn update_firmware(&self, firmware: &[u8]) -> Result<()> {
+        self.state().firmware = Some(firmware.to_vec());
+        Ok(())
+    }
+}
+
+fn check_page_id(id: char) -> Result<()> {
+    if !is_page_id_valid(id) {
+        return Err(ClientError::BadRequest("Page ID not valid".into()));
+    }
+    Ok(())
+}
+
+fn check_schedule_id(id: char) -> Result<()> {
+    if !is_schedule_id_valid(id) {
+        return Err(ClientError::BadRequest("Schedule ID not valid".into()));
+    }
+    Ok(())
+}
+
+/// Rejects content which would not fit into the panel memory, like the
+/// firmware does.
+fn check_memory(
+    pages: &BTreeMap<char, Page>,
+    schedules: &BTreeMap<char, Schedule>,
+    graphics: &[GraphicBlock],
+) -> Result<()> {
+    // Blocks sent again replace the earlier ones
+    let graphic_blocks = graphics
+        .iter()
+        .map(|block| (block.page, block.block))
+        .collect::<BTreeSet<_>>()
+        .len();
+    let estimate =
+        MemoryBudget::default().estimate(pages.values(), schedules.values(), graphic_blocks);
+    if estimate.status() == MemoryStatus::Overflow {
+        return Err(ClientError::BadRequest(format!(
+            "Panel memory would overflow, using {estimate}"
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use am03127_commands::{
+    graphic::GraphicBlock, page::Page, realtime_clock::DateTime, schedule::Schedule,
+};
+use async_trait::async_trait;
+
+use crate::{BuildInfo, Firmware, Result};
+
+/// Callback receiving the number of bytes sent so far and the total size
+pub type Progress = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/// Operations every AM03127 panel backend offers.
+///
+/// Implemented by [`PanelClient`](crate::PanelClient) for panels behind the
+/// ESP32 controller, [`SerialPanel`](crate::SerialPanel) for panels wired to a
+/// local serial port and [`MockPanel`](crate::MockPanel) for tests without
+/// any hardware. Backends return [`ClientError::Unsupported`](crate::ClientError::Unsupported)
+/// for operations they can not offer.
+#[async_trait]
+pub trait Panel: Send + Sync {
+    // ── Status ────────────────────────────────────────────────────────────────
+
+    async fn get_status(&self) -> Result<BuildInfo>;
+
+    // ── Clock ─────────────────────────────────────────────────────────────────
+
+    async fn set_clock(&self, dt: &DateTime) -> Result<()>;
+
+    // ── Pages ─────────────────────────────────────────────────────────────────
+
+    /// Returns the page with the given ID or `None` if the panel has no such page.
+    async fn get_page(&self, id: char) -> Result<Option<Page>>;
+
+    async fn set_page(&self, page: &Page) -> Result<()>;
+
+    async fn delete_page(&self, id: char) -> Result<()>;
+
+    async fn get_pages(&self) -> Result<Vec<Page>>;
+
+    async fn set_pages(&self, pages: &[Page]) -> Result<()>;
+
+    // ── Schedules ─────────────────────────────────────────────────────────────
+
+    /// Returns the schedule with the given ID or `None` if the panel has no
+    /// such schedule.
+    async fn get_schedule(&self, id: char) -> Result<Option<Schedule>>;
+
+    async fn set_schedule(&self, schedule: &Schedule) -> Result<()>;
+
+    async fn delete_schedule(&self, id: char) -> Result<()>;
+
+    async fn get_schedules(&self) -> Result<Vec<Schedule>>;
+
+    async fn set_schedules(&self, schedules: &[Schedule]) -> Result<()>;
+
+    // ── Graphics ──────────────────────────────────────────────────────────────
+
+    async fn set_graphic(&self, block: &GraphicBlock) -> Result<()>;
+
+    // ── Control ───────────────────────────────────────────────────────────────
+
+    /// Deletes all pages, schedules and graphics from the panel.
+    async fn reset(&self) -> Result<()>;
+
+    /// Uploads new firmware. The panel reboots automatically after a
+    /// successful upload.
+    async fn update_firmware(&self, firmware: &[u8]) -> Result<()>;
+
+    /// Uploads a checked firmware image, reporting the progress while
+    /// sending. Backends which can not stream report it all at once.
+    async fn update_firmware_with_progress(
+        &self,
+        firmware: &Firmware,
+        progress: Progress,
+    ) -> Result<()> {
+        self.update_firmware(firmware.as_bytes()).await?;
+        progress(firmware.len() as u64, firmware.len() as u64);
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use am03127_commands::{
+    Payload,
+    delete::{DeleteAll, DeletePage, DeleteSchedule},
+    frame::{Frame, Reply},
+    graphic::GraphicBlock,
+    page::Page,
+    realtime_clock::DateTime,
+    schedule::Schedule,
+};
+use async_trait::async_trait;
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+
+use crate::{BuildInfo, ClientError, Panel, Result};
+
+/// Baud rate of the AM03127 RS232 interface
+pub const BAUD_RATE: u32 = 9600;
+/// Time to wait for the panel's answer to a single frame
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+/// Number of times a frame is sent again after a NACK or timeout
+pub const DEFAULT_RETRIES: u32 = 2;
+
+/// Interval in which the port is polled while waiting for an answer
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Client for a single AM03127 panel wired directly to a serial port.
+///
+/// Speaks the panel's wire protocol at 9600 8N1, waits for the `ACK`/`NACK`
+/// answer of every frame and sends it again on failure. The panel cannot be
+/// read back, so only the write and delete operations of
+/// [`PanelClient`](crate::PanelClient) are available.
+#[derive(Clone)]
+pub struct SerialPanel {
+    port: Arc<Mutex<Box<dyn SerialPort>>>,
+    id: u8,
+    timeout: Duration,
+    retries: u32,
+}
+
+impl SerialPanel {
+    /// Opens the serial port at `path` for the panel with the given ID.
+    pub fn open(path: &str, id: u8) -> Result<Self> {
+        let port = serialport::new(path, BAUD_RATE)
+            .data_bits(DataBits::Eight)
+            .parity(Parity::None)
+            .stop_bits(StopBits::One)
+            .flow_control(FlowControl::None)
+            .timeout(POLL_INTERVAL)
+            .open()
+            .map_err(|e| ClientError::Offline(format!("failed to open '{path}': {e}")))?;
+
+        Ok(Self {
+            port: Arc::new(Mutex::new(port)),
+            id,
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+        })
+    }
+
+    /// Sets how long to wait for the panel's answer to a single frame.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how often a frame is sent again after a NACK or timeout.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Returns the ID of the panel the frames are addressed to.
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    // ── Setup ─────────────────────────────────────────────────────────────────
+
+    /// Assigns the panel's ID. Only a single panel may be connected while
+    /// doing so.
+    pub async fn set_id(&self) -> Result<()> {
+        self.send(Frame::set_id(self.id)).await
+    }
+
+    // ── Clock ─────────────────────────────────────────────────────────────────
+
+    pub async fn set_clock(&self, dt: &DateTime) -> Result<()> {
+        self.send_command(dt).await
+    }
+
+    // ── Pages ─────────────────────────────────────────────────────────────────
+
+    pub async fn set_page(&self, page: &Page) -> Result<()> {
+        self.send_command(page).await
+    }
+
+    pub async fn delete_page(&self, id: char) -> Result<()> {
+        self.send_command(&DeletePage::new(id)).await
+    }
+
+    pub async fn set_pages(&self, pages: &[Page]) -> Result<()> {
+        for page in pages {
+            self.set_page(page).await?;
+        }
+        Ok(())
+    }
+
+    // ── Schedules ─────────────────────────────────────────────────────────────
+
+    pub async fn set_schedule(&self, schedule: &Schedule) -> Result<()> {
+        self.send_command(schedule).await
+    }
+
+    pub async fn delete_schedule(&self, id: char) -> Result<()> {
+        self.send_command(&DeleteSchedule::new(id)).await
+    }
+
+    pub async fn set_schedules(&self, schedules: &[Schedule]) -> Result<()> {
+        for schedule in schedules {
+            self.set_schedule(schedule).await?;
+        }
+        Ok(())
+    }
+
+    // ── Graphics ──────────────────────────────────────────────────────────────
+
+    pub async fn set_graphic(&self, block: &GraphicBlock) -> Result<()> {
+        self.send_command(block).await
+    }
+
+    // ── Control ───────────────────────────────────────────────────────────────
+
+    /// Deletes all pages, schedules and graphics from the panel.
+    pub async fn reset(&self) -> Result<()> {
+        self.send_command(&DeleteAll {}).await
+    }
+
+    // ── Helpers ───────────────────────────────────────────────────────────────
+
+    async fn send_command<C: Payload + ?Sized>(&self, command: &C) -> Result<()> {
+        self.send(Frame::new(self.id, command)).await
+    }
+
+    /// Sends a frame on a blocking thread, retrying on NACK and timeouts.
+    async fn send(&self, frame: Frame) -> Result<()> {
+        let port = self.port.clone();
+        let timeout = self.timeout;
+        let attempts = self.retries + 1;
+
+        tokio::task::spawn_blocking(move || {
+            let mut port = port.lock().unwrap_or_else(|e| e.into_inner());
+            let mut result = Ok(());
+            for _ in 0..attempts {
+                result = transmit(port.as_mut(), &frame, timeout);
+                if result.is_ok() {
+                    break;
+                }
+            }
+            result
+        })
+        .await
+        .map_err(|e| ClientError::Other(format!("serial task failed: {e}")))?
+    }
+}
+
+#[async_trait]
+impl Panel for SerialPanel {
+    async fn get_status(&self) -> Result<BuildInfo> {
+        Err(unsupported("reading the status"))
+    }
+
+    async fn set_clock(&self, dt: &DateTime) -> Result<()> {
+        SerialPanel::set_clock(self, dt).await
+    }
+
+    async fn get_page(&self, _id: char) -> Result<Option<Page>> {
+        Err(unsupported("reading pages"))
+    }
+
+    async fn set_page(&self, page: &Page) -> Result<()> {
+        SerialPanel::set_page(self, page).await
+    }
+
+    async fn delete_page(&self, id: char) -> Result<()> {
+        SerialPanel::delete_page(self, id).await
+    }
+
+    async fn get_pages(&self) -> Result<Vec<Page>> {
+        Err(unsupported("reading pages"))
+    }
+
+    async fn set_pages(&self, pages: &[Page]) -> Result<()> {
+        SerialPanel::set_pages(self, pages).await
+    }
+
+    async fn get_schedule(&self, _id: char) -> Result<Option<Schedule>> {
+        Err(unsupported("reading schedules"))
+    }
+
+    async fn set_schedule(&self, schedule: &Schedule) -> Result<()> {
+        SerialPanel::set_schedule(self, schedule).await
+    }
+
+    async fn delete_schedule(&self, id: char) -> Result<()> {
+        SerialPanel::delete_schedule(self, id).await
+    }
+
+    async fn get_schedules(&self) -> Result<Vec<Schedule>> {
+        Err(unsupported("reading schedules"))
+    }
+
+    async fn set_schedules(&self, schedules: &[Schedule]) -> Result<()> {
+        SerialPanel::set_schedules(self, schedules).await
+    }
+
+    async fn set_graphic(&self, block: &GraphicBlock) -> Result<()> {
+        SerialPanel::set_graphic(self, block).await
+    }
+
+    async fn reset(&self) -> Result<()> {
+        SerialPanel::reset(self).await
+    }
+
+    async fn update_firmware(&self, _firmware: &[u8]) -> Result<()> {
+        Err(unsupported("firmware updates"))
+    }
+}
+
+fn unsupported(operation: &str) -> ClientError {
+    ClientError::Unsupported(format!("{operation} over a serial port"))
+}
+
+/// Writes a frame and waits for the answer it expects.
+fn transmit(port: &mut dyn SerialPort, frame: &Frame, timeout: Duration) -> Result<()> {
+    port.clear(ClearBuffer::Input)?;
+    port.write_all(&frame.to_bytes())?;
+    port.flush()?;
+
+    let reply = frame.reply();
+    if reply == Reply::None {
+        return Ok(());
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut response = Vec::new();
+    let mut buffer = [0u8; 32];
+    while Instant::now() < deadline {
+        match port.read(&mut buffer) {
+            Ok(n) => response.extend_from_slice(&buffer[..n]),
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let text = String::from_utf8_lossy(&response);
+        match reply {
+            Reply::Ack if text.contains("NACK") => return Err(ClientError::Nack),
+            Reply::Ack if text.contains("ACK") => return Ok(()),
+            Reply::Id(id) if text.contains(&format!("{id:02X}")) => return Ok(()),
+            _ => {}
+        }
+    }
+
+    if response.is_empty() {
+        return Err(ClientError::Timeout);
+    }
+    Err(ClientError::InvalidResponse(
+        String::from_utf8_lossy(&response).trim().to_string(),
+    ))
+}
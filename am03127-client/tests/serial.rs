@@ -0,0 +1,203 @@
+#![cfg(unix)]
+
+use std::{
+    fs::File,
+    io::{Read, Write},
+    os::fd::OwnedFd,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use am03127_client::{ClientError, SerialPanel};
+use am03127_commands::{
+    frame::{BROADCAST_ID, Decoded, Frame},
+    page::{Page, WaitingTime},
+};
+use nix::{
+    pty::openpty,
+    sys::termios::{self, SetArg},
+    unistd::ttyname,
+};
+
+/// Time the tests wait for a frame the stand-in should have received
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pseudo-terminal standing in for a panel at the other end of the line.
+struct Line {
+    path: String,
+    /// Frames the stand-in received, in order
+    frames: mpsc::Receiver<Decoded>,
+    /// Keeps the line open while no client is connected
+    _slave: OwnedFd,
+}
+
+impl Line {
+    /// Opens a line whose stand-in answers the n-th received frame with the
+    /// bytes `answer` returns. The first `garbled` frames get a payload byte
+    /// flipped on the way, so their checksum no longer matches.
+    fn open(
+        garbled: usize,
+        mut answer: impl FnMut(usize, &Decoded) -> &'static [u8] + Send + 'static,
+    ) -> Self {
+        let pty = openpty(None, None).unwrap();
+        let mut settings = termios::tcgetattr(&pty.slave).unwrap();
+        termios::cfmakeraw(&mut settings);
+        termios::tcsetattr(&pty.slave, SetArg::TCSANOW, &settings).unwrap();
+        let path = ttyname(&pty.slave).unwrap().display().to_string();
+
+        let (sender, frames) = mpsc::channel();
+        let mut master = File::from(pty.master);
+        thread::spawn(move || {
+            let mut received = Vec::new();
+            let mut chunk = [0u8; 1024];
+            let mut count = 0;
+            // Reading fails once the client and the test closed the line.
+            while let Ok(n @ 1..) = master.read(&mut chunk) {
+                received.extend_from_slice(&chunk[..n]);
+                loop {
+                    let (mut decoded, consumed) = Frame::decode(&received);
+                    if let Decoded::Frame(_) = decoded
+                        && count < garbled
+                    {
+                        let start = received.windows(3).position(|w| w == b"<ID").unwrap();
+                        received[start + 7] ^= 0x01;
+                        decoded = Frame::decode(&received).0;
+                    }
+                    received.drain(..consumed);
+                    match decoded {
+                        Decoded::Incomplete => break,
+                        Decoded::Malformed => continue,
+                        decoded => {
+                            master.write_all(answer(count, &decoded)).unwrap();
+                            count += 1;
+                            let _ = sender.send(decoded);
+                        }
+                    }
+                }
+            }
+        });
+
+        Line {
+            path,
+            frames,
+            _slave: pty.slave,
+        }
+    }
+
+    /// Returns the next frame the stand-in received.
+    fn next_frame(&self) -> Decoded {
+        self.frames.recv_timeout(RECEIVE_TIMEOUT).unwrap()
+    }
+
+    /// Returns whether the stand-in received no further frame.
+    fn is_idle(&self) -> bool {
+        self.frames
+            .recv_timeout(Duration::from_millis(200))
+            .is_err()
+    }
+}
+
+/// Answers a frame like a panel: `ACK` if its checksum matches, else `NACK`.
+fn panel(_: usize, decoded: &Decoded) -> &'static [u8] {
+    match decoded {
+        Decoded::Frame(_) => b"ACK",
+        _ => b"NACK",
+    }
+}
+
+fn page() -> Page {
+    Page::new(
+        'A',
+        String::from("Hello"),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        WaitingTime::new(4),
+    )
+}
+
+fn expected_frame(id: u8) -> Decoded {
+    Decoded::Frame(Frame::new(id, &page()))
+}
+
+#[tokio::test]
+async fn sends_frame_and_accepts_ack() {
+    let line = Line::open(0, panel);
+    let client = SerialPanel::open(&line.path, 1).unwrap();
+
+    client.set_page(&page()).await.unwrap();
+
+    assert_eq!(line.next_frame(), expected_frame(1));
+    assert!(line.is_idle());
+}
+
+#[tokio::test]
+async fn retries_after_nack() {
+    let line = Line::open(0, |count, _| if count == 0 { b"NACK" } else { b"ACK" });
+    let client = SerialPanel::open(&line.path, 1).unwrap();
+
+    client.set_page(&page()).await.unwrap();
+
+    assert_eq!(line.next_frame(), expected_frame(1));
+    assert_eq!(line.next_frame(), expected_frame(1));
+    assert!(line.is_idle());
+}
+
+#[tokio::test]
+async fn fails_after_retries_are_used_up() {
+    let line = Line::open(0, |_, _| b"NACK");
+    let client = SerialPanel::open(&line.path, 1).unwrap().with_retries(2);
+
+    let result = client.set_page(&page()).await;
+
+    assert!(matches!(result, Err(ClientError::Nack)), "{result:?}");
+    for _ in 0..3 {
+        assert_eq!(line.next_frame(), expected_frame(1));
+    }
+    assert!(line.is_idle());
+}
+
+#[tokio::test]
+async fn times_out_when_nothing_answers() {
+    let line = Line::open(0, |_, _| b"");
+    let client = SerialPanel::open(&line.path, 1)
+        .unwrap()
+        .with_timeout(Duration::from_millis(300))
+        .with_retries(1);
+
+    let start = Instant::now();
+    let result = client.set_page(&page()).await;
+
+    assert!(matches!(result, Err(ClientError::Timeout)), "{result:?}");
+    assert!(start.elapsed() >= Duration::from_millis(600));
+    assert_eq!(line.next_frame(), expected_frame(1));
+    assert_eq!(line.next_frame(), expected_frame(1));
+}
+
+#[tokio::test]
+async fn resends_frame_garbled_on_the_line() {
+    let line = Line::open(1, panel);
+    let client = SerialPanel::open(&line.path, 1).unwrap();
+
+    client.set_page(&page()).await.unwrap();
+
+    assert_eq!(line.next_frame(), Decoded::InvalidChecksum(1));
+    assert_eq!(line.next_frame(), expected_frame(1));
+    assert!(line.is_idle());
+}
+
+#[tokio::test]
+async fn broadcast_does_not_wait_for_an_answer() {
+    let line = Line::open(0, |_, _| b"");
+    let client = SerialPanel::open(&line.path, BROADCAST_ID)
+        .unwrap()
+        .with_timeout(Duration::from_secs(10));
+
+    let start = Instant::now();
+    client.set_page(&page()).await.unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert_eq!(line.next_frame(), expected_frame(BROADCAST_ID));
+    assert!(line.is_idle());
+}
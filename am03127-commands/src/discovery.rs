@@ -0,0 +1,71 @@
+extern crate alloc;
+use alloc::string::{String, ToString};
+use core::fmt::{self, Display};
+
+/// UDP port the controller listens on for discovery probes
+pub const DISCOVERY_PORT: u16 = 3127;
+/// Multicast group the controller joins to receive discovery probes
+pub const DISCOVERY_GROUP: [u8; 4] = [239, 255, 31, 27];
+/// Datagram asking all controllers on the network to announce themselves
+pub const PROBE: &[u8] = b"AM03127?";
+/// Prefix of every announcement
+const ANNOUNCEMENT_PREFIX: &str = "AM03127!";
+
+/// Answer of a controller to a discovery probe
+///
+/// Sent as a single datagram `AM03127!<port> <version> <name>`. The name comes
+/// last and may contain spaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Announcement {
+    /// Name of the panel
+    pub name: String,
+    /// Firmware version of the controller
+    pub version: String,
+    /// TCP port of the controller's HTTP server
+    pub port: u16,
+}
+
+impl Announcement {
+    /// Creates a new announcement
+    ///
+    /// # Arguments
+    /// * `name` - The name of the panel
+    /// * `version` - The firmware version of the controller
+    /// * `port` - The TCP port of the HTTP server
+    ///
+    /// # Returns
+    /// A new announcement
+    pub fn new(name: &str, version: &str, port: u16) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            port,
+        }
+    }
+
+    /// Parses a received announcement
+    ///
+    /// # Arguments
+    /// * `datagram` - The received datagram
+    ///
+    /// # Returns
+    /// The announcement, or `None` if the datagram is no valid announcement
+    pub fn parse(datagram: &[u8]) -> Option<Self> {
+        let text = core::str::from_utf8(datagram).ok()?;
+        let mut fields = text.strip_prefix(ANNOUNCEMENT_PREFIX)?.splitn(3, ' ');
+        let port = fields.next()?.parse().ok()?;
+        let version = fields.next().filter(|version| !version.is_empty())?;
+        let name = fields.next().filter(|name| !name.is_empty())?;
+        Some(Self::new(name, version, port))
+    }
+}
+
+impl Display for Announcement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{ANNOUNCEMENT_PREFIX}{} {} {}",
+            self.port, self.version, self.name
+        )
+    }
+}
@@ -2,10 +2,13 @@
 
 use core::fmt::{self, Display};
 
+use serde::{Deserialize, Serialize};
+
 /// Font sizes available for text on the LED panel
 ///
 /// Different font sizes can be used to display text with different
 /// appearances on the LED panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Font {
     /// Normal size (5x7)
     Normal,
@@ -36,6 +39,7 @@ impl Display for Font {
 /// Specifies the starting column for text on the LED panel
 ///
 /// This allows positioning text at a specific horizontal position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ColumnStart(pub u8);
 
 impl Display for ColumnStart {
@@ -47,6 +51,7 @@ impl Display for ColumnStart {
 /// Clock display formats for the LED panel
 ///
 /// These formats allow displaying the current time or date on the panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Clock {
     /// Date in format [DD/MM/YY]
     Date,
@@ -64,3 +69,21 @@ impl Display for Clock {
         write!(f, "{command}")
     }
 }
+
+/// Inserts a graphic block into the message
+///
+/// The block has to be sent to the panel beforehand with
+/// [`GraphicBlock`](crate::graphic::GraphicBlock).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Graphic {
+    /// Graphic page (A-P)
+    pub page: char,
+    /// Block number inside the graphic page (1-8)
+    pub block: u8,
+}
+
+impl Display for Graphic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<G{}{}>", self.page, self.block)
+    }
+}
@@ -0,0 +1,514 @@
+extern crate alloc;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Display};
+
+use super::{Payload, checksum, graphic::BLOCK_DATA_SIZE};
+
+/// Panel ID addressing every panel on the line at once
+///
+/// Panels never answer a frame sent to the broadcast ID.
+pub const BROADCAST_ID: u8 = 0x00;
+
+/// Response a panel sends back after receiving a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reply {
+    /// The panel does not answer (broadcast frames)
+    None,
+    /// The panel answers with `ACK` or `NACK`
+    Ack,
+    /// The panel echoes its newly assigned ID as two hex digits
+    Id(u8),
+}
+
+/// Result of decoding the start of a received byte stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decoded {
+    /// A complete frame with a matching checksum
+    Frame(Frame),
+    /// A complete frame for the given panel ID whose checksum does not match
+    InvalidChecksum(u8),
+    /// Bytes starting like a frame which can not be decoded
+    Malformed,
+    /// The bytes do not contain a complete frame yet
+    Incomplete,
+}
+
+/// Start of every frame
+const FRAME_START: &[u8] = b"<ID";
+/// Ending code of every frame
+const FRAME_END: &[u8] = b"<E>";
+
+/// Content carried by a frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Content {
+    /// Regular command payload, followed by its checksum
+    Command(Vec<u8>),
+    /// ID setting, which carries no payload and no checksum
+    SetId,
+}
+
+/// A single transmission unit for one panel ID
+///
+/// A frame wraps a command payload with the panel ID, the XOR checksum
+/// and the ending code, e.g. `<ID01><DTA>53<E>`. The [`Display`] output is
+/// only exact for text payloads, use [`Frame::to_bytes`] for binary ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// ID of the addressed panel (00 is broadcast)
+    id: u8,
+    /// Content of the frame
+    content: Content,
+}
+
+impl Frame {
+    /// Creates a new frame carrying a command
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the panel to send the command to
+    /// * `command` - The command to send
+    ///
+    /// # Returns
+    /// * A new Frame instance
+    pub fn new<C: Payload + ?Sized>(id: u8, command: &C) -> Self {
+        Self {
+            id,
+            content: Content::Command(command.payload()),
+        }
+    }
+
+    /// Creates a frame assigning a new ID to the connected panel
+    ///
+    /// Only one panel may be connected to the line when sending this frame.
+    /// Setting the [`BROADCAST_ID`] leaves the panel answering no frame at
+    /// all, as every frame addressed to it is then a broadcast.
+    ///
+    /// # Arguments
+    /// * `id` - The ID to set for the panel (01-FF)
+    ///
+    /// # Returns
+    /// * A new Frame instance
+    pub fn set_id(id: u8) -> Self {
+        Self {
+            id,
+            content: Content::SetId,
+        }
+    }
+
+    /// Returns the ID of the addressed panel
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Returns the command payload, or `None` for an ID setting
+    pub fn payload(&self) -> Option<&[u8]> {
+        match &self.content {
+            Content::Command(payload) => Some(payload),
+            Content::SetId => None,
+        }
+    }
+
+    /// Decodes the first frame of a received byte stream
+    ///
+    /// Bytes in front of the first frame are skipped. Graphic payloads are
+    /// read by their fixed length as their pixel data may contain the
+    /// ending code.
+    ///
+    /// # Arguments
+    /// * `bytes` - The bytes received so far
+    ///
+    /// # Returns
+    /// * The decoding result and the number of bytes the caller can drop
+    pub fn decode(bytes: &[u8]) -> (Decoded, usize) {
+        let Some(start) = find(bytes, FRAME_START) else {
+            // Keep a possibly cut off frame start
+            let keep = FRAME_START.len() - 1;
+            return (Decoded::Incomplete, bytes.len().saturating_sub(keep));
+        };
+        let frame = &bytes[start..];
+        let malformed = (Decoded::Malformed, start + FRAME_START.len());
+
+        // ID setting: <ID><XX><E>
+        if frame.starts_with(b"<ID><") {
+            let Some(end) = frame.get(..11) else {
+                return (Decoded::Incomplete, start);
+            };
+            return match (parse_hex(&end[5..7]), &end[7..]) {
+                (Some(id), b"><E>") => (Decoded::Frame(Frame::set_id(id)), start + end.len()),
+                _ => malformed,
+            };
+        }
+
+        // Command: <IDXX>payload CS<E>
+        let Some(header) = frame.get(..6) else {
+            return (Decoded::Incomplete, start);
+        };
+        let Some(id) = parse_hex(&header[3..5]).filter(|_| header[5] == b'>') else {
+            return malformed;
+        };
+        let body = &frame[6..];
+        let end = if body.starts_with(b"<G") {
+            let end = 5 + BLOCK_DATA_SIZE + 2;
+            match body.get(end..end + FRAME_END.len()) {
+                None => return (Decoded::Incomplete, start),
+                Some(ending) if ending != FRAME_END => return malformed,
+                Some(_) => end,
+            }
+        } else {
+            match find(body, FRAME_END) {
+                None => return (Decoded::Incomplete, start),
+                Some(end) if end < 2 => return malformed,
+                Some(end) => end,
+            }
+        };
+
+        let consumed = start + 6 + end + FRAME_END.len();
+        let payload = &body[..end - 2];
+        match parse_hex(&body[end - 2..end]) {
+            Some(check) if check == checksum(payload) => (
+                Decoded::Frame(Frame {
+                    id,
+                    content: Content::Command(payload.to_vec()),
+                }),
+                consumed,
+            ),
+            _ => (Decoded::InvalidChecksum(id), consumed),
+        }
+    }
+
+    /// Returns whether the frame is addressed to every panel on the line
+    pub fn is_broadcast(&self) -> bool {
+        matches!(self.content, Content::Command(_)) && self.id == BROADCAST_ID
+    }
+
+    /// Returns the response the transport has to wait for after sending the frame
+    pub fn reply(&self) -> Reply {
+        match self.content {
+            Content::SetId => Reply::Id(self.id),
+            Content::Command(_) if self.id == BROADCAST_ID => Reply::None,
+            Content::Command(_) => Reply::Ack,
+        }
+    }
+
+    /// Encodes the frame into the bytes sent over the line
+    ///
+    /// # Returns
+    /// * The encoded frame
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match &self.content {
+            Content::Command(payload) => {
+                let mut bytes = format!("<ID{:02X}>", self.id).into_bytes();
+                bytes.extend_from_slice(payload);
+                bytes.extend_from_slice(format!("{:02X}<E>", checksum(payload)).as_bytes());
+                bytes
+            }
+            Content::SetId => self.to_string().into_bytes(),
+        }
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.content {
+            Content::Command(payload) => write!(
+                f,
+                "<ID{:02X}>{}{:02X}<E>",
+                self.id,
+                String::from_utf8_lossy(payload),
+                checksum(payload)
+            ),
+            Content::SetId => write!(f, "<ID><{:02X}><E>", self.id),
+        }
+    }
+}
+
+/// A set of commands sent to one or more panels in a single transmission
+///
+/// Every command is framed once per addressed panel ID and all frames are
+/// concatenated. When the broadcast ID is among the addressed panels,
+/// every command is sent only once to the broadcast ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Batch {
+    /// IDs of the addressed panels
+    ids: Vec<u8>,
+    /// Payloads of the commands in sending order
+    payloads: Vec<Vec<u8>>,
+}
+
+impl Batch {
+    /// Creates a new empty batch for the given panel IDs
+    ///
+    /// # Arguments
+    /// * `ids` - The IDs of the panels to address
+    ///
+    /// # Returns
+    /// * A new Batch instance
+    pub fn new(ids: impl IntoIterator<Item = u8>) -> Self {
+        let mut batch = Self::default();
+        for id in ids {
+            batch.add_panel(id);
+        }
+        batch
+    }
+
+    /// Creates a new empty batch addressed to every panel on the line
+    ///
+    /// # Returns
+    /// * A new Batch instance
+    pub fn broadcast() -> Self {
+        Self::new([BROADCAST_ID])
+    }
+
+    /// Adds a panel ID to the addressed panels
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the panel to address
+    pub fn add_panel(&mut self, id: u8) {
+        if !self.ids.contains(&id) {
+            self.ids.push(id);
+        }
+    }
+
+    /// Appends a command to the batch
+    ///
+    /// # Arguments
+    /// * `command` - The command to append
+    pub fn push<C: Payload + ?Sized>(&mut self, command: &C) {
+        self.payloads.push(command.payload());
+    }
+
+    /// Appends a command to the batch and returns it
+    ///
+    /// # Arguments
+    /// * `command` - The command to append
+    ///
+    /// # Returns
+    /// * The batch including the command
+    pub fn with<C: Payload + ?Sized>(mut self, command: &C) -> Self {
+        self.push(command);
+        self
+    }
+
+    /// Returns whether the batch contains no frames
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty() || self.payloads.is_empty()
+    }
+
+    /// Returns whether the batch is addressed to every panel on the line
+    pub fn is_broadcast(&self) -> bool {
+        self.ids.contains(&BROADCAST_ID)
+    }
+
+    /// Returns the IDs the frames are actually sent to
+    fn recipients(&self) -> &[u8] {
+        if self.is_broadcast() {
+            &[BROADCAST_ID]
+        } else {
+            &self.ids
+        }
+    }
+
+    /// Builds the frames of the batch in sending order
+    ///
+    /// # Returns
+    /// * One frame per command and addressed panel
+    pub fn frames(&self) -> Vec<Frame> {
+        self.payloads
+            .iter()
+            .flat_map(|payload| {
+                self.recipients().iter().map(|&id| Frame {
+                    id,
+                    content: Content::Command(payload.clone()),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns whether the transport has to wait for acknowledgements
+    pub fn expects_ack(&self) -> bool {
+        self.expected_acks() > 0
+    }
+
+    /// Returns the number of `ACK`/`NACK` responses the transport has to wait for
+    pub fn expected_acks(&self) -> usize {
+        if self.is_broadcast() {
+            0
+        } else {
+            self.ids.len() * self.payloads.len()
+        }
+    }
+
+    /// Encodes all frames of the batch into the bytes sent over the line
+    ///
+    /// # Returns
+    /// * The concatenated encoded frames
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.frames().iter().flat_map(Frame::to_bytes).collect()
+    }
+}
+
+impl Display for Batch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for frame in self.frames() {
+            write!(f, "{frame}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the position of the first occurrence of `needle` in `bytes`
+fn find(bytes: &[u8], needle: &[u8]) -> Option<usize> {
+    bytes
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Parses two hexadecimal digits
+fn parse_hex(digits: &[u8]) -> Option<u8> {
+    if !digits.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    u8::from_str_radix(core::str::from_utf8(digits).ok()?, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{
+        delete::{DeleteAll, DeletePage},
+        graphic::{Color, GraphicBlock},
+    };
+
+    #[test]
+    fn encodes_a_command_with_its_checksum() {
+        let frame = Frame::new(0x1F, &DeleteAll {});
+
+        assert_eq!(frame.to_bytes(), b"<ID1F><D*>6C<E>");
+        assert_eq!(frame.to_string(), "<ID1F><D*>6C<E>");
+        assert_eq!(frame.payload(), Some(&b"<D*>"[..]));
+    }
+
+    #[test]
+    fn decodes_a_frame_after_skipping_noise() {
+        let frame = Frame::new(1, &DeleteAll {});
+        let mut bytes = b"ACK".to_vec();
+        bytes.extend(frame.to_bytes());
+
+        assert_eq!(Frame::decode(&bytes), (Decoded::Frame(frame), bytes.len()));
+    }
+
+    #[test]
+    fn partial_frames_are_incomplete() {
+        let bytes = Frame::new(1, &DeleteAll {}).to_bytes();
+
+        for length in 0..bytes.len() {
+            let (decoded, consumed) = Frame::decode(&bytes[..length]);
+            assert_eq!(decoded, Decoded::Incomplete, "{length} bytes");
+            assert_eq!(consumed, 0, "{length} bytes");
+        }
+        assert_eq!(Frame::decode(b"noise<I"), (Decoded::Incomplete, 5));
+    }
+
+    #[test]
+    fn wrong_checksum_is_reported_for_the_panel() {
+        let mut bytes = Frame::new(2, &DeleteAll {}).to_bytes();
+        let checksum = bytes.len() - 5;
+        bytes[checksum] = b'0';
+
+        assert_eq!(
+            Frame::decode(&bytes),
+            (Decoded::InvalidChecksum(2), bytes.len())
+        );
+    }
+
+    #[test]
+    fn invalid_ids_are_malformed() {
+        assert_eq!(Frame::decode(b"<IDZZ><D*>6C<E>"), (Decoded::Malformed, 3));
+        assert_eq!(Frame::decode(b"<ID+1><D*>6C<E>"), (Decoded::Malformed, 3));
+        assert_eq!(Frame::decode(b"<ID01>F<E>"), (Decoded::Malformed, 3));
+        assert_eq!(Frame::decode(b"<ID><+5><E>"), (Decoded::Malformed, 3));
+    }
+
+    #[test]
+    fn graphic_payloads_are_read_by_their_length() {
+        // The first bytes of the pixel data are "<E>"
+        let mut pixels = vec![Color::Black; 64];
+        pixels[1..3].fill(Color::Yellow);
+        pixels[4] = Color::Green;
+        pixels[6..8].fill(Color::Green);
+        pixels[33..35].fill(Color::Yellow);
+        pixels[35] = Color::Red;
+        let block = GraphicBlock::new('A', 1, &pixels);
+        let graphic = Frame::new(1, &block);
+        let next = Frame::new(1, &DeletePage::new('B'));
+        let mut bytes = graphic.to_bytes();
+        let length = bytes.len();
+        bytes.extend(next.to_bytes());
+
+        assert_eq!(&bytes[11..14], b"<E>");
+        assert_eq!(Frame::decode(&bytes), (Decoded::Frame(graphic), length));
+        assert_eq!(
+            Frame::decode(&bytes[length..]),
+            (Decoded::Frame(next), bytes.len() - length)
+        );
+        assert_eq!(
+            Frame::decode(&bytes[..length - 1]),
+            (Decoded::Incomplete, 0)
+        );
+    }
+
+    #[test]
+    fn id_setting_round_trips() {
+        let frame = Frame::set_id(5);
+
+        assert_eq!(frame.to_bytes(), b"<ID><05><E>");
+        assert_eq!(frame.payload(), None);
+        assert_eq!(Frame::decode(b"<ID><05><E>"), (Decoded::Frame(frame), 11));
+        assert_eq!(Frame::decode(b"<ID><05>"), (Decoded::Incomplete, 0));
+    }
+
+    #[test]
+    fn replies_depend_on_the_address() {
+        assert_eq!(Frame::new(1, &DeleteAll {}).reply(), Reply::Ack);
+        assert_eq!(Frame::new(BROADCAST_ID, &DeleteAll {}).reply(), Reply::None);
+        assert!(Frame::new(BROADCAST_ID, &DeleteAll {}).is_broadcast());
+        assert_eq!(Frame::set_id(7).reply(), Reply::Id(7));
+        assert!(!Frame::set_id(BROADCAST_ID).is_broadcast());
+    }
+
+    #[test]
+    fn batch_frames_every_command_for_every_panel() {
+        let batch = Batch::new([1, 2, 1])
+            .with(&DeleteAll {})
+            .with(&DeletePage::new('B'));
+
+        let ids: Vec<u8> = batch.frames().iter().map(Frame::id).collect();
+        assert_eq!(ids, [1, 2, 1, 2]);
+        assert_eq!(batch.frames()[2].payload(), Some(&b"<DL1PB>"[..]));
+        assert_eq!(batch.expected_acks(), 4);
+        assert_eq!(
+            batch.to_string(),
+            "<ID01><D*>6C<E><ID02><D*>6C<E><ID01><DL1PB>29<E><ID02><DL1PB>29<E>"
+        );
+    }
+
+    #[test]
+    fn broadcast_batch_sends_every_command_once() {
+        let mut batch = Batch::new([3, BROADCAST_ID]);
+        assert!(batch.is_empty());
+        batch.push(&DeleteAll {});
+
+        assert!(batch.is_broadcast());
+        assert!(!batch.expects_ack());
+        assert_eq!(batch.frames(), [Frame::new(BROADCAST_ID, &DeleteAll {})]);
+        assert_eq!(
+            batch.to_bytes(),
+            Batch::broadcast().with(&DeleteAll {}).to_bytes()
+        );
+    }
+}
@@ -0,0 +1,141 @@
+extern crate alloc;
+use alloc::{format, vec, vec::Vec};
+use core::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use super::Payload;
+
+/// Width of a graphic block in pixels
+pub const BLOCK_WIDTH: usize = 32;
+/// Height of a graphic block in pixels
+pub const BLOCK_HEIGHT: usize = 8;
+/// Size of the pixel data of a graphic block in bytes (2 bits per pixel)
+pub const BLOCK_DATA_SIZE: usize = BLOCK_WIDTH * BLOCK_HEIGHT / 4;
+/// Number of blocks in a graphic page
+pub const BLOCKS_PER_PAGE: u8 = 8;
+/// First graphic page
+pub const FIRST_GRAPHIC_PAGE: char = 'A';
+/// Last graphic page
+pub const LAST_GRAPHIC_PAGE: char = 'P';
+
+/// Width of the 8x8 units a graphic block is built of
+const UNIT_WIDTH: usize = 8;
+
+/// Pixel colors available for graphic blocks
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Color {
+    /// Pixel is off
+    #[default]
+    Black,
+    /// Green LED is on
+    Green,
+    /// Red LED is on
+    Red,
+    /// Red and green LEDs are on
+    Yellow,
+}
+
+impl Color {
+    /// Creates a color from the state of the red and green LEDs
+    ///
+    /// # Arguments
+    /// * `red` - Whether the red LED is on
+    /// * `green` - Whether the green LED is on
+    ///
+    /// # Returns
+    /// * The resulting color
+    pub fn from_leds(red: bool, green: bool) -> Self {
+        match (red, green) {
+            (false, false) => Color::Black,
+            (false, true) => Color::Green,
+            (true, false) => Color::Red,
+            (true, true) => Color::Yellow,
+        }
+    }
+
+    /// Returns the 2 bit code of the color, MSB is red and LSB is green
+    fn bits(self) -> u8 {
+        match self {
+            Color::Black => 0b00,
+            Color::Green => 0b01,
+            Color::Red => 0b10,
+            Color::Yellow => 0b11,
+        }
+    }
+}
+
+/// A 32x8 pixel graphic block stored in a graphic page of the LED panel
+///
+/// Graphic blocks are inserted into page messages with
+/// [`Graphic`](crate::formatting::Graphic).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GraphicBlock {
+    /// Graphic page (A-P)
+    pub page: char,
+    /// Block number inside the graphic page (1-8)
+    pub block: u8,
+    /// Pixel data, four pixels per byte
+    data: Vec<u8>,
+}
+
+impl Payload for GraphicBlock {
+    fn payload(&self) -> Vec<u8> {
+        let mut payload = format!("<G{}{}>", self.page, self.block).into_bytes();
+        payload.extend_from_slice(&self.data);
+        payload
+    }
+}
+
+impl GraphicBlock {
+    /// Creates a new graphic block from its pixels
+    ///
+    /// # Arguments
+    /// * `page` - The graphic page (A-P)
+    /// * `block` - The block number inside the graphic page (1-8)
+    /// * `pixels` - The 32x8 pixels row by row, missing pixels are black
+    ///
+    /// # Returns
+    /// * A new GraphicBlock instance
+    pub fn new(page: char, block: u8, pixels: &[Color]) -> Self {
+        let mut data = vec![0u8; BLOCK_DATA_SIZE];
+        // The block is built of four 8x8 units from left to right,
+        // each of them sent row by row.
+        for (index, color) in pixels.iter().take(BLOCK_WIDTH * BLOCK_HEIGHT).enumerate() {
+            let (x, y) = (index % BLOCK_WIDTH, index / BLOCK_WIDTH);
+            let unit = x / UNIT_WIDTH;
+            let position = unit * UNIT_WIDTH * BLOCK_HEIGHT + y * UNIT_WIDTH + x % UNIT_WIDTH;
+            let shift = 6 - 2 * (position % 4);
+            data[position / 4] |= color.bits() << shift;
+        }
+
+        Self { page, block, data }
+    }
+
+    /// Checks if the graphic page, block number and data size are valid
+    ///
+    /// # Returns
+    /// * `true` if the block can be sent to the panel
+    /// * `false` otherwise
+    pub fn is_valid(&self) -> bool {
+        (FIRST_GRAPHIC_PAGE..=LAST_GRAPHIC_PAGE).contains(&self.page)
+            && (1..=BLOCKS_PER_PAGE).contains(&self.block)
+            && self.data.len() == BLOCK_DATA_SIZE
+    }
+}
+
+/// Shows the block with its pixel data as hex, e.g. `<GA1>00FF…`
+///
+/// The pixel data is sent as raw bytes, so this is not the wire format. Use
+/// [`Payload::payload`] or [`Frame::to_bytes`](crate::frame::Frame::to_bytes)
+/// to send the block.
+impl Display for GraphicBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<G{}{}>", self.page, self.block)?;
+        for byte in &self.data {
+            write!(f, "{byte:02X}")?;
+        }
+        Ok(())
+    }
+}
@@ -2,18 +2,27 @@
 #![allow(dead_code)]
 
 pub mod delete;
+pub mod discovery;
 pub mod formatting;
+pub mod frame;
+pub mod graphic;
+pub mod markup;
+pub mod memory;
 pub mod page;
 pub mod realtime_clock;
 pub mod schedule;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 extern crate alloc;
 use alloc::{
-    format,
     string::{String, ToString},
+    vec::Vec,
 };
 use core::fmt::Display;
 
+use frame::Frame;
+
 // Constants for string sizes and defaults
 /// Default page ID
 pub const DEFAULT_PAGE: char = 'A';
@@ -21,12 +30,15 @@ pub const DEFAULT_PAGE: char = 'A';
 pub const DEFAULT_LINE: u8 = 1;
 /// Default schedule ID
 pub const DEFAULT_SCHEDULE: char = 'A';
+/// Maximum number of pages in a schedule
+pub const MAX_SCHEDULE_PAGES: usize = 31;
 
 /// Trait for types that can be converted to AM03127 panel commands
 ///
-/// This trait is implemented by types that represent commands for the LED panel.
-/// It provides a method to convert the command to a string with the proper format,
-/// including panel ID and checksum.
+/// This trait is implemented by types that represent text commands for the LED
+/// panel. It provides a method to convert the command to a string with the proper
+/// format, including panel ID and checksum. Use [`frame::Batch`] to send several
+/// commands to several panels in one transmission.
 pub trait CommandAble: Display {
     /// Converts the command to a string with the proper format for the LED panel
     ///
@@ -36,9 +48,27 @@ pub trait CommandAble: Display {
     /// # Returns
     /// * A string containing the formatted command
     fn command(&self, id: u8) -> String {
-        let payload = self.to_string();
-        let checksum = checksum(&payload);
-        format!("<ID{:02X}>{}{:02X}<E>", id, payload, checksum)
+        Frame::new(id, self).to_string()
+    }
+}
+
+/// Trait for types that can be framed and sent to the LED panel
+///
+/// Every [`CommandAble`] is sent as the UTF-8 encoding of its [`Display`]
+/// output. Commands carrying binary data, like
+/// [`GraphicBlock`](graphic::GraphicBlock), only implement this trait, as no
+/// string can hold them. Send them with [`Frame::to_bytes`].
+pub trait Payload {
+    /// Returns the raw bytes of the command as they are sent to the panel
+    ///
+    /// # Returns
+    /// * The payload bytes without panel ID, checksum and ending code
+    fn payload(&self) -> Vec<u8>;
+}
+
+impl<C: CommandAble + ?Sized> Payload for C {
+    fn payload(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
     }
 }
 
@@ -50,7 +80,31 @@ pub trait CommandAble: Display {
 /// # Returns
 /// * A string containing the formatted command
 pub fn set_id(id: u8) -> String {
-    format!("<ID><{:02X}><E>", id)
+    Frame::set_id(id).to_string()
+}
+
+/// Checks if a page ID is valid (A-Z)
+///
+/// # Arguments
+/// * `id` - The ID to check
+///
+/// # Returns
+/// * `true` if the ID is valid (A-Z)
+/// * `false` otherwise
+pub fn is_page_id_valid(id: char) -> bool {
+    id.is_ascii_uppercase()
+}
+
+/// Checks if a schedule ID is valid (A-E)
+///
+/// # Arguments
+/// * `id` - The ID to check
+///
+/// # Returns
+/// * `true` if the ID is valid (A-E)
+/// * `false` otherwise
+pub fn is_schedule_id_valid(id: char) -> bool {
+    ('A'..='E').contains(&id)
 }
 
 /// Calculates the checksum for a command payload
@@ -62,9 +116,9 @@ pub fn set_id(id: u8) -> String {
 ///
 /// # Returns
 /// * The calculated checksum
-fn checksum(payload: &str) -> u8 {
+fn checksum(payload: &[u8]) -> u8 {
     let mut check: u8 = 0;
-    for character in payload.as_bytes() {
+    for character in payload {
         check ^= character;
     }
     check
@@ -0,0 +1,382 @@
+extern crate alloc;
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use super::formatting::{Clock, ColumnStart, Font, Graphic};
+
+/// European characters and their codes in the panel's character table
+const EUROPEAN_CHARACTERS: [(char, u8); 7] = [
+    ('ü', 0x7C),
+    ('Ü', 0x5C),
+    ('ä', 0x64),
+    ('Ä', 0x44),
+    ('ö', 0x76),
+    ('Ö', 0x56),
+    ('ß', 0x5F),
+];
+/// Valid codes of the `<CX>` color tag
+const COLOR_CODES: &str = "ABCDEFGHIJKLMNPQRS";
+
+/// Element of a page message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Token {
+    /// Plain text
+    Text(String),
+    /// `<AX>` Font of the following characters
+    Font(Font),
+    /// `<BX>` Bell duration in half-second steps (A = 0.5s)
+    Bell(u8),
+    /// `<CX>` Color code of the following characters (A-S)
+    Color(char),
+    /// `<GXn>` Graphic block
+    Graphic(Graphic),
+    /// `<KX>` Date or time
+    Clock(Clock),
+    /// `<UXX>` Character of the European character table
+    European(u8),
+    /// `<NXX>` Starting column of the following characters
+    Column(ColumnStart),
+}
+
+/// Errors found while parsing a page message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkupError {
+    /// A `<` without matching `>` at the given character position
+    UnterminatedTag(usize),
+    /// An unknown or malformed tag at the given character position
+    InvalidTag(usize, String),
+    /// A character the panel cannot display at the given character position
+    InvalidCharacter(usize, char),
+}
+
+impl Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkupError::UnterminatedTag(position) => {
+                write!(f, "Unterminated tag at position {position}")
+            }
+            MarkupError::InvalidTag(position, tag) => {
+                write!(f, "Invalid tag <{tag}> at position {position}")
+            }
+            MarkupError::InvalidCharacter(position, character) => {
+                write!(f, "Invalid character '{character}' at position {position}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for MarkupError {}
+
+/// Parses a page message into its elements
+///
+/// European characters are returned as [`Token::European`].
+///
+/// # Arguments
+/// * `message` - The message text including markup tags
+///
+/// # Returns
+/// * `Ok(Vec<Token>)` - The elements of the message
+/// * `Err(MarkupError)` - If the message contains invalid tags or characters
+pub fn parse(message: &str) -> Result<Vec<Token>, MarkupError> {
+    let characters: Vec<char> = message.chars().collect();
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut position = 0;
+
+    while position < characters.len() {
+        let character = characters[position];
+        if character == '<' {
+            let Some(length) = characters[position + 1..].iter().position(|&c| c == '>') else {
+                return Err(MarkupError::UnterminatedTag(position));
+            };
+            let tag: String = characters[position + 1..position + 1 + length]
+                .iter()
+                .collect();
+            let token = parse_tag(&tag).ok_or(MarkupError::InvalidTag(position, tag))?;
+            if !text.is_empty() {
+                tokens.push(Token::Text(core::mem::take(&mut text)));
+            }
+            tokens.push(token);
+            position += length + 2;
+            continue;
+        }
+
+        if let Some(code) = european_code(character) {
+            if !text.is_empty() {
+                tokens.push(Token::Text(core::mem::take(&mut text)));
+            }
+            tokens.push(Token::European(code));
+        } else if (' '..='~').contains(&character) {
+            text.push(character);
+        } else {
+            return Err(MarkupError::InvalidCharacter(position, character));
+        }
+        position += 1;
+    }
+
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+
+    Ok(tokens)
+}
+
+/// Checks if a page message only contains valid tags and characters
+///
+/// # Arguments
+/// * `message` - The message text including markup tags
+///
+/// # Returns
+/// * `Ok(())` if the message is valid
+/// * `Err(MarkupError)` describing the first problem otherwise
+pub fn validate(message: &str) -> Result<(), MarkupError> {
+    parse(message).map(|_| ())
+}
+
+/// Replaces European characters with their panel-specific codes
+///
+/// # Arguments
+/// * `message` - The message text with possible European characters
+///
+/// # Returns
+/// * A new string with European characters replaced by panel codes
+pub fn escape(message: &str) -> String {
+    let mut result = String::new();
+    for c in message.chars() {
+        match european_code(c) {
+            Some(code) => result.push_str(&format!("<U{code:02X}>")),
+            None => result.push(c),
+        }
+    }
+    result
+}
+
+/// Renders a page message as readable text
+///
+/// Formatting tags are dropped, European characters are restored and date,
+/// time and graphic blocks are shown as placeholders. Messages which can not
+/// be parsed are returned unchanged.
+///
+/// # Arguments
+/// * `message` - The message text including markup tags
+///
+/// # Returns
+/// * The readable text
+pub fn plain_text(message: &str) -> String {
+    let Ok(tokens) = parse(message) else {
+        return String::from(message);
+    };
+
+    let mut result = String::new();
+    for token in tokens {
+        match token {
+            Token::Text(text) => result.push_str(&text),
+            Token::European(code) => match european_character(code) {
+                Some(character) => result.push(character),
+                None => result.push_str(&format!("<U{code:02X}>")),
+            },
+            Token::Clock(Clock::Date) => result.push_str("[DD/MM/YY]"),
+            Token::Clock(Clock::Time) => result.push_str("[hh:mm]"),
+            Token::Graphic(graphic) => {
+                result.push_str(&format!("[G{}{}]", graphic.page, graphic.block))
+            }
+            Token::Font(_) | Token::Bell(_) | Token::Color(_) | Token::Column(_) => {}
+        }
+    }
+    result
+}
+
+/// Parses the content of a tag without the enclosing `<` and `>`
+fn parse_tag(tag: &str) -> Option<Token> {
+    let mut characters = tag.chars();
+    let kind = characters.next()?;
+    let argument: String = characters.collect();
+    let mut arguments = argument.chars();
+    let first = arguments.next()?;
+    let rest = arguments.as_str();
+
+    let token = match (kind, rest.len()) {
+        ('A', 0) => Token::Font(match first {
+            'A' => Font::Normal,
+            'B' => Font::Bold,
+            'C' => Font::Narrow,
+            'D' => Font::Large,
+            'E' => Font::Long,
+            _ => return None,
+        }),
+        ('B', 0) if first.is_ascii_uppercase() => Token::Bell(first as u8 - b'A'),
+        ('C', 0) if COLOR_CODES.contains(first) => Token::Color(first),
+        ('G', 1) if ('A'..='P').contains(&first) => {
+            let block = rest.chars().next()?.to_digit(10)? as u8;
+            if !(1..=8).contains(&block) {
+                return None;
+            }
+            Token::Graphic(Graphic { page: first, block })
+        }
+        ('K', 0) => Token::Clock(match first {
+            'D' => Clock::Date,
+            'T' => Clock::Time,
+            _ => return None,
+        }),
+        ('U', 1) => {
+            let code = hex_byte(&argument)?;
+            if code > 0x7F {
+                return None;
+            }
+            Token::European(code)
+        }
+        ('N', 1) => Token::Column(ColumnStart(hex_byte(&argument)?)),
+        _ => return None,
+    };
+
+    Some(token)
+}
+
+/// Parses a hexadecimal tag argument, which unlike `u8::from_str_radix` must
+/// not have a sign
+fn hex_byte(argument: &str) -> Option<u8> {
+    if !argument.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u8::from_str_radix(argument, 16).ok()
+}
+
+/// Returns the panel code of a European character
+fn european_code(character: char) -> Option<u8> {
+    EUROPEAN_CHARACTERS
+        .iter()
+        .find(|(c, _)| *c == character)
+        .map(|(_, code)| *code)
+}
+
+/// Returns the European character of a panel code
+fn european_character(code: u8) -> Option<char> {
+    EUROPEAN_CHARACTERS
+        .iter()
+        .find(|(_, c)| *c == code)
+        .map(|(character, _)| *character)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+
+    #[test]
+    fn parses_text_and_tags() {
+        let tokens = parse("<AB>Hi <CA>you<KT><GB3><N1F><BC>").unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Font(Font::Bold),
+                Token::Text(String::from("Hi ")),
+                Token::Color('A'),
+                Token::Text(String::from("you")),
+                Token::Clock(Clock::Time),
+                Token::Graphic(Graphic {
+                    page: 'B',
+                    block: 3
+                }),
+                Token::Column(ColumnStart(0x1F)),
+                Token::Bell(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_the_tags_the_formatting_types_render() {
+        let message = [
+            Font::Long.to_string(),
+            Clock::Date.to_string(),
+            ColumnStart(0xA0).to_string(),
+            Graphic {
+                page: 'P',
+                block: 8,
+            }
+            .to_string(),
+        ]
+        .concat();
+
+        assert_eq!(
+            parse(&message).unwrap(),
+            vec![
+                Token::Font(Font::Long),
+                Token::Clock(Clock::Date),
+                Token::Column(ColumnStart(0xA0)),
+                Token::Graphic(Graphic {
+                    page: 'P',
+                    block: 8
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_signed_hex_arguments() {
+        assert_eq!(
+            validate("<U+F>"),
+            Err(MarkupError::InvalidTag(0, String::from("U+F")))
+        );
+        assert_eq!(
+            validate("x<N+F>"),
+            Err(MarkupError::InvalidTag(1, String::from("N+F")))
+        );
+        assert_eq!(
+            validate("<U-1>").unwrap_err().to_string(),
+            "Invalid tag <U-1> at position 0"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        assert_eq!(validate("a <AB"), Err(MarkupError::UnterminatedTag(2)));
+        assert_eq!(
+            validate("<AF>"),
+            Err(MarkupError::InvalidTag(0, String::from("AF")))
+        );
+        assert_eq!(
+            validate("<GA9>"),
+            Err(MarkupError::InvalidTag(0, String::from("GA9")))
+        );
+        assert_eq!(
+            validate("<CO>"),
+            Err(MarkupError::InvalidTag(0, String::from("CO")))
+        );
+        assert_eq!(
+            validate("<U80>"),
+            Err(MarkupError::InvalidTag(0, String::from("U80")))
+        );
+        assert_eq!(
+            validate("<>"),
+            Err(MarkupError::InvalidTag(0, String::new()))
+        );
+        assert_eq!(validate("€"), Err(MarkupError::InvalidCharacter(0, '€')));
+    }
+
+    #[test]
+    fn escaped_european_characters_round_trip() {
+        let message = "Grüße aus Köln, ÄÖÜ";
+
+        let escaped = escape(message);
+
+        assert_eq!(escaped, "Gr<U7C><U5F>e aus K<U76>ln, <U44><U56><U5C>");
+        assert_eq!(parse(&escaped).unwrap(), parse(message).unwrap());
+        assert_eq!(plain_text(&escaped), message);
+        assert_eq!(plain_text(message), message);
+    }
+
+    #[test]
+    fn plain_text_drops_formatting_and_shows_placeholders() {
+        assert_eq!(
+            plain_text("<AB><CB>It is <KT> on <KD><N10><GC1>"),
+            "It is [hh:mm] on [DD/MM/YY][GC1]"
+        );
+        assert_eq!(plain_text("<U41>"), "<U41>");
+        assert_eq!(plain_text("broken <AB"), "broken <AB");
+    }
+}
@@ -0,0 +1,162 @@
+extern crate alloc;
+use alloc::string::ToString;
+use core::fmt::{self, Display};
+
+use serde::Serialize;
+
+use super::{graphic::BLOCK_DATA_SIZE, page::Page, schedule::Schedule};
+
+/// Estimated internal memory of the panel in bytes
+pub const DEFAULT_MEMORY_CAPACITY: usize = 16 * 1024;
+/// Default usage in percent above which the budget reports a warning
+pub const DEFAULT_WARNING_PERCENT: u8 = 80;
+
+/// Utilization state of the panel memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryStatus {
+    /// The content fits comfortably
+    Ok,
+    /// The content fits but the warning threshold is reached
+    Warning,
+    /// The content does not fit into the panel memory
+    Overflow,
+}
+
+/// Memory budget of a panel
+///
+/// The panel does not report its free memory, so the budget estimates the
+/// used memory by the encoded size of the content sent to the panel.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryBudget {
+    /// Total memory of the panel in bytes
+    capacity: usize,
+    /// Usage in percent above which a warning is reported
+    warning_percent: u8,
+}
+
+impl MemoryBudget {
+    /// Creates a new memory budget
+    ///
+    /// # Arguments
+    /// * `capacity` - The total memory of the panel in bytes
+    ///
+    /// # Returns
+    /// * A new MemoryBudget instance
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            warning_percent: DEFAULT_WARNING_PERCENT,
+        }
+    }
+
+    /// Sets the usage in percent above which a warning is reported
+    ///
+    /// # Arguments
+    /// * `percent` - The warning threshold (0-100)
+    ///
+    /// # Returns
+    /// * The budget with the new warning threshold
+    pub fn with_warning_percent(mut self, percent: u8) -> Self {
+        self.warning_percent = percent.min(100);
+        self
+    }
+
+    /// Estimates the memory used by the given content
+    ///
+    /// # Arguments
+    /// * `pages` - The pages stored on the panel
+    /// * `schedules` - The schedules stored on the panel
+    /// * `graphic_blocks` - The number of graphic blocks stored on the panel
+    ///
+    /// # Returns
+    /// * The estimated memory usage
+    pub fn estimate<'a>(
+        &self,
+        pages: impl IntoIterator<Item = &'a Page>,
+        schedules: impl IntoIterator<Item = &'a Schedule>,
+        graphic_blocks: usize,
+    ) -> MemoryEstimate {
+        MemoryEstimate {
+            pages: pages.into_iter().map(encoded_size).sum(),
+            schedules: schedules.into_iter().map(encoded_size).sum(),
+            graphics: graphic_blocks * BLOCK_DATA_SIZE,
+            capacity: self.capacity,
+            warning_percent: self.warning_percent,
+        }
+    }
+}
+
+impl Default for MemoryBudget {
+    fn default() -> Self {
+        Self::new(DEFAULT_MEMORY_CAPACITY)
+    }
+}
+
+/// Estimated memory usage of a set of panel content
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MemoryEstimate {
+    /// Bytes used by pages
+    pub pages: usize,
+    /// Bytes used by schedules
+    pub schedules: usize,
+    /// Bytes used by graphic blocks
+    pub graphics: usize,
+    /// Total memory of the panel in bytes
+    pub capacity: usize,
+    /// Usage in percent above which a warning is reported
+    #[serde(skip)]
+    warning_percent: u8,
+}
+
+impl MemoryEstimate {
+    /// Returns the total number of used bytes
+    pub fn used(&self) -> usize {
+        self.pages + self.schedules + self.graphics
+    }
+
+    /// Returns the number of bytes left, zero if the memory overflows
+    pub fn remaining(&self) -> usize {
+        self.capacity.saturating_sub(self.used())
+    }
+
+    /// Returns the used memory in percent of the capacity
+    pub fn percent(&self) -> usize {
+        if self.capacity == 0 {
+            return 100;
+        }
+        self.used() * 100 / self.capacity
+    }
+
+    /// Returns the utilization state of the panel memory
+    pub fn status(&self) -> MemoryStatus {
+        if self.used() > self.capacity {
+            MemoryStatus::Overflow
+        } else if self.percent() >= self.warning_percent as usize {
+            MemoryStatus::Warning
+        } else {
+            MemoryStatus::Ok
+        }
+    }
+
+    /// Returns whether the content fits into the panel memory
+    pub fn fits(&self) -> bool {
+        self.status() != MemoryStatus::Overflow
+    }
+}
+
+impl Display for MemoryEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} bytes ({}%)",
+            self.used(),
+            self.capacity,
+            self.percent()
+        )
+    }
+}
+
+/// Returns the size of the command payload as it is sent to the panel
+fn encoded_size(command: &impl Display) -> usize {
+    command.to_string().len()
+}
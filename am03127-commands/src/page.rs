@@ -2,16 +2,19 @@
 
 extern crate alloc;
 use alloc::string::String;
-use core::fmt::{self, Display};
+use core::{
+    fmt::{self, Display},
+    time::Duration,
+};
 
 use serde::{Deserialize, Serialize};
 
-use super::CommandAble;
+use super::{CommandAble, markup};
 
 /// Leading effects for displaying content on the LED panel
 ///
 /// These effects control how content appears on the panel when it is first displayed.
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Leading {
     ///  8 pixel width display block will be moved from right to left one by one
@@ -53,6 +56,30 @@ pub enum Leading {
     Xopen,
 }
 
+impl Leading {
+    /// All leading effects, e.g. to offer them for selection
+    pub const ALL: &[Leading] = &[
+        Leading::BlockMove,
+        Leading::CurtainDown,
+        Leading::CurtainUp,
+        Leading::Hold,
+        Leading::Immediate,
+        Leading::PenAmplus,
+        Leading::PenHelloWorld,
+        Leading::PenWelcome,
+        Leading::Random,
+        Leading::ScrollDown,
+        Leading::ScrollLeft,
+        Leading::ScrollRight,
+        Leading::ScrollUp,
+        Leading::Snow,
+        Leading::Twinkle,
+        Leading::Vclose,
+        Leading::Vopen,
+        Leading::Xopen,
+    ];
+}
+
 impl Display for Leading {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let character = match self {
@@ -82,7 +109,7 @@ impl Display for Leading {
 /// Lagging effects for content on the LED panel
 ///
 /// These effects control how content disappears from the panel when it is removed.
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Lagging {
     /// Image will be disappeared one line by one line from top to bottom
@@ -110,6 +137,23 @@ pub enum Lagging {
     Xopen,
 }
 
+impl Lagging {
+    /// All lagging effects, e.g. to offer them for selection
+    pub const ALL: &[Lagging] = &[
+        Lagging::CurtainDown,
+        Lagging::CurtainUp,
+        Lagging::Hold,
+        Lagging::Immediate,
+        Lagging::ScrollDown,
+        Lagging::ScrollLeft,
+        Lagging::ScrollRight,
+        Lagging::ScrollUp,
+        Lagging::Vclose,
+        Lagging::Vopen,
+        Lagging::Xopen,
+    ];
+}
+
 impl Display for Lagging {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let character = match self {
@@ -134,7 +178,7 @@ impl Display for Lagging {
 ///
 /// These settings control how content behaves while it is being displayed,
 /// including speed of transitions and special effects like blinking or playing sounds.
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum WaitingModeAndSpeed {
     /// Display Blinking while waiting (fastest speed)
@@ -183,6 +227,32 @@ pub enum WaitingModeAndSpeed {
     SlowestSong3,
 }
 
+impl WaitingModeAndSpeed {
+    /// All waiting modes and speeds, e.g. to offer them for selection
+    pub const ALL: &[WaitingModeAndSpeed] = &[
+        WaitingModeAndSpeed::FastestBlinking,
+        WaitingModeAndSpeed::FastestNormal,
+        WaitingModeAndSpeed::FastestSong1,
+        WaitingModeAndSpeed::FastestSong2,
+        WaitingModeAndSpeed::FastestSong3,
+        WaitingModeAndSpeed::MiddleFastBlinking,
+        WaitingModeAndSpeed::MiddleFastNormal,
+        WaitingModeAndSpeed::MiddleFastSong1,
+        WaitingModeAndSpeed::MiddleFastSong2,
+        WaitingModeAndSpeed::MiddleFastSong3,
+        WaitingModeAndSpeed::MiddleSlowBlinking,
+        WaitingModeAndSpeed::MiddleSlowNormal,
+        WaitingModeAndSpeed::MiddleSlowSong1,
+        WaitingModeAndSpeed::MiddleSlowSong2,
+        WaitingModeAndSpeed::MiddleSlowSong3,
+        WaitingModeAndSpeed::SlowestBlinking,
+        WaitingModeAndSpeed::SlowestNormal,
+        WaitingModeAndSpeed::SlowestSong1,
+        WaitingModeAndSpeed::SlowestSong2,
+        WaitingModeAndSpeed::SlowestSong3,
+    ];
+}
+
 impl Display for WaitingModeAndSpeed {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let character = match self {
@@ -217,15 +287,42 @@ impl Display for WaitingModeAndSpeed {
 
 /// Represents the waiting time between leading and lagging
 ///
-/// Starting at 0.5s == A to 24s == Z
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Starting at 0.5s == A, then 1s == B, 2s == C up to 25s == Z
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct WaitingTime(u8);
 
 impl WaitingTime {
+    /// Creates a new waiting time
+    ///
+    /// # Arguments
+    /// * `value` - The waiting time step (0-25 for A-Z), larger values are
+    ///   clamped to 25
+    ///
+    /// # Returns
+    /// * A new WaitingTime instance
     pub fn new(value: u8) -> Self {
         // Max to 25 == Z
         WaitingTime(value.min(25))
     }
+
+    /// Returns the waiting time step
+    ///
+    /// # Returns
+    /// * The step (0-25 for A-Z)
+    pub fn value(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns how long the page is shown between leading and lagging
+    ///
+    /// # Returns
+    /// * 0.5s for A, otherwise one second per step
+    pub fn duration(&self) -> Duration {
+        match self.0 {
+            0 => Duration::from_millis(500),
+            step => Duration::from_secs(u64::from(step)),
+        }
+    }
 }
 
 impl Display for WaitingTime {
@@ -238,7 +335,7 @@ impl Display for WaitingTime {
 ///
 /// A page contains text content and display settings that control
 /// how the content appears, behaves, and disappears on the panel.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Page {
     /// Line number (usually 1)
     line: u8,
@@ -259,6 +356,18 @@ pub struct Page {
 impl CommandAble for Page {}
 
 impl Page {
+    /// Creates a new page on line 1
+    ///
+    /// # Arguments
+    /// * `id` - The page ID (A-Z)
+    /// * `message` - The text content, including markup tags
+    /// * `leading` - The effect for how the page appears
+    /// * `lagging` - The effect for how the page disappears
+    /// * `waiting_mode_and_speed` - The behavior while the page is displayed
+    /// * `waiting_time` - The waiting time between leading and lagging
+    ///
+    /// # Returns
+    /// * A new Page instance
     pub fn new(
         id: char,
         message: String,
@@ -277,35 +386,11 @@ impl Page {
             message,
         }
     }
-
-    /// Replaces European characters with their panel-specific codes
-    ///
-    /// # Arguments
-    /// * `message` - The message text with possible European characters
-    ///
-    /// # Returns
-    /// * A new string with European characters replaced by panel codes
-    fn replace_european_character(message: &str) -> String {
-        let mut result = String::new();
-        for c in message.chars() {
-            match c {
-                'ü' => result.push_str("<U7C>"),
-                'Ü' => result.push_str("<U5C>"),
-                'ä' => result.push_str("<U64>"),
-                'Ä' => result.push_str("<U44>"),
-                'ö' => result.push_str("<U76>"),
-                'Ö' => result.push_str("<U56>"),
-                'ß' => result.push_str("<U5F>"),
-                _ => result.push(c),
-            }
-        }
-        result
-    }
 }
 
 impl Display for Page {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let message = Self::replace_european_character(&self.message);
+        let message = markup::escape(&self.message);
         write!(
             f,
             "<L{}><P{}><F{}><M{}><W{}><F{}>{}",
@@ -319,3 +404,43 @@ impl Display for Page {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::*;
+
+    /// Returns the protocol codes of `values`, sorted and deduplicated
+    fn codes<T: Display>(values: &[T]) -> Vec<String> {
+        let mut codes: Vec<String> = values.iter().map(ToString::to_string).collect();
+        codes.sort();
+        codes.dedup();
+        codes
+    }
+
+    #[test]
+    fn all_lists_every_leading_effect_once() {
+        assert_eq!(codes(Leading::ALL).len(), 18);
+        assert!(Leading::ALL.contains(&Leading::default()));
+    }
+
+    #[test]
+    fn all_lists_every_lagging_effect_once() {
+        assert_eq!(codes(Lagging::ALL).len(), 11);
+        assert!(Lagging::ALL.contains(&Lagging::default()));
+    }
+
+    #[test]
+    fn all_lists_every_waiting_mode_and_speed_once() {
+        assert_eq!(codes(WaitingModeAndSpeed::ALL).len(), 20);
+        assert!(WaitingModeAndSpeed::ALL.contains(&WaitingModeAndSpeed::default()));
+    }
+
+    #[test]
+    fn waiting_time_starts_at_half_a_second() {
+        assert_eq!(WaitingTime::new(0).duration(), Duration::from_millis(500));
+        assert_eq!(WaitingTime::new(1).duration(), Duration::from_secs(1));
+        assert_eq!(WaitingTime::new(99).duration(), Duration::from_secs(25));
+    }
+}
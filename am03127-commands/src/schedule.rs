@@ -10,38 +10,107 @@ use super::CommandAble;
 ///
 /// A schedule defines when specific pages should be displayed based on time ranges.
 /// Each schedule has an ID, a start time, an end time, and a list of page IDs to display.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Schedule {
     /// Unique identifier for the schedule (A-Z)
     pub id: char,
     /// Start time for the schedule
-    from: ScheduleDateTime,
+    pub from: ScheduleDateTime,
     /// End time for the schedule
-    to: ScheduleDateTime,
+    pub to: ScheduleDateTime,
     /// List of page IDs to display during this schedule
-    pages: String,
+    pub pages: String,
 }
 
 impl CommandAble for Schedule {}
 
+impl Schedule {
+    /// Creates a new schedule
+    ///
+    /// # Arguments
+    /// * `id` - The schedule ID (A-E)
+    /// * `from` - When the schedule starts showing its pages
+    /// * `to` - When the schedule stops showing its pages
+    /// * `pages` - The IDs of the pages to show in turn, e.g. `"ABC"` (at most 31)
+    ///
+    /// # Returns
+    /// * A new Schedule instance
+    pub fn new(id: char, from: ScheduleDateTime, to: ScheduleDateTime, pages: String) -> Self {
+        Self {
+            id,
+            from,
+            to,
+            pages,
+        }
+    }
+}
+
 impl Display for Schedule {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "<T{}>{}{}{}", self.id, self.from, self.to, self.pages)
     }
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+/// Start or end of a schedule
+///
+/// A schedule whose start and end have the date 00/00/00 recurs every day
+/// between the two times of day.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ScheduleDateTime {
-    /// Year (0-99)
-    year: u8,
-    /// Month (1-12)
-    month: u8,
-    /// Day of the month (1-31)
-    day: u8,
+    /// Year within the century (0-99 for 2000-2099, 0 for every day)
+    pub year: u8,
+    /// Month (1-12, 0 for every day)
+    pub month: u8,
+    /// Day of the month (1-31, 0 for every day)
+    pub day: u8,
     /// Hour (0-23)
-    hour: u8,
+    pub hour: u8,
     /// Minute (0-59)
-    minute: u8,
+    pub minute: u8,
+}
+
+impl ScheduleDateTime {
+    /// Creates a new schedule date and time
+    ///
+    /// # Arguments
+    /// * `year` - The year within the century (0-99 for 2000-2099)
+    /// * `month` - The month (1-12)
+    /// * `day` - The day of the month (1-31)
+    /// * `hour` - The hour (0-23)
+    /// * `minute` - The minute (0-59)
+    ///
+    /// # Returns
+    /// * A new ScheduleDateTime instance
+    pub fn new(year: u8, month: u8, day: u8, hour: u8, minute: u8) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+        }
+    }
+
+    /// Creates a time of day for a schedule recurring every day
+    ///
+    /// # Arguments
+    /// * `hour` - The hour (0-23)
+    /// * `minute` - The minute (0-59)
+    ///
+    /// # Returns
+    /// * A new ScheduleDateTime instance with the date 00/00/00
+    pub fn daily(hour: u8, minute: u8) -> Self {
+        Self::new(0, 0, 0, hour, minute)
+    }
+
+    /// Checks if this is a time of day for a schedule recurring every day
+    ///
+    /// # Returns
+    /// * `true` if the date is 00/00/00
+    /// * `false` otherwise
+    pub fn is_daily(&self) -> bool {
+        (self.year, self.month, self.day) == (0, 0, 0)
+    }
 }
 
 impl Display for ScheduleDateTime {
@@ -0,0 +1,141 @@
+extern crate alloc;
+use alloc::string::String;
+
+use wasm_bindgen::prelude::*;
+
+use super::{
+    CommandAble,
+    delete::{DeleteAll, DeletePage, DeleteSchedule},
+    is_page_id_valid, is_schedule_id_valid, markup,
+    page::{Lagging, Leading, Page, WaitingModeAndSpeed},
+    realtime_clock::DateTime,
+    schedule::Schedule,
+};
+
+/// Encodes a page into a command for the given panel
+///
+/// # Arguments
+/// * `page` - The page in the same JSON shape the REST API accepts
+/// * `panel_id` - The ID of the panel to send the command to
+///
+/// # Returns
+/// * The formatted command
+#[wasm_bindgen(js_name = encodePage)]
+pub fn encode_page(page: JsValue, panel_id: u8) -> Result<String, JsError> {
+    let page: Page = serde_wasm_bindgen::from_value(page)?;
+    if !is_page_id_valid(page.id) {
+        return Err(JsError::new("Page ID not valid"));
+    }
+    markup::validate(&page.message)?;
+    Ok(page.command(panel_id))
+}
+
+/// Encodes a schedule into a command for the given panel
+///
+/// # Arguments
+/// * `schedule` - The schedule in the same JSON shape the REST API accepts
+/// * `panel_id` - The ID of the panel to send the command to
+///
+/// # Returns
+/// * The formatted command
+#[wasm_bindgen(js_name = encodeSchedule)]
+pub fn encode_schedule(schedule: JsValue, panel_id: u8) -> Result<String, JsError> {
+    let schedule: Schedule = serde_wasm_bindgen::from_value(schedule)?;
+    if !is_schedule_id_valid(schedule.id) {
+        return Err(JsError::new("Schedule ID not valid"));
+    }
+    Ok(schedule.command(panel_id))
+}
+
+/// Encodes a clock setting into a command for the given panel
+///
+/// # Arguments
+/// * `date_time` - The date and time in the same JSON shape the REST API accepts
+/// * `panel_id` - The ID of the panel to send the command to
+///
+/// # Returns
+/// * The formatted command
+#[wasm_bindgen(js_name = encodeDateTime)]
+pub fn encode_date_time(date_time: JsValue, panel_id: u8) -> Result<String, JsError> {
+    let date_time: DateTime = serde_wasm_bindgen::from_value(date_time)?;
+    Ok(date_time.command(panel_id))
+}
+
+/// Encodes the deletion of a page into a command for the given panel
+#[wasm_bindgen(js_name = encodeDeletePage)]
+pub fn encode_delete_page(id: char, panel_id: u8) -> Result<String, JsError> {
+    if !is_page_id_valid(id) {
+        return Err(JsError::new("Page ID not valid"));
+    }
+    Ok(DeletePage::new(id).command(panel_id))
+}
+
+/// Encodes the deletion of a schedule into a command for the given panel
+#[wasm_bindgen(js_name = encodeDeleteSchedule)]
+pub fn encode_delete_schedule(id: char, panel_id: u8) -> Result<String, JsError> {
+    if !is_schedule_id_valid(id) {
+        return Err(JsError::new("Schedule ID not valid"));
+    }
+    Ok(DeleteSchedule::new(id).command(panel_id))
+}
+
+/// Encodes the deletion of all pages and schedules into a command for the given panel
+#[wasm_bindgen(js_name = encodeDeleteAll)]
+pub fn encode_delete_all(panel_id: u8) -> String {
+    DeleteAll {}.command(panel_id)
+}
+
+/// Checks if a page ID is valid (A-Z)
+#[wasm_bindgen(js_name = isPageIdValid)]
+pub fn page_id_valid(id: char) -> bool {
+    is_page_id_valid(id)
+}
+
+/// Checks if a schedule ID is valid (A-E)
+#[wasm_bindgen(js_name = isScheduleIdValid)]
+pub fn schedule_id_valid(id: char) -> bool {
+    is_schedule_id_valid(id)
+}
+
+/// Checks if a page message only contains valid tags and characters
+#[wasm_bindgen(js_name = validateMessage)]
+pub fn validate_message(message: &str) -> Result<(), JsError> {
+    Ok(markup::validate(message)?)
+}
+
+/// Parses a page message into a list of `{ type, value }` elements
+#[wasm_bindgen(js_name = parseMarkup)]
+pub fn parse_markup(message: &str) -> Result<JsValue, JsError> {
+    let tokens = markup::parse(message)?;
+    Ok(serde_wasm_bindgen::to_value(&tokens)?)
+}
+
+/// Replaces European characters with their panel-specific codes
+#[wasm_bindgen(js_name = escapeMessage)]
+pub fn escape_message(message: &str) -> String {
+    markup::escape(message)
+}
+
+/// Renders a page message as readable text
+#[wasm_bindgen(js_name = plainText)]
+pub fn plain_text(message: &str) -> String {
+    markup::plain_text(message)
+}
+
+/// Returns the names of all leading effects as used by the REST API
+#[wasm_bindgen(js_name = leadingEffects)]
+pub fn leading_effects() -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(Leading::ALL)?)
+}
+
+/// Returns the names of all lagging effects as used by the REST API
+#[wasm_bindgen(js_name = laggingEffects)]
+pub fn lagging_effects() -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(Lagging::ALL)?)
+}
+
+/// Returns the names of all waiting modes and speeds as used by the REST API
+#[wasm_bindgen(js_name = waitingModes)]
+pub fn waiting_modes() -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(WaitingModeAndSpeed::ALL)?)
+}
@@ -0,0 +1,203 @@
+use std::fmt::Display;
+
+use am03127_commands::{
+    graphic::BLOCK_DATA_SIZE,
+    page::{Lagging, Leading, Page, WaitingModeAndSpeed, WaitingTime},
+    realtime_clock::DateTime,
+    schedule::{Schedule, ScheduleDateTime},
+};
+
+/// Command carried in the payload of a frame
+#[derive(Debug, Clone)]
+pub enum Command {
+    Page(Page),
+    Schedule(Schedule),
+    Clock(DateTime),
+    DeletePage(char),
+    DeleteSchedule(char),
+    DeleteAll,
+    Graphic {
+        page: char,
+        block: u8,
+        data: Vec<u8>,
+    },
+    /// `<BX>` Brightness from A (100%) to D (25%)
+    Brightness(char),
+    /// `<RPX>` Page shown while no schedule is active
+    DefaultPage(char),
+}
+
+impl Command {
+    /// Decodes the payload of a frame, returning `None` for anything the
+    /// panel would answer with `NACK`.
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        let mut reader = Reader(payload);
+        let command = match reader.tag()? {
+            [b'L', line] if (b'1'..=b'8').contains(line) => decode_page(&mut reader)?,
+            [b'T', id] => decode_schedule(char::from(*id), &mut reader)?,
+            b"SC" => Command::Clock(DateTime {
+                year: reader.number()?,
+                week: reader.number()?,
+                month: reader.number()?,
+                day: reader.number()?,
+                hour: reader.number()?,
+                minute: reader.number()?,
+                second: reader.number()?,
+            }),
+            [b'D', b'L', _, b'P', id] if id.is_ascii_uppercase() => {
+                Command::DeletePage(char::from(*id))
+            }
+            [b'D', b'T', id] if (b'A'..=b'E').contains(id) => {
+                Command::DeleteSchedule(char::from(*id))
+            }
+            b"D*" => Command::DeleteAll,
+            [b'G', page, block]
+                if (b'A'..=b'P').contains(page) && (b'1'..=b'8').contains(block) =>
+            {
+                let data = reader.rest();
+                if data.len() != BLOCK_DATA_SIZE {
+                    return None;
+                }
+                return Some(Command::Graphic {
+                    page: char::from(*page),
+                    block: block - b'0',
+                    data: data.to_vec(),
+                });
+            }
+            [b'B', level] if (b'A'..=b'D').contains(level) => {
+                Command::Brightness(char::from(*level))
+            }
+            [b'R', b'P', id] if id.is_ascii_uppercase() => Command::DefaultPage(char::from(*id)),
+            _ => return None,
+        };
+
+        reader.rest().is_empty().then_some(command)
+    }
+}
+
+/// `<L1><PA><FA><MA><WC><FK>message`
+fn decode_page(reader: &mut Reader) -> Option<Command> {
+    let id = match reader.tag()? {
+        [b'P', id] if id.is_ascii_uppercase() => char::from(*id),
+        _ => return None,
+    };
+    let leading = reader.effect(b'F', Leading::ALL)?;
+    let waiting_mode_and_speed = reader.effect(b'M', WaitingModeAndSpeed::ALL)?;
+    let waiting_time = match reader.tag()? {
+        [b'W', time] if time.is_ascii_uppercase() => WaitingTime::new(time - b'A'),
+        _ => return None,
+    };
+    let lagging = reader.effect(b'F', Lagging::ALL)?;
+    let message = std::str::from_utf8(reader.rest()).ok()?.to_string();
+
+    Some(Command::Page(Page::new(
+        id,
+        message,
+        leading,
+        lagging,
+        waiting_mode_and_speed,
+        waiting_time,
+    )))
+}
+
+/// `<TA>` followed by the start and end as `YYMMDDhhmm` and the page IDs
+fn decode_schedule(id: char, reader: &mut Reader) -> Option<Command> {
+    if !('A'..='E').contains(&id) {
+        return None;
+    }
+    let from = reader.schedule_date_time()?;
+    let to = reader.schedule_date_time()?;
+    let pages = std::str::from_utf8(reader.rest()).ok()?;
+    if pages.is_empty() || !pages.chars().all(|page| page.is_ascii_uppercase()) {
+        return None;
+    }
+
+    Some(Command::Schedule(Schedule::new(
+        id,
+        from,
+        to,
+        pages.to_string(),
+    )))
+}
+
+/// Cursor over a payload
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    /// Reads a `<...>` tag and returns its content.
+    fn tag(&mut self) -> Option<&'a [u8]> {
+        let rest = self.0.strip_prefix(b"<")?;
+        let end = rest.iter().position(|&byte| byte == b'>')?;
+        self.0 = &rest[end + 1..];
+        Some(&rest[..end])
+    }
+
+    /// Reads a `<KX>` tag and returns the effect encoded as `X`.
+    fn effect<T: Display + Clone>(&mut self, kind: u8, all: &[T]) -> Option<T> {
+        match self.tag()? {
+            [tag, code] if *tag == kind => all
+                .iter()
+                .find(|effect| effect.to_string().as_bytes() == [*code])
+                .cloned(),
+            _ => None,
+        }
+    }
+
+    /// Reads a two digit decimal number.
+    fn number(&mut self) -> Option<u8> {
+        let digits = self.0.get(..2)?;
+        let number = std::str::from_utf8(digits).ok()?.parse().ok()?;
+        self.0 = &self.0[2..];
+        Some(number)
+    }
+
+    fn schedule_date_time(&mut self) -> Option<ScheduleDateTime> {
+        Some(ScheduleDateTime::new(
+            self.number()?,
+            self.number()?,
+            self.number()?,
+            self.number()?,
+            self.number()?,
+        ))
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use am03127_commands::{
+        Payload,
+        frame::{Decoded, Frame},
+        graphic::{BLOCK_HEIGHT, BLOCK_WIDTH, Color, GraphicBlock},
+    };
+
+    use super::Command;
+
+    #[test]
+    fn graphic_block_survives_the_wire() {
+        // Red and yellow pixels set the high bits of the data bytes
+        let pixels: Vec<Color> = (0..BLOCK_WIDTH * BLOCK_HEIGHT)
+            .map(|index| [Color::Red, Color::Yellow, Color::Green, Color::Black][index % 4])
+            .collect();
+        let block = GraphicBlock::new('C', 5, &pixels);
+        let sent = block.payload()[5..].to_vec();
+        assert!(sent.iter().any(|byte| *byte >= 0x80));
+
+        let bytes = Frame::new(0x01, &block).to_bytes();
+        let (Decoded::Frame(frame), consumed) = Frame::decode(&bytes) else {
+            panic!("frame not decoded");
+        };
+        assert_eq!(consumed, bytes.len());
+
+        let Some(Command::Graphic { page, block, data }) =
+            Command::decode(frame.payload().unwrap())
+        else {
+            panic!("graphic block not decoded");
+        };
+        assert_eq!((page, block), ('C', 5));
+        assert_eq!(data, sent);
+    }
+}
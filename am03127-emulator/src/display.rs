@@ -0,0 +1,312 @@
+use std::io::{self, Write};
+
+use am03127_commands::{
+    formatting::Clock,
+    graphic::{BLOCK_DATA_SIZE, BLOCK_HEIGHT, BLOCK_WIDTH},
+    markup::{self, Token},
+    page::Page,
+};
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor, Stylize},
+    terminal::{Clear, ClearType},
+};
+
+use crate::panel::Panel;
+
+/// Width of the 8x8 units a graphic block is built of
+const UNIT_WIDTH: usize = 8;
+/// Color of unlit LEDs
+const DARK: Color = Color::Rgb {
+    r: 40,
+    g: 40,
+    b: 40,
+};
+/// Colors cycled through by the rainbow color codes
+const RAINBOW: [Color; 3] = [Color::Red, Color::Yellow, Color::Green];
+
+/// Draws the panel state onto the terminal.
+pub fn draw(out: &mut impl Write, panel: &mut Panel, tty: &str) -> io::Result<()> {
+    queue!(out, MoveTo(0, 0), Clear(ClearType::All))?;
+
+    let now = panel.now();
+    let brightness = match panel.brightness {
+        'A' => 100,
+        'B' => 75,
+        'C' => 50,
+        _ => 25,
+    };
+    line(
+        out,
+        format!(
+            "{}  {}  ID {:02X}  brightness {brightness}%  {}",
+            "AM03127 emulator".bold(),
+            tty,
+            panel.id,
+            format_args!(
+                "{}-{:02}-{:02} {:02}:{:02}:{:02}",
+                now.year(),
+                u8::from(now.month()),
+                now.day(),
+                now.hour(),
+                now.minute(),
+                now.second()
+            ),
+        ),
+    )?;
+    line(out, "")?;
+
+    let page = panel.current_page().cloned();
+    line(out, format!("┌{}┐", "─".repeat(78)))?;
+    queue!(out, Print("│ "))?;
+    if let Some(page) = &page {
+        message(out, panel, page)?;
+    }
+    queue!(out, ResetColor, Print("\r\n"))?;
+    line(out, format!("└{}┘", "─".repeat(78)))?;
+    if let Some(page) = &page {
+        graphics(out, panel, page)?;
+        line(
+            out,
+            format!(
+                "Page {}  leading {:?}  lagging {:?}  waiting {:?} {}s",
+                page.id,
+                page.leading,
+                page.lagging,
+                page.waiting_mode_and_speed,
+                page.waiting_time.duration().as_secs_f32()
+            ),
+        )?;
+    } else {
+        line(out, "No page to show".dark_grey())?;
+    }
+    line(out, "")?;
+
+    let ids = |ids: Vec<String>| {
+        if ids.is_empty() {
+            String::from("-")
+        } else {
+            ids.join(" ")
+        }
+    };
+    line(
+        out,
+        format!(
+            "Pages      {}",
+            ids(panel.pages.keys().map(char::to_string).collect())
+        ),
+    )?;
+    line(
+        out,
+        format!(
+            "Schedules  {}",
+            ids(panel
+                .schedules
+                .values()
+                .map(|schedule| format!(
+                    "{}:{}-{}:{}",
+                    schedule.id, schedule.from, schedule.to, schedule.pages
+                ))
+                .collect())
+        ),
+    )?;
+    line(
+        out,
+        format!(
+            "Graphics   {}",
+            ids(panel
+                .graphics
+                .keys()
+                .map(|(page, block)| format!("{page}{block}"))
+                .collect())
+        ),
+    )?;
+    line(
+        out,
+        format!(
+            "Playing    {}",
+            ids(panel.playlist().iter().map(char::to_string).collect())
+        ),
+    )?;
+    line(out, "")?;
+
+    line(out, "Received".bold())?;
+    for received in &panel.history {
+        let answer = received.answer.as_deref().unwrap_or("-");
+        line(out, format!("{}  {}", answer.cyan(), received.frame))?;
+    }
+    line(out, "")?;
+    line(out, "Press q to quit".dark_grey())?;
+
+    out.flush()
+}
+
+fn line(out: &mut impl Write, text: impl std::fmt::Display) -> io::Result<()> {
+    queue!(out, Print(text), Print("\r\n"))
+}
+
+/// Prints the page message with its colors, clock values and placeholders
+/// for graphics.
+fn message(out: &mut impl Write, panel: &Panel, page: &Page) -> io::Result<()> {
+    let Ok(tokens) = markup::parse(&page.message) else {
+        return queue!(out, Print(&page.message));
+    };
+
+    let now = panel.now();
+    let mut color = 'B';
+    let mut rainbow = 0;
+    for token in tokens {
+        let text = match token {
+            Token::Text(text) => text,
+            Token::European(code) => markup::plain_text(&format!("<U{code:02X}>")),
+            Token::Clock(Clock::Date) => {
+                format!(
+                    "{:02}/{:02}/{:02}",
+                    now.day(),
+                    u8::from(now.month()),
+                    now.year() % 100
+                )
+            }
+            Token::Clock(Clock::Time) => format!("{:02}:{:02}", now.hour(), now.minute()),
+            Token::Graphic(graphic) => format!("[G{}{}]", graphic.page, graphic.block),
+            Token::Color(code) => {
+                color = code;
+                continue;
+            }
+            Token::Font(_) | Token::Bell(_) | Token::Column(_) => continue,
+        };
+
+        for character in text.chars() {
+            let (foreground, background) = match color {
+                'R' | 'S' => {
+                    rainbow += 1;
+                    (RAINBOW[rainbow % RAINBOW.len()], Color::Reset)
+                }
+                code => text_color(code),
+            };
+            queue!(
+                out,
+                SetForegroundColor(foreground),
+                SetBackgroundColor(background),
+                Print(character)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the foreground and background of a `<CX>` color code.
+fn text_color(code: char) -> (Color, Color) {
+    let orange = Color::Rgb {
+        r: 255,
+        g: 140,
+        b: 0,
+    };
+    match code {
+        'A' => (Color::DarkRed, Color::Reset),
+        'C' => (
+            Color::Rgb {
+                r: 255,
+                g: 90,
+                b: 90,
+            },
+            Color::Reset,
+        ),
+        'D' => (Color::DarkGreen, Color::Reset),
+        'E' => (Color::Green, Color::Reset),
+        'F' => (
+            Color::Rgb {
+                r: 130,
+                g: 255,
+                b: 130,
+            },
+            Color::Reset,
+        ),
+        'G' => (
+            Color::Rgb {
+                r: 150,
+                g: 80,
+                b: 0,
+            },
+            Color::Reset,
+        ),
+        'H' => (orange, Color::Reset),
+        'I' => (
+            Color::Rgb {
+                r: 255,
+                g: 190,
+                b: 80,
+            },
+            Color::Reset,
+        ),
+        'J' => (Color::Yellow, Color::Reset),
+        'K' => (
+            Color::Rgb {
+                r: 180,
+                g: 255,
+                b: 0,
+            },
+            Color::Reset,
+        ),
+        'L' => (Color::Black, Color::Red),
+        'M' => (Color::Black, Color::Green),
+        'N' => (Color::Black, orange),
+        'P' => (Color::Red, Color::DarkGreen),
+        'Q' => (Color::Green, Color::DarkRed),
+        _ => (Color::Red, Color::Reset),
+    }
+}
+
+/// Draws the graphic blocks used by the page side by side, two pixel rows
+/// per terminal line.
+fn graphics(out: &mut impl Write, panel: &Panel, page: &Page) -> io::Result<()> {
+    let Ok(tokens) = markup::parse(&page.message) else {
+        return Ok(());
+    };
+    let blocks: Vec<Option<&Vec<u8>>> = tokens
+        .iter()
+        .filter_map(|token| match token {
+            Token::Graphic(graphic) => Some(panel.graphics.get(&(graphic.page, graphic.block))),
+            _ => None,
+        })
+        .collect();
+    if blocks.is_empty() {
+        return Ok(());
+    }
+
+    for y in (0..BLOCK_HEIGHT).step_by(2) {
+        queue!(out, Print("  "))?;
+        for block in &blocks {
+            for x in 0..BLOCK_WIDTH {
+                let top = block.map_or(DARK, |data| pixel(data, x, y));
+                let bottom = block.map_or(DARK, |data| pixel(data, x, y + 1));
+                queue!(
+                    out,
+                    SetForegroundColor(top),
+                    SetBackgroundColor(bottom),
+                    Print('▀')
+                )?;
+            }
+            queue!(out, ResetColor, Print(' '))?;
+        }
+        queue!(out, ResetColor, Print("\r\n"))?;
+    }
+    Ok(())
+}
+
+/// Returns the color of a pixel of a graphic block's data.
+fn pixel(data: &[u8], x: usize, y: usize) -> Color {
+    // The block is built of four 8x8 units from left to right,
+    // each of them sent row by row.
+    let position = (x / UNIT_WIDTH) * UNIT_WIDTH * BLOCK_HEIGHT + y * UNIT_WIDTH + x % UNIT_WIDTH;
+    if position / 4 >= BLOCK_DATA_SIZE.min(data.len()) {
+        return DARK;
+    }
+    match (data[position / 4] >> (6 - 2 * (position % 4))) & 0b11 {
+        0b01 => Color::Green,
+        0b10 => Color::Red,
+        0b11 => Color::Yellow,
+        _ => DARK,
+    }
+}
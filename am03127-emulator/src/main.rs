@@ -0,0 +1,162 @@
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    os::fd::OwnedFd,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::Duration,
+};
+
+use am03127_commands::frame::{Decoded, Frame};
+use anyhow::{Context, Result};
+use clap::Parser;
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use nix::{
+    pty::openpty,
+    sys::termios::{self, SetArg},
+    unistd::ttyname,
+};
+
+mod command;
+mod display;
+mod panel;
+
+use panel::Panel;
+
+/// Received bytes after which an unfinished frame is dropped
+const MAX_FRAME_SIZE: usize = 4096;
+/// Interval in which the display is redrawn
+const REFRESH: Duration = Duration::from_millis(200);
+
+/// Emulates an AM03127 LED panel on a pseudo-terminal
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// ID of the emulated panel
+    #[arg(long, default_value_t = 1)]
+    id: u8,
+
+    /// Create a symlink to the pseudo-terminal, e.g. /tmp/am03127
+    #[arg(long)]
+    link: Option<PathBuf>,
+
+    /// Print received frames and answers instead of drawing the panel
+    #[arg(long)]
+    headless: bool,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let pty = openpty(None, None).context("failed to open a pseudo-terminal")?;
+    // Pass the bytes through unchanged, without echo or line editing
+    let mut settings = termios::tcgetattr(&pty.slave)?;
+    termios::cfmakeraw(&mut settings);
+    termios::tcsetattr(&pty.slave, SetArg::TCSANOW, &settings)?;
+    let tty = ttyname(&pty.slave)?.display().to_string();
+
+    if let Some(link) = &args.link {
+        if link.is_symlink() {
+            std::fs::remove_file(link)?;
+        }
+        std::os::unix::fs::symlink(&tty, link)
+            .with_context(|| format!("failed to create link '{}'", link.display()))?;
+    }
+
+    let panel = Arc::new(Mutex::new(Panel::new(args.id)));
+    let reader = {
+        let panel = panel.clone();
+        let headless = args.headless;
+        let master = pty.master;
+        thread::spawn(move || serve(master, &panel, headless))
+    };
+
+    let result = if args.headless {
+        eprintln!("Emulating panel {:02X} on {tty}", args.id);
+        reader.join().unwrap_or(Ok(())).map_err(Into::into)
+    } else {
+        show(&panel, &tty)
+    };
+
+    if let Some(link) = &args.link {
+        let _ = std::fs::remove_file(link);
+    }
+    // Keep the slave side open until the end, reading the master fails otherwise
+    drop(pty.slave);
+    result
+}
+
+/// Reads frames from the pseudo-terminal and answers them.
+fn serve(master: OwnedFd, panel: &Mutex<Panel>, headless: bool) -> io::Result<()> {
+    let mut master = File::from(master);
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 256];
+
+    loop {
+        let n = master.read(&mut chunk)?;
+        buffer.extend_from_slice(&chunk[..n]);
+
+        loop {
+            let (decoded, consumed) = Frame::decode(&buffer);
+            if decoded == Decoded::Incomplete {
+                buffer.drain(..consumed);
+                if buffer.len() > MAX_FRAME_SIZE {
+                    buffer.clear();
+                }
+                break;
+            }
+
+            let raw = &buffer[..consumed];
+            let answer = lock(panel).receive(&decoded, raw);
+            if let Some(answer) = &answer {
+                master.write_all(answer.as_bytes())?;
+            }
+            if headless {
+                println!(
+                    "{}  {}",
+                    answer.as_deref().unwrap_or("-"),
+                    raw.escape_ascii()
+                );
+            }
+            buffer.drain(..consumed);
+        }
+    }
+}
+
+/// Draws the panel until the user quits.
+fn show(panel: &Mutex<Panel>, tty: &str) -> Result<()> {
+    let mut out = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(out, EnterAlternateScreen, Hide)?;
+
+    let result = (|| -> Result<()> {
+        loop {
+            display::draw(&mut out, &mut lock(panel), tty)?;
+            if !event::poll(REFRESH)? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                let quit = matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+                    || (key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL));
+                if quit && key.kind == KeyEventKind::Press {
+                    return Ok(());
+                }
+            }
+        }
+    })();
+
+    execute!(out, Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn lock(panel: &Mutex<Panel>) -> MutexGuard<'_, Panel> {
+    panel.lock().unwrap_or_else(|e| e.into_inner())
+}
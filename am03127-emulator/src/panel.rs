@@ -0,0 +1,320 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Instant,
+};
+
+use am03127_commands::{
+    frame::{BROADCAST_ID, Decoded, Frame, Reply},
+    page::Page,
+    schedule::{Schedule, ScheduleDateTime},
+};
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+use crate::command::Command;
+
+/// Number of received frames kept for display
+const HISTORY: usize = 8;
+/// Page shown while no schedule is active and no default page was set
+const DEFAULT_PAGE: char = 'A';
+
+/// Frame received by the emulator and what it answered
+pub struct Received {
+    pub frame: String,
+    pub answer: Option<String>,
+}
+
+/// Page currently shown and when it started to be shown
+struct Showing {
+    id: char,
+    since: Instant,
+}
+
+/// Emulated state of an AM03127 panel
+pub struct Panel {
+    pub id: u8,
+    pub pages: BTreeMap<char, Page>,
+    pub schedules: BTreeMap<char, Schedule>,
+    pub graphics: BTreeMap<(char, u8), Vec<u8>>,
+    /// Brightness from A (100%) to D (25%)
+    pub brightness: char,
+    pub default_page: char,
+    pub history: VecDeque<Received>,
+    clock: PrimitiveDateTime,
+    clock_set: Instant,
+    showing: Option<Showing>,
+}
+
+impl Panel {
+    /// Creates an empty panel with the given ID whose clock runs on local time.
+    pub fn new(id: u8) -> Self {
+        let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+        Self {
+            id,
+            pages: BTreeMap::new(),
+            schedules: BTreeMap::new(),
+            graphics: BTreeMap::new(),
+            brightness: 'A',
+            default_page: DEFAULT_PAGE,
+            history: VecDeque::new(),
+            clock: PrimitiveDateTime::new(now.date(), now.time()),
+            clock_set: Instant::now(),
+            showing: None,
+        }
+    }
+
+    /// Returns the current time of the panel's clock.
+    pub fn now(&self) -> PrimitiveDateTime {
+        self.clock + self.clock_set.elapsed()
+    }
+
+    /// Handles a decoded frame and returns the answer to send back.
+    pub fn receive(&mut self, decoded: &Decoded, raw: &[u8]) -> Option<String> {
+        let answer = match decoded {
+            Decoded::Frame(frame) => self.handle(frame),
+            Decoded::InvalidChecksum(id) if *id == self.id => Some(String::from("NACK")),
+            Decoded::InvalidChecksum(_) | Decoded::Malformed | Decoded::Incomplete => None,
+        };
+
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(Received {
+            frame: raw.escape_ascii().to_string(),
+            answer: answer.clone(),
+        });
+        answer
+    }
+
+    fn handle(&mut self, frame: &Frame) -> Option<String> {
+        let Some(payload) = frame.payload() else {
+            // Every panel on the line takes the new ID and echoes it
+            self.id = frame.id();
+            return Some(format!("{:02X}", frame.id()));
+        };
+        if frame.id() != self.id && frame.id() != BROADCAST_ID {
+            return None;
+        }
+
+        let accepted = Command::decode(payload).is_some_and(|command| self.apply(command));
+        match frame.reply() {
+            Reply::Ack if accepted => Some(String::from("ACK")),
+            Reply::Ack => Some(String::from("NACK")),
+            Reply::None | Reply::Id(_) => None,
+        }
+    }
+
+    /// Applies a command, returning `false` if it carries invalid values.
+    fn apply(&mut self, command: Command) -> bool {
+        match command {
+            Command::Page(page) => {
+                self.pages.insert(page.id, page);
+            }
+            Command::Schedule(schedule) => {
+                self.schedules.insert(schedule.id, schedule);
+            }
+            Command::Clock(dt) => {
+                let Some(clock) = Month::try_from(dt.month)
+                    .ok()
+                    .and_then(|month| {
+                        Date::from_calendar_date(2000 + i32::from(dt.year), month, dt.day).ok()
+                    })
+                    .zip(Time::from_hms(dt.hour, dt.minute, dt.second).ok())
+                else {
+                    return false;
+                };
+                self.clock = PrimitiveDateTime::new(clock.0, clock.1);
+                self.clock_set = Instant::now();
+            }
+            Command::DeletePage(id) => {
+                self.pages.remove(&id);
+            }
+            Command::DeleteSchedule(id) => {
+                self.schedules.remove(&id);
+            }
+            Command::DeleteAll => {
+                // Like the panel, which also deletes graphics and the default page
+                self.pages.clear();
+                self.schedules.clear();
+                self.graphics.clear();
+                self.default_page = DEFAULT_PAGE;
+            }
+            Command::Graphic { page, block, data } => {
+                self.graphics.insert((page, block), data);
+            }
+            Command::Brightness(level) => self.brightness = level,
+            Command::DefaultPage(id) => self.default_page = id,
+        }
+        true
+    }
+
+    /// Returns the IDs of the pages to show at the current time.
+    ///
+    /// These are the pages of all active schedules, or the default page while
+    /// no schedule is active. Schedules dated 00/00/00 are active every day.
+    pub fn playlist(&self) -> Vec<char> {
+        let now = self.now();
+        let now = (
+            (now.year() - 2000) as u8,
+            u8::from(now.month()),
+            now.day(),
+            now.hour(),
+            now.minute(),
+        );
+
+        let mut playlist: Vec<char> = Vec::new();
+        for schedule in self.schedules.values() {
+            let active = if schedule.from.is_daily() && schedule.to.is_daily() {
+                let time = (now.3, now.4);
+                (schedule.from.hour, schedule.from.minute) <= time
+                    && time < (schedule.to.hour, schedule.to.minute)
+            } else {
+                key(&schedule.from) <= now && now < key(&schedule.to)
+            };
+            if active {
+                for page in schedule.pages.chars() {
+                    if !playlist.contains(&page) {
+                        playlist.push(page);
+                    }
+                }
+            }
+        }
+        if playlist.is_empty() {
+            playlist.push(self.default_page);
+        }
+        playlist.retain(|page| self.pages.contains_key(page));
+        playlist
+    }
+
+    /// Returns the page to show now, moving on to the next page of the
+    /// playlist once the waiting time of the current one is over.
+    pub fn current_page(&mut self) -> Option<&Page> {
+        let playlist = self.playlist();
+        let position = self
+            .showing
+            .as_ref()
+            .and_then(|showing| playlist.iter().position(|&id| id == showing.id));
+
+        let next = match (position, &self.showing) {
+            (Some(position), Some(showing)) => {
+                let page = &self.pages[&showing.id];
+                (showing.since.elapsed() >= page.waiting_time.duration())
+                    .then(|| playlist[(position + 1) % playlist.len()])
+            }
+            _ => playlist.first().copied(),
+        };
+        if let Some(id) = next {
+            self.showing = Some(Showing {
+                id,
+                since: Instant::now(),
+            });
+        }
+
+        let showing = self.showing.as_ref()?;
+        if !playlist.contains(&showing.id) {
+            return None;
+        }
+        self.pages.get(&showing.id)
+    }
+}
+
+fn key(dt: &ScheduleDateTime) -> (u8, u8, u8, u8, u8) {
+    (dt.year, dt.month, dt.day, dt.hour, dt.minute)
+}
+
+#[cfg(test)]
+mod tests {
+    use am03127_commands::{
+        Payload,
+        delete::DeleteAll,
+        frame::Frame,
+        page::{Lagging, Leading, Page, WaitingModeAndSpeed, WaitingTime},
+    };
+
+    use super::{DEFAULT_PAGE, Panel};
+
+    fn page(id: char) -> Page {
+        Page::new(
+            id,
+            String::from("Hello"),
+            Leading::Immediate,
+            Lagging::Immediate,
+            WaitingModeAndSpeed::FastestNormal,
+            WaitingTime::new(0),
+        )
+    }
+
+    /// Feeds `bytes` to the panel and returns its answer.
+    fn send(panel: &mut Panel, bytes: &[u8]) -> Option<String> {
+        let (decoded, consumed) = Frame::decode(bytes);
+        assert_eq!(consumed, bytes.len());
+        panel.receive(&decoded, bytes)
+    }
+
+    fn frame(id: u8, command: &impl Payload) -> Vec<u8> {
+        Frame::new(id, command).to_bytes()
+    }
+
+    /// Replaces the checksum in front of `<E>` by another one.
+    fn corrupt(mut bytes: Vec<u8>) -> Vec<u8> {
+        let checksum = bytes.len() - 5;
+        let wrong: &[u8] = if &bytes[checksum..checksum + 2] == b"00" {
+            b"01"
+        } else {
+            b"00"
+        };
+        bytes[checksum..checksum + 2].copy_from_slice(wrong);
+        bytes
+    }
+
+    #[test]
+    fn acknowledges_frames_for_its_id() {
+        let mut panel = Panel::new(0x01);
+
+        assert_eq!(
+            send(&mut panel, &frame(0x01, &page('B'))).as_deref(),
+            Some("ACK")
+        );
+        assert!(panel.pages.contains_key(&'B'));
+    }
+
+    #[test]
+    fn answers_bad_checksums_with_nack() {
+        let mut panel = Panel::new(0x01);
+        let bytes = corrupt(frame(0x01, &page('B')));
+
+        assert_eq!(send(&mut panel, &bytes).as_deref(), Some("NACK"));
+        assert!(panel.pages.is_empty());
+    }
+
+    #[test]
+    fn ignores_frames_for_other_ids() {
+        let mut panel = Panel::new(0x01);
+        assert_eq!(send(&mut panel, &frame(0x02, &page('B'))), None);
+        assert_eq!(send(&mut panel, &corrupt(frame(0x02, &page('C')))), None);
+        assert!(panel.pages.is_empty());
+    }
+
+    #[test]
+    fn applies_broadcasts_without_answering() {
+        let mut panel = Panel::new(0x01);
+
+        assert_eq!(send(&mut panel, &frame(0x00, &page('B'))), None);
+        assert!(panel.pages.contains_key(&'B'));
+    }
+
+    #[test]
+    fn delete_all_resets_graphics_and_default_page() {
+        let mut panel = Panel::new(0x01);
+        panel.graphics.insert(('A', 1), vec![0; 64]);
+        panel.default_page = 'C';
+        send(&mut panel, &frame(0x01, &page('B')));
+
+        assert_eq!(
+            send(&mut panel, &frame(0x01, &DeleteAll {})).as_deref(),
+            Some("ACK")
+        );
+        assert!(panel.pages.is_empty());
+        assert!(panel.graphics.is_empty());
+        assert_eq!(panel.default_page, DEFAULT_PAGE);
+    }
+}
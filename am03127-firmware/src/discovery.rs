@@ -0,0 +1,84 @@
+extern crate alloc;
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use am03127_commands::discovery::{Announcement, DISCOVERY_GROUP, DISCOVERY_PORT, PROBE};
+use embassy_net::{
+    HardwareAddress, Ipv4Address, Stack as NetworkStack,
+    udp::{PacketMetadata, UdpSocket},
+};
+
+use crate::server::HTTP_PORT;
+
+const LOGGER_NAME: &str = "Discovery";
+
+/// Returns the name the panel announces itself with.
+///
+/// This is `PANEL_NAME` if it was set at build time, otherwise a name made
+/// of the end of the MAC address.
+fn panel_name(network_stack: NetworkStack<'_>) -> String {
+    if let Some(name) = option_env!("PANEL_NAME") {
+        return name.to_string();
+    }
+    #[allow(unreachable_patterns)]
+    match network_stack.hardware_address() {
+        HardwareAddress::Ethernet(mac) => {
+            let mac = mac.as_bytes();
+            format!("am03127-{:02x}{:02x}{:02x}", mac[3], mac[4], mac[5])
+        }
+        _ => String::from("am03127"),
+    }
+}
+
+/// This task answers discovery probes sent to the discovery multicast group
+/// or as broadcast, announcing the panel's name, firmware version and HTTP port.
+///
+/// # Arguments
+/// * `network_stack` - Network stack
+#[embassy_executor::task]
+pub async fn discovery_task(network_stack: NetworkStack<'static>) {
+    log::info!("{LOGGER_NAME}: Start discovery task on port {DISCOVERY_PORT}");
+    network_stack.wait_config_up().await;
+    if let Err(e) = network_stack.join_multicast_group(Ipv4Address::from(DISCOVERY_GROUP)) {
+        log::error!("{LOGGER_NAME}: Failed to join multicast group. {:?}", e);
+    }
+
+    let announcement =
+        Announcement::new(&panel_name(network_stack), env!("BUILD_VERSION"), HTTP_PORT).to_string();
+    log::info!("{LOGGER_NAME}: Announcing {announcement}");
+
+    let mut rx_meta = [PacketMetadata::EMPTY; 4];
+    let mut rx_buffer = [0; 256];
+    let mut tx_meta = [PacketMetadata::EMPTY; 4];
+    let mut tx_buffer = [0; 256];
+    let mut socket = UdpSocket::new(
+        network_stack,
+        &mut rx_meta,
+        &mut rx_buffer,
+        &mut tx_meta,
+        &mut tx_buffer,
+    );
+    if let Err(e) = socket.bind(DISCOVERY_PORT) {
+        log::error!("{LOGGER_NAME}: Failed to bind port {DISCOVERY_PORT}. {:?}", e);
+        return;
+    }
+
+    let mut probe = [0; 32];
+    loop {
+        let (length, meta) = match socket.recv_from(&mut probe).await {
+            Ok(received) => received,
+            Err(e) => {
+                log::error!("{LOGGER_NAME}: Failed to receive probe. {:?}", e);
+                continue;
+            }
+        };
+        if &probe[..length] != PROBE {
+            continue;
+        }
+        if let Err(e) = socket.send_to(announcement.as_bytes(), meta.endpoint).await {
+            log::error!("{LOGGER_NAME}: Failed to answer {}. {:?}", meta.endpoint, e);
+        }
+    }
+}
@@ -5,12 +5,14 @@
 #[deny(clippy::mem_forget)]
 #[cfg(feature = "sntp")]
 mod clock;
+mod discovery;
 mod error;
 mod panel;
 mod server;
 mod storage;
 mod uart;
 
+use discovery::discovery_task;
 use embassy_executor::Spawner;
 use embassy_net::{Runner, Stack as NetworkStack, StackResources};
 use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
@@ -36,12 +38,12 @@ use uart::Uart;
 use crate::clock::timing_task;
 
 const WEB_TASK_POOL_SIZE: usize = 2;
-// Webtask poolsize + sntp socket + one extra
+// Webtask poolsize + sntp socket + discovery socket + one extra
 #[cfg(feature = "sntp")]
-const STACK_RESSOURCE_SIZE: usize = WEB_TASK_POOL_SIZE + 1 + 1;
-// Webtask poolsize + one extra
+const STACK_RESSOURCE_SIZE: usize = WEB_TASK_POOL_SIZE + 1 + 1 + 1;
+// Webtask poolsize + discovery socket + one extra
 #[cfg(not(feature = "sntp"))]
-const STACK_RESSOURCE_SIZE: usize = WEB_TASK_POOL_SIZE + 1;
+const STACK_RESSOURCE_SIZE: usize = WEB_TASK_POOL_SIZE + 1 + 1;
 const SSID: &str = env!("WIFI_SSID");
 const PASSWORD: &str = env!("WIFI_PASS");
 const PANEL_INIT_DELAY_SECS: u64 = 10;
@@ -124,6 +126,7 @@ async fn main(spawner: Spawner) {
     spawner.must_spawn(wifi_task(wifi_controller, network_stack));
     spawner.must_spawn(network_task(network_runner));
     spawner.must_spawn(panel_init_task(panel));
+    spawner.must_spawn(discovery_task(network_stack));
     #[cfg(feature = "sntp")]
     spawner.must_spawn(timing_task(network_stack, panel));
 
@@ -1,14 +1,18 @@
 extern crate alloc;
-use alloc::vec::Vec;
+use alloc::{collections::BTreeSet, format, vec::Vec};
 
 use am03127_commands::{
     CommandAble,
     delete::{DeleteAll, DeletePage, DeleteSchedule},
+    frame::Frame,
+    graphic::GraphicBlock,
+    memory::{MemoryBudget, MemoryStatus},
     page::Page,
     realtime_clock::DateTime,
     schedule::Schedule,
     set_id,
 };
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
 
 use crate::{
     SharedStorage, SharedUart,
@@ -47,6 +51,9 @@ pub struct Panel {
     page_storage: NvsStorageSection<PageWrapper, { PAGE_ENTRY_SIZE }>,
     /// Storage for schedules
     schedule_storage: NvsStorageSection<ScheduleWrapper, { SCHEDULE_ENTRY_SIZE }>,
+    /// Graphic page and block number of every graphic block sent since boot,
+    /// as the panel can not be asked which blocks it holds
+    graphic_blocks: Mutex<CriticalSectionRawMutex, BTreeSet<(char, u8)>>,
 }
 
 impl Panel {
@@ -73,6 +80,7 @@ impl Panel {
             uart,
             page_storage,
             schedule_storage,
+            graphic_blocks: Mutex::new(BTreeSet::new()),
         }
     }
 
@@ -172,6 +180,12 @@ impl Panel {
         log::info!("{LOGGER_NAME}: Setting page \"{page_id}\"");
         log::debug!("{LOGGER_NAME}: {:?}", page);
 
+        let mut pages = self.get_pages().await?;
+        pages.retain(|stored| stored.id != page_id);
+        pages.push(page.clone());
+        let graphic_blocks = self.graphic_blocks.lock().await.len();
+        self.check_memory(&pages, &self.get_schedules().await?, graphic_blocks)?;
+
         let command = page.command(DEFAULT_PANEL_ID);
 
         self.uart.lock().await.write(&command).await?;
@@ -180,6 +194,41 @@ impl Panel {
         Ok(())
     }
 
+    /// Sends a graphic block to the panel
+    ///
+    /// Graphic blocks are not persisted and have to be sent again after the
+    /// panel lost its memory. Blocks sent since boot count towards the panel
+    /// memory, replacing a block does not use more.
+    ///
+    /// # Arguments
+    /// * `block` - The graphic block to send
+    ///
+    /// # Returns
+    /// * `Ok(())` if the graphic block was sent successfully
+    /// * `Err(Error)` if sending the graphic block failed
+    pub async fn set_graphic(&self, block: &GraphicBlock) -> Result<(), Error> {
+        log::info!(
+            "{LOGGER_NAME}: Setting graphic block \"{}{}\"",
+            block.page,
+            block.block
+        );
+
+        let mut graphic_blocks = self.graphic_blocks.lock().await;
+        let key = (block.page, block.block);
+        let count = graphic_blocks.len() + usize::from(!graphic_blocks.contains(&key));
+        self.check_memory(
+            &self.get_pages().await?,
+            &self.get_schedules().await?,
+            count,
+        )?;
+
+        let frame = Frame::new(DEFAULT_PANEL_ID, block);
+        self.uart.lock().await.write_bytes(&frame.to_bytes()).await?;
+        graphic_blocks.insert(key);
+
+        Ok(())
+    }
+
     /// Retrieves a page from storage
     ///
     /// # Arguments
@@ -249,6 +298,12 @@ impl Panel {
         log::info!("{LOGGER_NAME}: Setting schedule \"{schedule_id}\"");
         log::debug!("{LOGGER_NAME}: {:?}", schedule);
 
+        let mut schedules = self.get_schedules().await?;
+        schedules.retain(|stored| stored.id != schedule_id);
+        schedules.push(schedule.clone());
+        let graphic_blocks = self.graphic_blocks.lock().await.len();
+        self.check_memory(&self.get_pages().await?, &schedules, graphic_blocks)?;
+
         let command = schedule.command(DEFAULT_PANEL_ID);
         self.uart.lock().await.write(&command).await?;
         self.schedule_storage
@@ -311,10 +366,10 @@ impl Panel {
         Ok(())
     }
 
-    /// Deletes all pages and schedules from the panel and storage
+    /// Deletes all pages, schedules and graphics from the panel and storage
     ///
-    /// Sends a delete all command to the LED panel and erases all
-    /// pages and schedules from flash storage.
+    /// Sends a delete all command to the LED panel, which also deletes its
+    /// graphic blocks, and erases all pages and schedules from flash storage.
     ///
     /// # Returns
     /// * `Ok(())` if all data was deleted successfully
@@ -326,6 +381,36 @@ impl Panel {
         self.uart.lock().await.write(&command).await?;
         self.page_storage.delete_all().await?;
         self.schedule_storage.delete_all().await?;
+        self.graphic_blocks.lock().await.clear();
         Ok(())
     }
+
+    /// Checks whether the given content fits into the panel memory
+    ///
+    /// # Arguments
+    /// * `pages` - All pages the panel would hold
+    /// * `schedules` - All schedules the panel would hold
+    /// * `graphic_blocks` - The number of graphic blocks the panel would hold
+    ///
+    /// # Returns
+    /// * `Ok(())` if the content fits into the panel memory
+    /// * `Err(Error)` if the content would overflow the panel memory
+    fn check_memory(
+        &self,
+        pages: &[Page],
+        schedules: &[Schedule],
+        graphic_blocks: usize,
+    ) -> Result<(), Error> {
+        let estimate = MemoryBudget::default().estimate(pages, schedules, graphic_blocks);
+        match estimate.status() {
+            MemoryStatus::Ok => Ok(()),
+            MemoryStatus::Warning => {
+                log::warn!("{LOGGER_NAME}: Panel memory almost full, using {estimate}");
+                Ok(())
+            }
+            MemoryStatus::Overflow => Err(Error::BadRequest(format!(
+                "Panel memory would overflow, using {estimate}"
+            ))),
+        }
+    }
 }
@@ -11,6 +11,9 @@ use picoserve::{
 
 use crate::{SharedStorage, WEB_TASK_POOL_SIZE, error::Error, panel::Panel};
 
+/// TCP port of the HTTP server
+pub const HTTP_PORT: u16 = 80;
+
 /// Shared reference to the Panel instance
 // pub type SharedPanel = &'static Mutex<CriticalSectionRawMutex, Panel>;
 
@@ -61,6 +64,7 @@ impl AppWithStateBuilder for ServerProperties {
                 routers::schedule_router(),
             )
             .route("/schedules", routers::schedules_router())
+            .route("/graphic", routers::graphic_router())
             .route("/clock", routers::clock_router())
             .route("/reset", routers::delete_all_router())
             .route("/ota", routers::ota_router())
@@ -133,7 +137,6 @@ pub async fn web_task(
     state: AppState,
 ) -> ! {
     log::info!("Server: Starting webserver listener {id}");
-    let port = 80;
     let mut tcp_rx_buffer = [0; 1024];
     let mut tcp_tx_buffer = [0; 1024];
     let mut http_buffer = [0; 2048];
@@ -143,7 +146,7 @@ pub async fn web_task(
         config,
         &mut http_buffer,
     )
-    .listen_and_serve(id, stack, port, &mut tcp_rx_buffer, &mut tcp_tx_buffer)
+    .listen_and_serve(id, stack, HTTP_PORT, &mut tcp_rx_buffer, &mut tcp_tx_buffer)
     .await
     .into_never()
 }
@@ -1,7 +1,10 @@
 extern crate alloc;
 use alloc::vec::Vec;
 
-use am03127_commands::{page::Page, realtime_clock::DateTime, schedule::Schedule};
+use am03127_commands::{
+    graphic::GraphicBlock, is_page_id_valid, is_schedule_id_valid, page::Page,
+    realtime_clock::DateTime, schedule::Schedule,
+};
 #[cfg(feature = "web_interface")]
 use picoserve::routing::{MethodHandler, get_service};
 use picoserve::{
@@ -65,6 +68,31 @@ pub fn clock_router() -> impl MethodHandler<AppState> {
     )
 }
 
+/// Creates a router for the graphic block endpoint
+///
+/// # Returns
+/// * A router that sends graphic blocks to the panel if they fit into its memory
+pub fn graphic_router() -> impl MethodHandler<AppState> {
+    post(
+        |State(panel): State<&'static Panel>, Json::<GraphicBlock>(block)| async move {
+            log::info!("{LOGGER_NAME}: Set graphic block");
+            if !block.is_valid() {
+                return Err(Error::BadRequest(
+                    "Graphic block not valid".try_into().unwrap(),
+                ));
+            }
+
+            match panel.set_graphic(&block).await {
+                Ok(_) => Ok(()),
+                Err(err) => {
+                    log::error!("{LOGGER_NAME}: {err}");
+                    Err(err)
+                }
+            }
+        },
+    )
+}
+
 /// Creates a router for page-related endpoints
 ///
 /// # Returns
@@ -255,27 +283,3 @@ pub fn delete_all_router() -> impl MethodHandler<AppState> {
 pub fn ota_router() -> impl MethodHandler<AppState> {
     put_service(OverTheAirUpdate)
 }
-
-/// Checks if an ID is valid (A-Z)
-///
-/// # Arguments
-/// * `id` - The ID to check
-///
-/// # Returns
-/// * `true` if the ID is valid (A-Z)
-/// * `false` otherwise
-fn is_page_id_valid(id: char) -> bool {
-    id >= 'A' && id <= 'Z'
-}
-
-/// Checks if an ID is valid page(A-Z)
-///
-/// # Arguments
-/// * `id` - The ID to check
-///
-/// # Returns
-/// * `true` if the ID is valid (A-Z)
-/// * `false` otherwise
-fn is_schedule_id_valid(id: char) -> bool {
-    id >= 'A' && id <= 'E'
-}
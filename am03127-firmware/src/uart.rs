@@ -69,9 +69,20 @@ impl<'a> Uart<'a> {
     /// * `Err(Error)` if the write failed or the panel rejected the command
     pub async fn write(&mut self, data: &str) -> Result<(), Error> {
         log::debug!("{LOGGER_NAME}: Sending {data}");
+        self.write_bytes(data.as_bytes()).await
+    }
 
+    /// Writes raw data to the LED panel and processes the response
+    ///
+    /// # Arguments
+    /// * `data` - The bytes to write to the LED panel
+    ///
+    /// # Returns
+    /// * `Ok(())` if the write was successful and the panel acknowledged it
+    /// * `Err(Error)` if the write failed or the panel rejected the command
+    pub async fn write_bytes(&mut self, data: &[u8]) -> Result<(), Error> {
         let timeout = Duration::from_secs(UART_TIMEOUT_SECS);
-        with_timeout(timeout, self.uart.write_all(data.as_bytes()))
+        with_timeout(timeout, self.uart.write_all(data))
             .await
             .map_err(|_| Error::Uart("Write timeout".try_into().unwrap()))??;
 
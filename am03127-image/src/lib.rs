@@ -0,0 +1,336 @@
+use std::path::Path;
+
+use am03127_commands::{
+    formatting::Graphic,
+    graphic::{
+        BLOCK_HEIGHT, BLOCK_WIDTH, BLOCKS_PER_PAGE, Color, FIRST_GRAPHIC_PAGE, GraphicBlock,
+        LAST_GRAPHIC_PAGE,
+    },
+};
+use anyhow::{Context, Result, bail};
+use image::{DynamicImage, GenericImageView, imageops::FilterType};
+
+/// Bayer matrix used for ordered dithering
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// How the image is reduced to the panel's red and green LEDs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Dithering {
+    /// Every channel is compared against the threshold
+    #[default]
+    Threshold,
+    /// Quantization errors are diffused to neighbouring pixels
+    FloydSteinberg,
+    /// The threshold varies with a 4x4 Bayer pattern
+    Ordered,
+}
+
+/// Settings for converting an image into graphic blocks
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Target width in pixels, derived from the aspect ratio if not set
+    pub width: Option<u32>,
+    /// Target height in pixels, rounded up to full 8 pixel lines
+    pub height: u32,
+    /// How colors are reduced to the panel palette
+    pub dithering: Dithering,
+    /// Channel value (0-255) from which a LED is turned on
+    pub threshold: u8,
+    /// Graphic page (A-P) the first block is stored in
+    pub first_page: char,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            width: None,
+            height: BLOCK_HEIGHT as u32,
+            dithering: Dithering::default(),
+            threshold: 128,
+            first_page: FIRST_GRAPHIC_PAGE,
+        }
+    }
+}
+
+/// An image converted into graphic blocks
+#[derive(Debug, Clone)]
+pub struct ConvertedImage {
+    /// Graphic blocks to send to the panel
+    pub blocks: Vec<GraphicBlock>,
+    /// Message references reassembling the image, one per 8 pixel line
+    pub lines: Vec<String>,
+}
+
+/// Reads an image file (PNG, BMP or GIF) and converts it into graphic blocks.
+pub fn open(path: &Path, options: &Options) -> Result<ConvertedImage> {
+    let image =
+        image::open(path).with_context(|| format!("failed to read '{}'", path.display()))?;
+    convert(&image, options)
+}
+
+/// Converts an image into graphic blocks.
+///
+/// The image is scaled to fit the target size, reduced to the panel's four
+/// colors and tiled into 32x8 blocks. Blocks are stored line by line, starting
+/// at block 1 of `options.first_page` and continuing on the following pages.
+pub fn convert(image: &DynamicImage, options: &Options) -> Result<ConvertedImage> {
+    if !(FIRST_GRAPHIC_PAGE..=LAST_GRAPHIC_PAGE).contains(&options.first_page) {
+        bail!(
+            "graphic page must be between {FIRST_GRAPHIC_PAGE} and {LAST_GRAPHIC_PAGE}, got '{}'",
+            options.first_page
+        );
+    }
+    if options.height == 0 || options.width == Some(0) {
+        bail!("target size must not be empty");
+    }
+    let (source_width, source_height) = image.dimensions();
+    if source_width == 0 || source_height == 0 {
+        bail!("image is empty");
+    }
+
+    let width = options.width.unwrap_or_else(|| {
+        (source_width as u64 * options.height as u64)
+            .div_ceil(source_height as u64)
+            .max(1) as u32
+    });
+    let scaled = image.resize(width, options.height, FilterType::Triangle);
+
+    let columns = (scaled.width() as usize).div_ceil(BLOCK_WIDTH);
+    let rows = (options.height as usize).div_ceil(BLOCK_HEIGHT);
+    let available =
+        (LAST_GRAPHIC_PAGE as usize - options.first_page as usize + 1) * BLOCKS_PER_PAGE as usize;
+    if columns * rows > available {
+        bail!(
+            "image needs {} graphic blocks but only {available} are available from page '{}'",
+            columns * rows,
+            options.first_page
+        );
+    }
+
+    let canvas_width = columns * BLOCK_WIDTH;
+    let canvas_height = rows * BLOCK_HEIGHT;
+    let top = (canvas_height - scaled.height() as usize) / 2;
+    let mut red = vec![0f32; canvas_width * canvas_height];
+    let mut green = vec![0f32; canvas_width * canvas_height];
+    for (x, y, pixel) in scaled.to_rgba8().enumerate_pixels() {
+        let [r, g, _, a] = pixel.0;
+        let index = (y as usize + top) * canvas_width + x as usize;
+        red[index] = r as f32 * a as f32 / 255.0;
+        green[index] = g as f32 * a as f32 / 255.0;
+    }
+
+    let red = quantize(&mut red, canvas_width, options);
+    let green = quantize(&mut green, canvas_width, options);
+
+    let mut blocks = Vec::with_capacity(columns * rows);
+    let mut lines = Vec::with_capacity(rows);
+    for row in 0..rows {
+        let mut line = String::new();
+        for column in 0..columns {
+            let index = blocks.len();
+            let page =
+                (options.first_page as u8 + (index / BLOCKS_PER_PAGE as usize) as u8) as char;
+            let block = (index % BLOCKS_PER_PAGE as usize) as u8 + 1;
+
+            let mut pixels = Vec::with_capacity(BLOCK_WIDTH * BLOCK_HEIGHT);
+            for y in row * BLOCK_HEIGHT..(row + 1) * BLOCK_HEIGHT {
+                for x in column * BLOCK_WIDTH..(column + 1) * BLOCK_WIDTH {
+                    let index = y * canvas_width + x;
+                    pixels.push(Color::from_leds(red[index], green[index]));
+                }
+            }
+
+            blocks.push(GraphicBlock::new(page, block, &pixels));
+            line.push_str(&Graphic { page, block }.to_string());
+        }
+        lines.push(line);
+    }
+
+    Ok(ConvertedImage { blocks, lines })
+}
+
+/// Reduces a single color channel to LED states.
+fn quantize(channel: &mut [f32], width: usize, options: &Options) -> Vec<bool> {
+    let threshold = options.threshold as f32;
+    let height = channel.len() / width;
+    let mut leds = vec![false; channel.len()];
+
+    for y in 0..height {
+        for x in 0..width {
+            let index = y * width + x;
+            let value = channel[index];
+            let on = match options.dithering {
+                Dithering::Threshold | Dithering::FloydSteinberg => value >= threshold,
+                Dithering::Ordered => {
+                    let offset = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5;
+                    value >= threshold + offset * 255.0
+                }
+            };
+            leds[index] = on;
+
+            if options.dithering == Dithering::FloydSteinberg {
+                let error = value - if on { 255.0 } else { 0.0 };
+                let mut diffuse = |dx: isize, dy: usize, weight: f32| {
+                    let nx = x as isize + dx;
+                    let ny = y + dy;
+                    if nx >= 0 && (nx as usize) < width && ny < height {
+                        channel[ny * width + nx as usize] += error * weight;
+                    }
+                };
+                diffuse(1, 0, 7.0 / 16.0);
+                diffuse(-1, 1, 3.0 / 16.0);
+                diffuse(0, 1, 5.0 / 16.0);
+                diffuse(1, 1, 1.0 / 16.0);
+            }
+        }
+    }
+
+    leds
+}
+
+#[cfg(test)]
+mod tests {
+    use am03127_commands::Payload;
+    use image::{Rgba, RgbaImage};
+
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const GREEN: Rgba<u8> = Rgba([0, 255, 0, 255]);
+    const YELLOW: Rgba<u8> = Rgba([255, 255, 0, 255]);
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+    /// Returns the pixel data of a block without the `<Gpb>` tag.
+    fn data(block: &GraphicBlock) -> Vec<u8> {
+        block.payload()[5..].to_vec()
+    }
+
+    /// Returns the number of lit pixels of a block.
+    fn lit(block: &GraphicBlock) -> usize {
+        data(block)
+            .iter()
+            .map(|byte| (0..4).filter(|i| byte >> (2 * i) & 0b11 != 0).count())
+            .sum()
+    }
+
+    fn uniform(width: u32, height: u32, value: u8) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            width,
+            height,
+            Rgba([value, value, value, 255]),
+        ))
+    }
+
+    #[test]
+    fn packs_pixels_msb_first_in_8x8_units() {
+        let mut image = RgbaImage::from_pixel(32, 8, BLACK);
+        image.put_pixel(0, 0, RED);
+        image.put_pixel(1, 0, GREEN);
+        image.put_pixel(2, 0, YELLOW);
+        image.put_pixel(0, 1, RED);
+        image.put_pixel(8, 0, GREEN);
+        image.put_pixel(31, 7, YELLOW);
+
+        let converted = convert(&DynamicImage::ImageRgba8(image), &Options::default()).unwrap();
+
+        assert_eq!(converted.blocks.len(), 1);
+        let data = data(&converted.blocks[0]);
+        let mut expected = vec![0u8; 64];
+        // Red, green, yellow, black in the first four pixels of the first unit
+        expected[0] = 0b10_01_11_00;
+        // Second row of the first unit
+        expected[2] = 0b10_00_00_00;
+        // First pixel of the second unit
+        expected[16] = 0b01_00_00_00;
+        // Last pixel of the last unit
+        expected[63] = 0b00_00_00_11;
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn numbers_blocks_line_by_line_across_pages() {
+        let options = Options {
+            height: 16,
+            first_page: 'B',
+            ..Options::default()
+        };
+
+        let converted = convert(&uniform(160, 16, 255), &options).unwrap();
+
+        let blocks: Vec<(char, u8)> = converted
+            .blocks
+            .iter()
+            .map(|block| (block.page, block.block))
+            .collect();
+        assert_eq!(
+            blocks,
+            [
+                ('B', 1),
+                ('B', 2),
+                ('B', 3),
+                ('B', 4),
+                ('B', 5),
+                ('B', 6),
+                ('B', 7),
+                ('B', 8),
+                ('C', 1),
+                ('C', 2),
+            ]
+        );
+        assert_eq!(
+            converted.lines,
+            ["<GB1><GB2><GB3><GB4><GB5>", "<GB6><GB7><GB8><GC1><GC2>"]
+        );
+    }
+
+    #[test]
+    fn dithering_spreads_mid_tones() {
+        let convert_with = |dithering| {
+            let options = Options {
+                dithering,
+                ..Options::default()
+            };
+            lit(&convert(&uniform(32, 8, 64), &options).unwrap().blocks[0])
+        };
+
+        // A quarter of the full brightness stays below the threshold
+        assert_eq!(convert_with(Dithering::Threshold), 0);
+        // The dithered images light about a quarter of the 256 pixels
+        let floyd_steinberg = convert_with(Dithering::FloydSteinberg);
+        assert!((48..=80).contains(&floyd_steinberg), "{floyd_steinberg}");
+        let ordered = convert_with(Dithering::Ordered);
+        assert!((48..=80).contains(&ordered), "{ordered}");
+    }
+
+    #[test]
+    fn threshold_turns_on_bright_channels() {
+        let options = Options {
+            threshold: 200,
+            ..Options::default()
+        };
+
+        assert_eq!(
+            lit(&convert(&uniform(32, 8, 199), &options).unwrap().blocks[0]),
+            0
+        );
+        assert_eq!(
+            lit(&convert(&uniform(32, 8, 200), &options).unwrap().blocks[0]),
+            256
+        );
+    }
+
+    #[test]
+    fn refuses_images_needing_too_many_blocks() {
+        let options = Options {
+            first_page: 'P',
+            ..Options::default()
+        };
+
+        assert!(convert(&uniform(256, 8, 255), &options).is_ok());
+        let error = convert(&uniform(288, 8, 255), &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "image needs 9 graphic blocks but only 8 are available from page 'P'"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr},
+};
+
+use am03127_commands::discovery::{DISCOVERY_GROUP, PROBE};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::UdpSocket;
+
+/// Binds the socket probes are received on.
+///
+/// Several mock servers can share the port, as they join the discovery group
+/// on the default interface and the loopback interface.
+pub fn bind(port: u16) -> io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)).into())?;
+
+    let group = Ipv4Addr::from(DISCOVERY_GROUP);
+    socket.join_multicast_v4(&group, &Ipv4Addr::LOCALHOST)?;
+    // Hosts without a default route only have the loopback interface
+    let _ = socket.join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED);
+
+    UdpSocket::from_std(socket.into())
+}
+
+/// Answers every probe with `announcement`, like the controller does.
+///
+/// Answers are sent from `reply` if given, so they come from the address the
+/// HTTP server listens on rather than from whichever interface the probe
+/// arrived at.
+pub async fn answer(socket: UdpSocket, reply: Option<UdpSocket>, announcement: String) {
+    let mut probe = [0; 32];
+    while let Ok((length, from)) = socket.recv_from(&mut probe).await {
+        if &probe[..length] == PROBE {
+            let _ = reply
+                .as_ref()
+                .unwrap_or(&socket)
+                .send_to(announcement.as_bytes(), from)
+                .await;
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use axum::http::Method;
+
+/// What an injected failure does to a matching request
+#[derive(Debug, Clone)]
+pub(crate) enum Action {
+    /// Answer with the status and plain text body instead of handling it
+    Respond { status: u16, message: String },
+    /// Wait before handling the request
+    Delay(Duration),
+}
+
+/// Failure injected into a [`MockServer`](crate::MockServer).
+///
+/// Matches every request whose path starts with the given prefix, so
+/// `"/page/"` hits all pages and `"/"` hits every route. Restrict it to a
+/// method with [`Failure::method`] and to a number of requests with
+/// [`Failure::times`].
+#[derive(Debug, Clone)]
+pub struct Failure {
+    path: String,
+    method: Option<Method>,
+    remaining: Option<u32>,
+    pub(crate) action: Action,
+}
+
+impl Failure {
+    /// Answers matching requests with `status` and the plain text `message`,
+    /// like the firmware does for invalid requests and storage or UART errors.
+    pub fn status(path: &str, status: u16, message: &str) -> Self {
+        Self::new(
+            path,
+            Action::Respond {
+                status,
+                message: message.to_string(),
+            },
+        )
+    }
+
+    /// Delays matching requests, e.g. to run into the client's timeout.
+    pub fn delay(path: &str, delay: Duration) -> Self {
+        Self::new(path, Action::Delay(delay))
+    }
+
+    /// Only matches requests with the given HTTP method.
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Only hits the next `times` matching requests.
+    pub fn times(mut self, times: u32) -> Self {
+        self.remaining = Some(times);
+        self
+    }
+
+    fn new(path: &str, action: Action) -> Self {
+        Self {
+            path: path.to_string(),
+            method: None,
+            remaining: None,
+            action,
+        }
+    }
+
+    pub(crate) fn matches(&self, method: &Method, path: &str) -> bool {
+        path.starts_with(&self.path)
+            && self
+                .method
+                .as_ref()
+                .is_none_or(|expected| expected == method)
+            && self.remaining != Some(0)
+    }
+
+    /// Counts a hit and returns `false` once the failure is used up.
+    pub(crate) fn hit(&mut self) -> bool {
+        if let Some(remaining) = &mut self.remaining {
+            *remaining = remaining.saturating_sub(1);
+            return *remaining > 0;
+        }
+        true
+    }
+}
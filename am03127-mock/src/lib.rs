@@ -0,0 +1,235 @@
+//! In-process stand-in for the AM03127 controller.
+//!
+//! [`MockServer`] serves the same HTTP routes as the firmware on a local
+//! port, keeps pages and schedules in memory and records the raw frames the
+//! firmware would write to the panel. Point a
+//! [`PanelClient`](am03127_client::PanelClient) or the CLI at
+//! [`MockServer::url`] to test them without hardware. With
+//! [`MockServerBuilder::announce`] it also answers discovery probes.
+
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use am03127_client::MockPanel;
+use am03127_commands::discovery::{Announcement, DISCOVERY_PORT};
+use tokio::{
+    net::{TcpListener, UdpSocket},
+    task::JoinHandle,
+};
+
+mod discovery;
+mod failure;
+mod routes;
+
+pub use failure::Failure;
+
+/// Callback invoked with every frame the server records
+type FrameHook = Box<dyn Fn(&[u8]) + Send + Sync>;
+
+/// State shared between the server task and its handle
+struct Shared {
+    panel: MockPanel,
+    panel_id: u8,
+    frames: Mutex<Vec<Vec<u8>>>,
+    requests: Mutex<Vec<String>>,
+    failures: Mutex<Vec<Failure>>,
+    on_frame: Option<FrameHook>,
+}
+
+impl Shared {
+    fn record_frame(&self, frame: Vec<u8>) {
+        if let Some(hook) = &self.on_frame {
+            hook(&frame);
+        }
+        lock(&self.frames).push(frame);
+    }
+}
+
+/// Local HTTP server answering like the ESP32 controller.
+///
+/// The server runs on the current tokio runtime until the handle is dropped.
+pub struct MockServer {
+    address: SocketAddr,
+    discovery_port: Option<u16>,
+    shared: Arc<Shared>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Starts a server with an empty panel on a free port of the loopback
+    /// interface.
+    pub async fn start() -> io::Result<Self> {
+        Self::builder().start().await
+    }
+
+    pub fn builder() -> MockServerBuilder {
+        MockServerBuilder::default()
+    }
+
+    /// Returns the address the server listens on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns the base URL of the server, e.g. `http://127.0.0.1:41234`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Returns the UDP port discovery probes are answered on, if the server
+    /// announces itself.
+    pub fn discovery_port(&self) -> Option<u16> {
+        self.discovery_port
+    }
+
+    /// Returns the panel holding the server's pages, schedules and clock.
+    ///
+    /// Changes made through it are visible to the HTTP clients and vice versa,
+    /// but do not record any frames.
+    pub fn panel(&self) -> &MockPanel {
+        &self.shared.panel
+    }
+
+    /// Returns the raw frames written to the panel so far, oldest first.
+    pub fn frames(&self) -> Vec<Vec<u8>> {
+        lock(&self.shared.frames).clone()
+    }
+
+    /// Returns and forgets the frames written to the panel so far.
+    pub fn take_frames(&self) -> Vec<Vec<u8>> {
+        std::mem::take(&mut *lock(&self.shared.frames))
+    }
+
+    /// Returns all requests received so far as `"METHOD /path"`, oldest
+    /// first, including the ones answered by an injected failure.
+    pub fn requests(&self) -> Vec<String> {
+        lock(&self.shared.requests).clone()
+    }
+
+    /// Injects a failure. Failures are checked in the order they were added
+    /// and the first matching one is applied.
+    pub fn fail(&self, failure: Failure) {
+        lock(&self.shared.failures).push(failure);
+    }
+
+    /// Removes all injected failures.
+    pub fn clear_failures(&self) {
+        lock(&self.shared.failures).clear();
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Builder for a [`MockServer`] on a fixed address or with prepared content.
+pub struct MockServerBuilder {
+    address: SocketAddr,
+    panel: MockPanel,
+    panel_id: u8,
+    on_frame: Option<FrameHook>,
+    name: Option<String>,
+    discovery_port: u16,
+}
+
+impl Default for MockServerBuilder {
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            panel: MockPanel::new(),
+            panel_id: 1,
+            on_frame: None,
+            name: None,
+            discovery_port: DISCOVERY_PORT,
+        }
+    }
+}
+
+impl MockServerBuilder {
+    /// Sets the address to listen on. Port 0 picks a free port.
+    pub fn address(mut self, address: SocketAddr) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// Sets the panel to serve, e.g. one already holding pages.
+    pub fn panel(mut self, panel: MockPanel) -> Self {
+        self.panel = panel;
+        self
+    }
+
+    /// Sets the panel ID the recorded frames are addressed to. The firmware
+    /// always uses 1.
+    pub fn panel_id(mut self, panel_id: u8) -> Self {
+        self.panel_id = panel_id;
+        self
+    }
+
+    /// Calls `hook` with every frame as soon as it is recorded.
+    pub fn on_frame(mut self, hook: impl Fn(&[u8]) + Send + Sync + 'static) -> Self {
+        self.on_frame = Some(Box::new(hook));
+        self
+    }
+
+    /// Answers discovery probes with `name`, like the controller does.
+    pub fn announce(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Sets the UDP port discovery probes are answered on. Port 0 picks a
+    /// free port.
+    pub fn discovery_port(mut self, port: u16) -> Self {
+        self.discovery_port = port;
+        self
+    }
+
+    pub async fn start(self) -> io::Result<MockServer> {
+        let listener = TcpListener::bind(self.address).await?;
+        let address = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            panel: self.panel,
+            panel_id: self.panel_id,
+            frames: Mutex::default(),
+            requests: Mutex::default(),
+            failures: Mutex::default(),
+            on_frame: self.on_frame,
+        });
+
+        let app = routes::build_app(shared.clone());
+        let mut tasks = vec![tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        })];
+
+        let mut discovery_port = None;
+        if let Some(name) = &self.name {
+            let socket = discovery::bind(self.discovery_port)?;
+            discovery_port = Some(socket.local_addr()?.port());
+            let reply = if address.ip().is_unspecified() {
+                None
+            } else {
+                Some(UdpSocket::bind((address.ip(), 0)).await?)
+            };
+            let announcement = Announcement::new(name, "mock", address.port()).to_string();
+            tasks.push(tokio::spawn(discovery::answer(socket, reply, announcement)));
+        }
+
+        Ok(MockServer {
+            address,
+            discovery_port,
+            shared,
+            tasks,
+        })
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
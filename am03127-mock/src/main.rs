@@ -0,0 +1,48 @@
+use std::net::SocketAddr;
+
+use am03127_mock::MockServer;
+use anyhow::{Context, Result};
+use clap::Parser;
+
+/// Serves the HTTP API of the AM03127 controller without hardware and prints
+/// the frames it would send to the panel.
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// Address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// Panel ID the frames are addressed to
+    #[arg(long, default_value_t = 1)]
+    panel_id: u8,
+
+    /// Answer discovery probes with this panel name
+    #[arg(long)]
+    announce: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let mut builder = MockServer::builder()
+        .address(args.listen)
+        .panel_id(args.panel_id)
+        .on_frame(|frame| println!("{}", frame.escape_ascii()));
+    if let Some(name) = &args.announce {
+        builder = builder.announce(name);
+    }
+    let server = builder
+        .start()
+        .await
+        .with_context(|| format!("failed to listen on {}", args.listen))?;
+
+    eprintln!("Mock panel listening on {}", server.url());
+    if let Some(port) = server.discovery_port() {
+        eprintln!("Answering discovery probes on UDP port {port}");
+    }
+    tokio::signal::ctrl_c().await?;
+
+    Ok(())
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use am03127_client::{ClientError, Panel};
+use am03127_commands::{
+    Payload,
+    delete::{DeleteAll, DeletePage, DeleteSchedule},
+    frame::Frame,
+    graphic::GraphicBlock,
+    page::Page,
+    realtime_clock::DateTime,
+    schedule::Schedule,
+};
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Request, State},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+};
+use serde::Serialize;
+
+use crate::{Shared, failure::Action, lock};
+
+type AppState = Arc<Shared>;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Panel error answered with the status and plain text body of the firmware
+struct Error(ClientError);
+
+impl From<ClientError> for Error {
+    fn from(value: ClientError) -> Self {
+        Self(value)
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = match self.0 {
+            ClientError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ClientError::NotFound(_) => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let message = self
+            .0
+            .message()
+            .map_or_else(|| self.0.to_string(), str::to_string);
+        (status, message).into_response()
+    }
+}
+
+#[derive(Serialize)]
+struct BuildInfo {
+    version: String,
+    build_time: String,
+    build_date: String,
+}
+
+/// Builds the same routes as the firmware's `ServerProperties::build_app`.
+pub(crate) fn build_app(state: AppState) -> Router {
+    Router::new()
+        .route(
+            "/page/{id}",
+            get(get_page).post(set_page).delete(delete_page),
+        )
+        .route("/pages", get(get_pages).post(set_pages))
+        .route(
+            "/schedule/{id}",
+            get(get_schedule).post(set_schedule).delete(delete_schedule),
+        )
+        .route("/schedules", get(get_schedules).post(set_schedules))
+        .route("/graphic", post(set_graphic))
+        .route("/clock", post(set_clock))
+        .route("/reset", post(reset))
+        .route(
+            "/ota",
+            put(update_firmware).layer(DefaultBodyLimit::disable()),
+        )
+        .route("/status", get(get_status))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            inject_failures,
+        ))
+        .with_state(state)
+}
+
+/// Logs the request and applies the first matching injected failure.
+async fn inject_failures(State(shared): State<AppState>, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    lock(&shared.requests).push(format!("{method} {path}"));
+
+    let action = {
+        let mut failures = lock(&shared.failures);
+        let index = failures
+            .iter()
+            .position(|failure| failure.matches(&method, &path));
+        index.map(|index| {
+            let action = failures[index].action.clone();
+            if !failures[index].hit() {
+                failures.remove(index);
+            }
+            action
+        })
+    };
+
+    match action {
+        Some(Action::Respond { status, message }) => {
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            (status, message).into_response()
+        }
+        Some(Action::Delay(delay)) => {
+            tokio::time::sleep(delay).await;
+            next.run(request).await
+        }
+        None => next.run(request).await,
+    }
+}
+
+/// Records the frame the firmware writes to the panel for `command`.
+fn send<C: Payload + ?Sized>(shared: &Shared, command: &C) {
+    shared.record_frame(Frame::new(shared.panel_id, command).to_bytes());
+}
+
+// ── Status ────────────────────────────────────────────────────────────────────
+
+async fn get_status(State(shared): State<AppState>) -> Result<Json<BuildInfo>> {
+    let info = shared.panel.get_status().await?;
+    Ok(Json(BuildInfo {
+        version: info.version,
+        build_time: info.build_time,
+        build_date: info.build_date,
+    }))
+}
+
+// ── Clock ─────────────────────────────────────────────────────────────────────
+
+async fn set_clock(State(shared): State<AppState>, Json(dt): Json<DateTime>) -> Result<()> {
+    shared.panel.set_clock(&dt).await?;
+    send(&shared, &dt);
+    Ok(())
+}
+
+// ── Pages ─────────────────────────────────────────────────────────────────────
+
+async fn get_page(State(shared): State<AppState>, Path(id): Path<char>) -> Result<Json<Page>> {
+    match shared.panel.get_page(id).await? {
+        Some(page) => Ok(Json(page)),
+        None => Err(ClientError::NotFound("Page not found".into()).into()),
+    }
+}
+
+async fn set_page(
+    State(shared): State<AppState>,
+    Path(id): Path<char>,
+    Json(mut page): Json<Page>,
+) -> Result<()> {
+    if !am03127_commands::is_page_id_valid(id) {
+        return Err(ClientError::BadRequest("Page ID not valid".into()).into());
+    }
+    // The firmware stores the page under the ID of the path, whatever the body says.
+    page.id = id;
+    shared.panel.set_page(&page).await?;
+    send(&shared, &page);
+    Ok(())
+}
+
+async fn delete_page(State(shared): State<AppState>, Path(id): Path<char>) -> Result<()> {
+    shared.panel.delete_page(id).await?;
+    send(&shared, &DeletePage::new(id));
+    Ok(())
+}
+
+async fn get_pages(State(shared): State<AppState>) -> Result<Json<Vec<Page>>> {
+    Ok(Json(shared.panel.get_pages().await?))
+}
+
+async fn set_pages(State(shared): State<AppState>, Json(pages): Json<Vec<Page>>) -> Result<()> {
+    for page in pages {
+        shared.panel.set_page(&page).await?;
+        send(&shared, &page);
+    }
+    Ok(())
+}
+
+// ── Schedules ─────────────────────────────────────────────────────────────────
+
+async fn get_schedule(
+    State(shared): State<AppState>,
+    Path(id): Path<char>,
+) -> Result<Json<Schedule>> {
+    match shared.panel.get_schedule(id).await? {
+        Some(schedule) => Ok(Json(schedule)),
+        None => Err(ClientError::NotFound("Schedule not found".into()).into()),
+    }
+}
+
+async fn set_schedule(
+    State(shared): State<AppState>,
+    Path(id): Path<char>,
+    Json(mut schedule): Json<Schedule>,
+) -> Result<()> {
+    if !am03127_commands::is_schedule_id_valid(id) {
+        return Err(ClientError::BadRequest("Schedule ID not valid".into()).into());
+    }
+    // The firmware stores the schedule under the ID of the path, whatever the body says.
+    schedule.id = id;
+    shared.panel.set_schedule(&schedule).await?;
+    send(&shared, &schedule);
+    Ok(())
+}
+
+async fn delete_schedule(State(shared): State<AppState>, Path(id): Path<char>) -> Result<()> {
+    shared.panel.delete_schedule(id).await?;
+    send(&shared, &DeleteSchedule::new(id));
+    Ok(())
+}
+
+async fn get_schedules(State(shared): State<AppState>) -> Result<Json<Vec<Schedule>>> {
+    Ok(Json(shared.panel.get_schedules().await?))
+}
+
+async fn set_schedules(
+    State(shared): State<AppState>,
+    Json(schedules): Json<Vec<Schedule>>,
+) -> Result<()> {
+    for schedule in schedules {
+        shared.panel.set_schedule(&schedule).await?;
+        send(&shared, &schedule);
+    }
+    Ok(())
+}
+
+// ── Graphics ──────────────────────────────────────────────────────────────────
+
+async fn set_graphic(
+    State(shared): State<AppState>,
+    Json(block): Json<GraphicBlock>,
+) -> Result<()> {
+    shared.panel.set_graphic(&block).await?;
+    send(&shared, &block);
+    Ok(())
+}
+
+// ── Control ───────────────────────────────────────────────────────────────────
+
+async fn reset(State(shared): State<AppState>) -> Result<()> {
+    shared.panel.reset().await?;
+    send(&shared, &DeleteAll {});
+    Ok(())
+}
+
+async fn update_firmware(State(shared): State<AppState>, firmware: Bytes) -> Result<()> {
+    shared.panel.update_firmware(&firmware).await?;
+    Ok(())
+}
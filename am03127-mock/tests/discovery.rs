@@ -0,0 +1,98 @@
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::Duration,
+};
+
+use am03127_client::Discovery;
+use am03127_mock::MockServer;
+
+/// Discovery over multicast on the loopback interface, without broadcast
+fn loopback(port: u16) -> Discovery {
+    Discovery::new()
+        .with_port(port)
+        .with_interface(Ipv4Addr::LOCALHOST)
+        .with_broadcast(false)
+        .with_timeout(Duration::from_millis(500))
+}
+
+#[tokio::test]
+async fn finds_panels_on_loopback_multicast() {
+    let first = MockServer::builder()
+        .announce("lobby")
+        .discovery_port(0)
+        .start()
+        .await
+        .unwrap();
+    let port = first.discovery_port().unwrap();
+    let second = MockServer::builder()
+        .announce("entrance")
+        .discovery_port(port)
+        .start()
+        .await
+        .unwrap();
+
+    let panels = loopback(port).run().await.unwrap();
+
+    let names: Vec<&str> = panels.iter().map(|panel| panel.name.as_str()).collect();
+    assert_eq!(names, ["entrance", "lobby"]);
+    for (panel, server) in panels.iter().zip([&second, &first]) {
+        assert_eq!(panel.address, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(panel.port, server.address().port());
+        assert_eq!(panel.version, "mock");
+    }
+}
+
+#[tokio::test]
+async fn keeps_panels_sharing_a_name() {
+    let first = MockServer::builder()
+        .announce("am03127")
+        .discovery_port(0)
+        .start()
+        .await
+        .unwrap();
+    let port = first.discovery_port().unwrap();
+    let second = MockServer::builder()
+        .announce("am03127")
+        .discovery_port(port)
+        .start()
+        .await
+        .unwrap();
+
+    let panels = loopback(port).run().await.unwrap();
+
+    let ports: Vec<u16> = panels.iter().map(|panel| panel.port).collect();
+    let mut expected = [first.address().port(), second.address().port()];
+    expected.sort();
+    assert_eq!(ports, expected);
+}
+
+#[tokio::test]
+async fn discovered_panel_is_reachable() {
+    let server = MockServer::builder()
+        .announce("lobby")
+        .discovery_port(0)
+        .start()
+        .await
+        .unwrap();
+
+    let panels = loopback(server.discovery_port().unwrap())
+        .run()
+        .await
+        .unwrap();
+
+    assert_eq!(panels.len(), 1);
+    assert_eq!(panels[0].url(), server.url());
+    let status = panels[0].client().get_status().await.unwrap();
+    assert_eq!(status.version, "mock");
+}
+
+#[tokio::test]
+async fn finds_nothing_without_panels() {
+    let unused = std::net::UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).unwrap();
+    let port = unused.local_addr().unwrap().port();
+    drop(unused);
+
+    let panels = loopback(port).run().await.unwrap();
+
+    assert!(panels.is_empty());
+}
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use am03127_client::{ClientError, PanelClient, PanelClientBuilder};
+use am03127_commands::realtime_clock::DateTime;
+use am03127_mock::{Failure, MockServer};
+use axum::http::Method;
+
+const RETRIES: u32 = 2;
+
+fn client(server: &MockServer) -> PanelClient {
+    PanelClientBuilder::new("mock")
+        .base_url(&server.url())
+        .retries(RETRIES)
+        .backoff(Duration::from_millis(10))
+        .build()
+        .unwrap()
+}
+
+/// Smallest image passing the client's firmware checks
+fn firmware() -> Vec<u8> {
+    let mut image = vec![0u8; 0x120];
+    image[0] = 0xE9;
+    image[0x0C..0x0E].copy_from_slice(&0x0005u16.to_le_bytes());
+    image[0x20..0x24].copy_from_slice(&0xABCD_5432u32.to_le_bytes());
+    image[0x30..0x35].copy_from_slice(b"1.2.3");
+    image[0x50..0x62].copy_from_slice(b"AM03127-Controller");
+    image
+}
+
+#[tokio::test]
+async fn not_found_is_a_missing_page() {
+    let server = MockServer::start().await.unwrap();
+    let client = client(&server);
+
+    server.fail(Failure::status("/page/", 404, "Page not found").times(2));
+    let page = client.get_page('A').await.unwrap();
+    let error = client.delete_page('A').await.unwrap_err();
+
+    assert!(page.is_none());
+    assert!(matches!(&error, ClientError::NotFound(message) if message == "Page not found"));
+    assert_eq!(error.status(), Some(404));
+    assert_eq!(server.requests(), ["GET /page/A", "DELETE /page/A"]);
+}
+
+#[tokio::test]
+async fn server_errors_are_retried_up_to_the_retries() {
+    let server = MockServer::start().await.unwrap();
+    let client = client(&server);
+
+    server.fail(Failure::status("/status", 503, "busy").times(RETRIES));
+    client.get_status().await.unwrap();
+    assert_eq!(server.requests().len(), RETRIES as usize + 1);
+
+    server.fail(Failure::status("/status", 503, "busy"));
+    let error = client.get_status().await.unwrap_err();
+    assert!(error.is_server_error());
+    assert_eq!(error.message(), Some("busy"));
+    assert_eq!(server.requests().len(), 2 * (RETRIES as usize + 1));
+}
+
+#[tokio::test]
+async fn bad_requests_are_not_retried() {
+    let server = MockServer::start().await.unwrap();
+    let client = client(&server);
+
+    server.fail(Failure::status("/clock", 400, "Invalid date").method(Method::POST));
+    let error = client.set_clock(&DateTime::default()).await.unwrap_err();
+
+    assert!(error.is_bad_request());
+    assert_eq!(error.message(), Some("Invalid date"));
+    assert_eq!(server.requests(), ["POST /clock"]);
+}
+
+#[tokio::test]
+async fn firmware_uploads_are_never_retried() {
+    let server = MockServer::start().await.unwrap();
+    let client = client(&server);
+
+    server.fail(Failure::status("/ota", 503, "flash busy").times(1));
+    let error = client.update_firmware(&firmware()).await.unwrap_err();
+
+    assert!(error.is_server_error());
+    assert_eq!(server.requests(), ["PUT /ota"]);
+    assert!(server.panel().firmware().is_none());
+}
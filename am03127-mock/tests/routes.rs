@@ -0,0 +1,143 @@
+use std::time::{Duration, Instant};
+
+use am03127_client::Panel;
+use am03127_commands::{frame::Frame, page::Page};
+use am03127_mock::{Failure, MockServer};
+use axum::http::Method;
+use serde_json::json;
+
+#[tokio::test]
+async fn page_is_stored_under_the_path_id() {
+    let server = MockServer::start().await.unwrap();
+    let panel = server.panel();
+    panel
+        .set_page(&serde_json::from_value(page('A', "first")).unwrap())
+        .await
+        .unwrap();
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/page/B", server.url()))
+        .json(&page('A', "second"))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(response.status().is_success());
+    assert_eq!(panel.get_page('A').await.unwrap().unwrap().message, "first");
+    let stored = panel.get_page('B').await.unwrap().unwrap();
+    assert_eq!((stored.id, stored.message.as_str()), ('B', "second"));
+}
+
+#[tokio::test]
+async fn schedule_is_stored_under_the_path_id() {
+    let server = MockServer::start().await.unwrap();
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/schedule/B", server.url()))
+        .json(&json!({
+            "id": 'A',
+            "from": { "year": 26, "month": 1, "day": 1, "hour": 0, "minute": 0 },
+            "to": { "year": 26, "month": 12, "day": 31, "hour": 23, "minute": 59 },
+            "pages": "A",
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(response.status().is_success(), "{}", response.status());
+    let panel = server.panel();
+    assert!(panel.get_schedule('A').await.unwrap().is_none());
+    assert_eq!(panel.get_schedule('B').await.unwrap().unwrap().id, 'B');
+}
+
+#[tokio::test]
+async fn injected_status_is_served_the_given_times() {
+    let server = MockServer::start().await.unwrap();
+    server.fail(Failure::status("/status", 503, "busy").times(2));
+    let client = reqwest::Client::new();
+
+    let mut statuses = Vec::new();
+    for _ in 0..3 {
+        let response = client
+            .get(format!("{}/status", server.url()))
+            .send()
+            .await
+            .unwrap();
+        statuses.push(response.status().as_u16());
+    }
+
+    assert_eq!(statuses, [503, 503, 200]);
+    assert_eq!(server.requests(), ["GET /status"; 3]);
+}
+
+#[tokio::test]
+async fn injected_failure_only_hits_its_method() {
+    let server = MockServer::start().await.unwrap();
+    server.fail(Failure::status("/page/", 500, "UART error").method(Method::POST));
+    let client = reqwest::Client::new();
+
+    let post = client
+        .post(format!("{}/page/B", server.url()))
+        .json(&page('B', "hello"))
+        .send()
+        .await
+        .unwrap();
+    let get = client
+        .get(format!("{}/pages", server.url()))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(post.status().as_u16(), 500);
+    assert_eq!(post.text().await.unwrap(), "UART error");
+    assert!(get.status().is_success());
+    assert!(server.frames().is_empty());
+}
+
+#[tokio::test]
+async fn injected_delay_holds_the_request() {
+    let server = MockServer::start().await.unwrap();
+    server.fail(Failure::delay("/", Duration::from_millis(300)).times(1));
+    let client = reqwest::Client::new();
+
+    let started = Instant::now();
+    let response = client
+        .get(format!("{}/status", server.url()))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(response.status().is_success());
+    assert!(started.elapsed() >= Duration::from_millis(300));
+}
+
+#[tokio::test]
+async fn page_post_records_the_frame_for_the_panel_id() {
+    let server = MockServer::builder().panel_id(0x05).start().await.unwrap();
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/page/B", server.url()))
+        .json(&page('B', "hello"))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(response.status().is_success());
+    let page: Page = serde_json::from_value(page('B', "hello")).unwrap();
+    let frames = server.take_frames();
+    assert_eq!(frames, [Frame::new(0x05, &page).to_bytes()]);
+    assert!(frames[0].starts_with(b"<ID05><L1><PB>"));
+    assert!(server.frames().is_empty());
+}
+
+fn page(id: char, message: &str) -> serde_json::Value {
+    json!({
+        "line": 1,
+        "id": id,
+        "leading": "immediate",
+        "lagging": "immediate",
+        "waiting_mode_and_speed": "fastest_normal",
+        "waiting_time": 0,
+        "message": message,
+    })
+}
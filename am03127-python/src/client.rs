@@ -0,0 +1,129 @@
+use std::future::Future;
+
+use pyo3::{create_exception, exceptions::PyException, prelude::*};
+use tokio::runtime::Runtime;
+
+use crate::commands::{DateTime, Page, Schedule};
+
+create_exception!(
+    am03127,
+    PanelError,
+    PyException,
+    "Raised when a request to a panel controller fails."
+);
+
+/// Firmware information reported by a panel controller
+#[pyclass(module = "am03127", frozen, get_all)]
+pub struct BuildInfo {
+    version: String,
+    build_time: String,
+    build_date: String,
+}
+
+/// Synchronous HTTP client for a single AM03127 panel.
+///
+/// Every call blocks until the controller answered and releases the GIL
+/// while waiting.
+#[pyclass(module = "am03127")]
+pub struct PanelClient {
+    client: am03127_client::PanelClient,
+    runtime: Runtime,
+}
+
+#[pymethods]
+impl PanelClient {
+    #[new]
+    fn new(address: &str) -> PyResult<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            client: am03127_client::PanelClient::new(address),
+            runtime,
+        })
+    }
+
+    fn get_status(&self, py: Python<'_>) -> PyResult<BuildInfo> {
+        let info = self.block_on(py, self.client.get_status())?;
+        Ok(BuildInfo {
+            version: info.version,
+            build_time: info.build_time,
+            build_date: info.build_date,
+        })
+    }
+
+    fn set_clock(&self, py: Python<'_>, date_time: DateTime) -> PyResult<()> {
+        self.block_on(py, self.client.set_clock(&date_time.0))
+    }
+
+    /// Returns the page with the given ID or `None` if the panel has no such page.
+    fn get_page(&self, py: Python<'_>, id: char) -> PyResult<Option<Page>> {
+        let page = self.block_on(py, self.client.get_page(id))?;
+        Ok(page.map(Page))
+    }
+
+    fn set_page(&self, py: Python<'_>, page: Page) -> PyResult<()> {
+        self.block_on(py, self.client.set_page(&page.0))
+    }
+
+    fn delete_page(&self, py: Python<'_>, id: char) -> PyResult<()> {
+        self.block_on(py, self.client.delete_page(id))
+    }
+
+    fn get_pages(&self, py: Python<'_>) -> PyResult<Vec<Page>> {
+        let pages = self.block_on(py, self.client.get_pages())?;
+        Ok(pages.into_iter().map(Page).collect())
+    }
+
+    fn set_pages(&self, py: Python<'_>, pages: Vec<Page>) -> PyResult<()> {
+        let pages: Vec<_> = pages.into_iter().map(|page| page.0).collect();
+        self.block_on(py, self.client.set_pages(&pages))
+    }
+
+    /// Returns the schedule with the given ID or `None` if the panel has no
+    /// such schedule.
+    fn get_schedule(&self, py: Python<'_>, id: char) -> PyResult<Option<Schedule>> {
+        let schedule = self.block_on(py, self.client.get_schedule(id))?;
+        Ok(schedule.map(Schedule))
+    }
+
+    fn set_schedule(&self, py: Python<'_>, schedule: Schedule) -> PyResult<()> {
+        self.block_on(py, self.client.set_schedule(&schedule.0))
+    }
+
+    fn delete_schedule(&self, py: Python<'_>, id: char) -> PyResult<()> {
+        self.block_on(py, self.client.delete_schedule(id))
+    }
+
+    fn get_schedules(&self, py: Python<'_>) -> PyResult<Vec<Schedule>> {
+        let schedules = self.block_on(py, self.client.get_schedules())?;
+        Ok(schedules.into_iter().map(Schedule).collect())
+    }
+
+    fn set_schedules(&self, py: Python<'_>, schedules: Vec<Schedule>) -> PyResult<()> {
+        let schedules: Vec<_> = schedules.into_iter().map(|schedule| schedule.0).collect();
+        self.block_on(py, self.client.set_schedules(&schedules))
+    }
+
+    /// Deletes all pages, schedules and graphics from the panel.
+    fn reset(&self, py: Python<'_>) -> PyResult<()> {
+        self.block_on(py, self.client.reset())
+    }
+
+    /// Checks and uploads new firmware via OTA. The panel reboots
+    /// automatically after a successful upload.
+    fn update_firmware(&self, py: Python<'_>, firmware: &[u8]) -> PyResult<()> {
+        self.block_on(py, self.client.update_firmware(firmware))
+    }
+}
+
+impl PanelClient {
+    fn block_on<T: Send>(
+        &self,
+        py: Python<'_>,
+        future: impl Future<Output = am03127_client::Result<T>> + Send,
+    ) -> PyResult<T> {
+        py.detach(|| self.runtime.block_on(future))
+            .map_err(|e| PanelError::new_err(e.to_string()))
+    }
+}
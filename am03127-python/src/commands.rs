@@ -0,0 +1,409 @@
+use am03127_commands::{
+    CommandAble, is_page_id_valid, is_schedule_id_valid, markup, page, realtime_clock, schedule,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use serde::{Serialize, de::DeserializeOwned};
+
+/// Panel ID used when no other ID is given, the same the controller uses
+const DEFAULT_PANEL_ID: u8 = 1;
+
+/// Returns the name of a value as used by the REST API
+fn name_of<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+/// Looks up a value by its name as used by the REST API
+fn from_name<T: DeserializeOwned>(kind: &str, name: &str) -> PyResult<T> {
+    serde_json::from_value(serde_json::Value::String(name.into()))
+        .map_err(|_| PyValueError::new_err(format!("unknown {kind} '{name}'")))
+}
+
+fn to_json<T: Serialize>(value: &T) -> PyResult<String> {
+    serde_json::to_string(value).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> PyResult<T> {
+    serde_json::from_str(json).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Wraps one of the effect enums of a page.
+///
+/// Effects are created from their REST API name, e.g. `Leading("scroll_left")`.
+/// Functions taking an effect accept the name as well.
+macro_rules! effect {
+    ($name:ident, $inner:ty, $kind:literal) => {
+        #[pyclass(module = "am03127", frozen, eq)]
+        #[derive(Clone, PartialEq)]
+        pub struct $name(pub $inner);
+
+        #[pymethods]
+        impl $name {
+            #[new]
+            fn new(name: &str) -> PyResult<Self> {
+                from_name($kind, name).map(Self)
+            }
+
+            /// Returns all values in the order of the panel documentation.
+            #[staticmethod]
+            fn all() -> Vec<Self> {
+                <$inner>::ALL.iter().cloned().map(Self).collect()
+            }
+
+            #[getter]
+            fn name(&self) -> String {
+                name_of(&self.0)
+            }
+
+            fn __str__(&self) -> String {
+                self.name()
+            }
+
+            fn __repr__(&self) -> String {
+                format!("{}('{}')", stringify!($name), self.name())
+            }
+        }
+
+        impl $name {
+            /// Extracts the effect from an instance or its name, falling back
+            /// to the default if nothing is given.
+            fn extract(value: Option<&Bound<'_, PyAny>>) -> PyResult<$inner> {
+                let Some(value) = value else {
+                    return Ok(<$inner>::default());
+                };
+                if let Ok(effect) = value.cast::<Self>() {
+                    return Ok(effect.get().0.clone());
+                }
+                from_name($kind, &value.extract::<String>()?)
+            }
+        }
+    };
+}
+
+effect!(Leading, page::Leading, "leading effect");
+effect!(Lagging, page::Lagging, "lagging effect");
+effect!(
+    WaitingModeAndSpeed,
+    page::WaitingModeAndSpeed,
+    "waiting mode"
+);
+
+/// A page of content for the LED panel
+#[pyclass(module = "am03127")]
+#[derive(Clone)]
+pub struct Page(pub page::Page);
+
+#[pymethods]
+impl Page {
+    #[new]
+    #[pyo3(signature = (id, message, leading=None, lagging=None, waiting_mode_and_speed=None, waiting_time=0))]
+    fn new(
+        id: char,
+        message: String,
+        leading: Option<&Bound<'_, PyAny>>,
+        lagging: Option<&Bound<'_, PyAny>>,
+        waiting_mode_and_speed: Option<&Bound<'_, PyAny>>,
+        waiting_time: u8,
+    ) -> PyResult<Self> {
+        Self::validated(page::Page::new(
+            id,
+            message,
+            Leading::extract(leading)?,
+            Lagging::extract(lagging)?,
+            WaitingModeAndSpeed::extract(waiting_mode_and_speed)?,
+            page::WaitingTime::new(waiting_time),
+        ))
+    }
+
+    /// Parses a page from the JSON used by the REST API.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        Self::validated(from_json(json)?)
+    }
+
+    /// Returns the page as JSON used by the REST API.
+    fn to_json(&self) -> PyResult<String> {
+        to_json(&self.0)
+    }
+
+    /// Returns the command sending the page to the given panel.
+    #[pyo3(signature = (panel_id=DEFAULT_PANEL_ID))]
+    fn command(&self, panel_id: u8) -> String {
+        self.0.command(panel_id)
+    }
+
+    /// Returns the message as readable text without formatting tags.
+    fn plain_text(&self) -> String {
+        markup::plain_text(&self.0.message)
+    }
+
+    #[getter]
+    fn id(&self) -> char {
+        self.0.id
+    }
+
+    #[getter]
+    fn message(&self) -> String {
+        self.0.message.clone()
+    }
+
+    #[getter]
+    fn leading(&self) -> Leading {
+        Leading(self.0.leading.clone())
+    }
+
+    #[getter]
+    fn lagging(&self) -> Lagging {
+        Lagging(self.0.lagging.clone())
+    }
+
+    #[getter]
+    fn waiting_mode_and_speed(&self) -> WaitingModeAndSpeed {
+        WaitingModeAndSpeed(self.0.waiting_mode_and_speed.clone())
+    }
+
+    #[getter]
+    fn waiting_time(&self) -> u8 {
+        self.0.waiting_time.value()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Page(id='{}', message={:?}, leading='{}', lagging='{}', waiting_mode_and_speed='{}', waiting_time={})",
+            self.0.id,
+            self.0.message,
+            name_of(&self.0.leading),
+            name_of(&self.0.lagging),
+            name_of(&self.0.waiting_mode_and_speed),
+            self.0.waiting_time.value(),
+        )
+    }
+}
+
+impl Page {
+    fn validated(page: page::Page) -> PyResult<Self> {
+        if !is_page_id_valid(page.id) {
+            return Err(PyValueError::new_err(format!(
+                "page id must be an uppercase ASCII letter (A-Z), got '{}'",
+                page.id
+            )));
+        }
+        markup::validate(&page.message).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self(page))
+    }
+}
+
+/// Start or end of a schedule
+#[pyclass(module = "am03127")]
+#[derive(Clone)]
+pub struct ScheduleDateTime(pub schedule::ScheduleDateTime);
+
+#[pymethods]
+impl ScheduleDateTime {
+    #[new]
+    fn new(year: u8, month: u8, day: u8, hour: u8, minute: u8) -> Self {
+        Self(schedule::ScheduleDateTime::new(
+            year, month, day, hour, minute,
+        ))
+    }
+
+    #[getter]
+    fn year(&self) -> u8 {
+        self.0.year
+    }
+
+    #[getter]
+    fn month(&self) -> u8 {
+        self.0.month
+    }
+
+    #[getter]
+    fn day(&self) -> u8 {
+        self.0.day
+    }
+
+    #[getter]
+    fn hour(&self) -> u8 {
+        self.0.hour
+    }
+
+    #[getter]
+    fn minute(&self) -> u8 {
+        self.0.minute
+    }
+
+    fn __repr__(&self) -> String {
+        let t = &self.0;
+        format!(
+            "ScheduleDateTime(year={}, month={}, day={}, hour={}, minute={})",
+            t.year, t.month, t.day, t.hour, t.minute
+        )
+    }
+}
+
+/// A schedule displaying pages during a time range
+///
+/// `start` and `end` correspond to `from` and `to` of the REST API.
+#[pyclass(module = "am03127")]
+#[derive(Clone)]
+pub struct Schedule(pub schedule::Schedule);
+
+#[pymethods]
+impl Schedule {
+    #[new]
+    fn new(
+        id: char,
+        start: ScheduleDateTime,
+        end: ScheduleDateTime,
+        pages: String,
+    ) -> PyResult<Self> {
+        Self::validated(schedule::Schedule::new(id, start.0, end.0, pages))
+    }
+
+    /// Parses a schedule from the JSON used by the REST API.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        Self::validated(from_json(json)?)
+    }
+
+    /// Returns the schedule as JSON used by the REST API.
+    fn to_json(&self) -> PyResult<String> {
+        to_json(&self.0)
+    }
+
+    /// Returns the command sending the schedule to the given panel.
+    #[pyo3(signature = (panel_id=DEFAULT_PANEL_ID))]
+    fn command(&self, panel_id: u8) -> String {
+        self.0.command(panel_id)
+    }
+
+    #[getter]
+    fn id(&self) -> char {
+        self.0.id
+    }
+
+    #[getter]
+    fn start(&self) -> ScheduleDateTime {
+        ScheduleDateTime(self.0.from.clone())
+    }
+
+    #[getter]
+    fn end(&self) -> ScheduleDateTime {
+        ScheduleDateTime(self.0.to.clone())
+    }
+
+    #[getter]
+    fn pages(&self) -> String {
+        self.0.pages.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Schedule(id='{}', start={}, end={}, pages='{}')",
+            self.0.id,
+            self.start().__repr__(),
+            self.end().__repr__(),
+            self.0.pages
+        )
+    }
+}
+
+impl Schedule {
+    fn validated(schedule: schedule::Schedule) -> PyResult<Self> {
+        if !is_schedule_id_valid(schedule.id) {
+            return Err(PyValueError::new_err(format!(
+                "schedule id must be between A and E, got '{}'",
+                schedule.id
+            )));
+        }
+        if let Some(id) = schedule.pages.chars().find(|&id| !is_page_id_valid(id)) {
+            return Err(PyValueError::new_err(format!(
+                "page id must be an uppercase ASCII letter (A-Z), got '{id}'"
+            )));
+        }
+        Ok(Self(schedule))
+    }
+}
+
+/// Date and time for the panel's real-time clock
+#[pyclass(module = "am03127")]
+#[derive(Clone)]
+pub struct DateTime(pub realtime_clock::DateTime);
+
+#[pymethods]
+impl DateTime {
+    #[new]
+    fn new(year: u8, week: u8, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+        Self(realtime_clock::DateTime {
+            year,
+            week,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    /// Parses a date and time from the JSON used by the REST API.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        from_json(json).map(Self)
+    }
+
+    /// Returns the date and time as JSON used by the REST API.
+    fn to_json(&self) -> PyResult<String> {
+        to_json(&self.0)
+    }
+
+    /// Returns the command setting the clock of the given panel.
+    #[pyo3(signature = (panel_id=DEFAULT_PANEL_ID))]
+    fn command(&self, panel_id: u8) -> String {
+        self.0.command(panel_id)
+    }
+
+    #[getter]
+    fn year(&self) -> u8 {
+        self.0.year
+    }
+
+    #[getter]
+    fn week(&self) -> u8 {
+        self.0.week
+    }
+
+    #[getter]
+    fn month(&self) -> u8 {
+        self.0.month
+    }
+
+    #[getter]
+    fn day(&self) -> u8 {
+        self.0.day
+    }
+
+    #[getter]
+    fn hour(&self) -> u8 {
+        self.0.hour
+    }
+
+    #[getter]
+    fn minute(&self) -> u8 {
+        self.0.minute
+    }
+
+    #[getter]
+    fn second(&self) -> u8 {
+        self.0.second
+    }
+
+    fn __repr__(&self) -> String {
+        let t = &self.0;
+        format!(
+            "DateTime(year={}, week={}, month={}, day={}, hour={}, minute={}, second={})",
+            t.year, t.week, t.month, t.day, t.hour, t.minute, t.second
+        )
+    }
+}
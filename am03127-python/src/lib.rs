@@ -0,0 +1,23 @@
+//! Python bindings for the AM03127 command library and HTTP client.
+//!
+//! Build with `maturin develop` or `maturin build` from this directory.
+
+use pyo3::prelude::*;
+
+mod client;
+mod commands;
+
+#[pymodule]
+fn am03127(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<commands::Leading>()?;
+    m.add_class::<commands::Lagging>()?;
+    m.add_class::<commands::WaitingModeAndSpeed>()?;
+    m.add_class::<commands::Page>()?;
+    m.add_class::<commands::ScheduleDateTime>()?;
+    m.add_class::<commands::Schedule>()?;
+    m.add_class::<commands::DateTime>()?;
+    m.add_class::<client::BuildInfo>()?;
+    m.add_class::<client::PanelClient>()?;
+    m.add("PanelError", m.py().get_type::<client::PanelError>())?;
+    Ok(())
+}
@@ -0,0 +1,116 @@
+import json
+import threading
+import unittest
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import am03127
+
+PAGE = {
+    "line": 1,
+    "id": "A",
+    "leading": "immediate",
+    "lagging": "hold",
+    "waiting_mode_and_speed": "fastest_normal",
+    "waiting_time": 0,
+    "message": "Hello",
+}
+
+
+class MockPanel(BaseHTTPRequestHandler):
+    """Answers like the firmware REST API and records every request."""
+
+    requests = []
+
+    def log_message(self, *args):
+        pass
+
+    def _reply(self, status, body=None):
+        data = json.dumps(body).encode() if body is not None else b""
+        self.send_response(status)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(data)))
+        self.end_headers()
+        self.wfile.write(data)
+
+    def _record(self):
+        length = int(self.headers.get("Content-Length", 0))
+        body = self.rfile.read(length)
+        self.requests.append((self.command, self.path, body))
+        return body
+
+    def do_GET(self):
+        self._record()
+        if self.path == "/status":
+            self._reply(200, {"version": "1.2.3", "build_time": "12:00", "build_date": "2024-01-01"})
+        elif self.path == "/page/A":
+            self._reply(200, PAGE)
+        elif self.path == "/pages":
+            self._reply(200, [PAGE])
+        else:
+            self._reply(404)
+
+    def do_POST(self):
+        self._record()
+        if self.path == "/page/Z":
+            data = b"Storage is full"
+            self.send_response(500)
+            self.send_header("Content-Length", str(len(data)))
+            self.end_headers()
+            self.wfile.write(data)
+        else:
+            self._reply(200)
+
+    def do_DELETE(self):
+        self._record()
+        self._reply(200)
+
+
+class PanelClientTest(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.server = HTTPServer(("127.0.0.1", 0), MockPanel)
+        threading.Thread(target=cls.server.serve_forever, daemon=True).start()
+        cls.client = am03127.PanelClient(f"127.0.0.1:{cls.server.server_port}")
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.server.shutdown()
+
+    def setUp(self):
+        MockPanel.requests.clear()
+
+    def test_get_status(self):
+        info = self.client.get_status()
+        self.assertEqual(info.version, "1.2.3")
+        self.assertEqual(info.build_date, "2024-01-01")
+
+    def test_get_page(self):
+        page = self.client.get_page("A")
+        self.assertEqual(page.message, "Hello")
+        self.assertEqual(page.lagging.name, "hold")
+        self.assertEqual(len(self.client.get_pages()), 1)
+
+    def test_set_page_sends_rest_json(self):
+        self.client.set_page(am03127.Page("B", "Hi", leading="scroll_up"))
+        method, path, body = MockPanel.requests[0]
+        self.assertEqual((method, path), ("POST", "/page/B"))
+        self.assertEqual(json.loads(body)["leading"], "scroll_up")
+
+    def test_delete_page(self):
+        self.client.delete_page("C")
+        self.assertEqual(MockPanel.requests[0][:2], ("DELETE", "/page/C"))
+
+    def test_missing_page_is_none(self):
+        self.assertIsNone(self.client.get_page("Z"))
+
+    def test_errors_raise_panel_error_with_firmware_message(self):
+        with self.assertRaisesRegex(am03127.PanelError, "Storage is full"):
+            self.client.set_page(am03127.Page("Z", "Hi"))
+
+    def test_unreachable_panel_raises_panel_error(self):
+        with self.assertRaises(am03127.PanelError):
+            am03127.PanelClient("127.0.0.1:1").get_status()
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,79 @@
+import json
+import unittest
+
+import am03127
+
+
+class PageTest(unittest.TestCase):
+    def test_command(self):
+        page = am03127.Page("A", "Hello", leading="scroll_left")
+        self.assertEqual(page.command(), "<ID01><L1><PA><FE><MA><WA><FK>Hello3A<E>")
+
+    def test_european_characters_are_escaped(self):
+        page = am03127.Page("B", "Grüße")
+        self.assertIn("Gr<U7C><U5F>e", page.command())
+
+    def test_effects_accept_names_and_instances(self):
+        page = am03127.Page(
+            "C",
+            "Hi",
+            leading=am03127.Leading("curtain_up"),
+            lagging="scroll_right",
+            waiting_mode_and_speed=am03127.WaitingModeAndSpeed("slowest_blinking"),
+        )
+        self.assertEqual(page.leading, am03127.Leading("curtain_up"))
+        self.assertEqual(page.lagging.name, "scroll_right")
+        self.assertEqual(str(page.waiting_mode_and_speed), "slowest_blinking")
+
+    def test_effect_lists(self):
+        self.assertEqual(len(am03127.Leading.all()), 18)
+        self.assertEqual(len(am03127.Lagging.all()), 11)
+        self.assertEqual(len(am03127.WaitingModeAndSpeed.all()), 20)
+
+    def test_invalid_values_are_rejected(self):
+        with self.assertRaises(ValueError):
+            am03127.Page("a", "Hello")
+        with self.assertRaises(ValueError):
+            am03127.Page("A", "<ZZ>Hello")
+        with self.assertRaises(ValueError):
+            am03127.Page("A", "Hello", leading="sideways")
+
+    def test_json_round_trip(self):
+        page = am03127.Page("D", "<KT>", waiting_time=3)
+        data = json.loads(page.to_json())
+        self.assertEqual(data["id"], "D")
+        self.assertEqual(data["leading"], "immediate")
+        self.assertEqual(data["waiting_time"], 3)
+        self.assertEqual(am03127.Page.from_json(page.to_json()).command(), page.command())
+
+    def test_plain_text(self):
+        self.assertEqual(am03127.Page("E", "<AB>Time <KT>").plain_text(), "Time [hh:mm]")
+
+
+class ScheduleTest(unittest.TestCase):
+    def test_command(self):
+        schedule = am03127.Schedule(
+            "A",
+            am03127.ScheduleDateTime(24, 1, 2, 8, 0),
+            am03127.ScheduleDateTime(24, 1, 2, 18, 30),
+            "AB",
+        )
+        self.assertTrue(schedule.command(2).startswith("<ID02><TA>24010208002401021830AB"))
+        self.assertEqual(json.loads(schedule.to_json())["from"]["hour"], 8)
+
+    def test_invalid_values_are_rejected(self):
+        start = am03127.ScheduleDateTime(24, 1, 2, 8, 0)
+        with self.assertRaises(ValueError):
+            am03127.Schedule("F", start, start, "A")
+        with self.assertRaises(ValueError):
+            am03127.Schedule("A", start, start, "a")
+
+
+class DateTimeTest(unittest.TestCase):
+    def test_command(self):
+        date_time = am03127.DateTime(24, 1, 1, 2, 13, 45, 0)
+        self.assertTrue(date_time.command().startswith("<ID01><SC>24010102134500"))
+
+
+if __name__ == "__main__":
+    unittest.main()
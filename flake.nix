@@ -29,6 +29,9 @@
           pkgs.jre21_minimal
           pkgs.cargo-sort
           pkgs.cargo-edit
+          pkgs.wasm-bindgen-cli
+          pkgs.maturin
+          pkgs.python3
           pkgs.python313Packages.atopile-easyeda2kicad
         ];
       };